@@ -1,6 +1,8 @@
+use std::path::PathBuf;
+
 use clap::{Args, Parser};
 
-use crate::groups::Group;
+use crate::groups::{BootloaderType, Group, parse_sha256};
 
 #[derive(Debug, Args)]
 pub struct CommonArgs {
@@ -13,15 +15,13 @@ pub struct CommonArgs {
     pub verbose: bool,
 
     /// Print command results as JSON, if possible
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub json: bool,
 }
 
-/// Command line client for Zephyr's MCUmgr SMP protocol
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-#[command(disable_help_subcommand = true)]
-pub struct App {
+/// Selection and configuration of the connection to the device
+#[derive(Debug, Args)]
+pub struct BackendArgs {
     /// Use the given serial port as backend
     ///
     /// If no argument provided, list all available ports and exit.
@@ -35,14 +35,125 @@ pub struct App {
     #[arg(short, long, verbatim_doc_comment, num_args = 0..=1, default_missing_value = "")]
     pub usb_serial: Option<String>,
 
+    /// Replay a session recorded with `--record` instead of using a device
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["serial", "usb_serial", "record"])]
+    pub replay: Option<PathBuf>,
+
+    /// Use the given pseudo-terminal as backend, like the UART of a Zephyr `native_sim` build
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["serial", "usb_serial", "replay", "tcp", "udp"])]
+    pub pty: Option<PathBuf>,
+
+    /// Use the given Unix domain socket as backend
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["serial", "usb_serial", "replay", "tcp", "udp", "pty"])]
+    pub unix: Option<PathBuf>,
+
+    /// How SMP frames are encoded on the Unix domain socket
+    #[cfg(unix)]
+    #[arg(long, requires = "unix", default_value = "raw")]
+    pub unix_framing: UnixFraming,
+
+    /// Use the serial port behind the given TCP serial bridge as backend, like `ser2net`
+    #[arg(long, value_name = "HOST:PORT", conflicts_with_all = ["serial", "usb_serial", "replay"])]
+    pub tcp: Option<String>,
+
+    /// Speak RFC 2217 with the TCP serial bridge
+    ///
+    /// Allows to configure the remote serial port with `--baud`.
+    #[arg(long, verbatim_doc_comment, requires = "tcp")]
+    pub rfc2217: bool,
+
+    /// Use SMP over UDP with the given address as backend
+    ///
+    /// For example a device with a network connection,
+    /// or a device that is exposed through `mcumgrctl proxy`.
+    #[arg(long, verbatim_doc_comment, value_name = "HOST:PORT", conflicts_with_all = ["serial", "usb_serial", "replay", "tcp"])]
+    pub udp: Option<String>,
+
     /// Serial port baud rate
-    #[arg(short, long, default_value_t = 115200)]
-    pub baud: u32,
+    ///
+    /// Use `auto` to detect the baud rate of the device.
+    #[arg(short, long, verbatim_doc_comment, default_value_t = BaudRate::Fixed(115200))]
+    pub baud: BaudRate,
+
+    /// Maximum line length of the serial transport (in bytes)
+    ///
+    /// Must match the serial frame size of the device (Zephyr: `MCUMGR_SERIAL_MAX_FRAME`).
+    #[arg(long, verbatim_doc_comment, default_value_t = 127, value_parser = clap::value_parser!(u16).range(7..))]
+    pub serial_mtu: u16,
+
+    /// Accept serial frames that are terminated with `\r\n` instead of `\n`
+    ///
+    /// Required for devices or bridges that translate line endings.
+    #[arg(long, verbatim_doc_comment)]
+    pub serial_tolerate_crlf: bool,
 
     /// Communication timeout (in ms)
     #[arg(short, long, default_value_t = 10000)]
     pub timeout: u64,
 
+    /// Print the device's console output that shares the serial port with SMP
+    #[arg(long)]
+    pub show_console: bool,
+
+    /// Record all SMP frames exchanged with the device into a session log
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+}
+
+impl BackendArgs {
+    /// Whether any backend was selected.
+    pub fn is_selected(&self) -> bool {
+        #[cfg(unix)]
+        if self.pty.is_some() || self.unix.is_some() {
+            return true;
+        }
+        self.serial.is_some()
+            || self.usb_serial.is_some()
+            || self.replay.is_some()
+            || self.tcp.is_some()
+            || self.udp.is_some()
+    }
+}
+
+/// Command line client for Zephyr's MCUmgr SMP protocol
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+#[command(disable_help_subcommand = true)]
+pub struct App {
+    /// Connection to the device
+    #[command(flatten)]
+    pub backend: BackendArgs,
+
+    /// Force the SMP protocol version (1 or 2)
+    ///
+    /// If missing, use v2 if the device supports it, otherwise v1.
+    #[arg(long, verbatim_doc_comment, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub smp_version: Option<u8>,
+
+    /// Keep printing console output for this long after the command finished (in ms)
+    ///
+    /// Useful to capture the boot log after a `firmware update`.
+    #[arg(
+        long,
+        verbatim_doc_comment,
+        requires = "show_console",
+        default_value_t = 0
+    )]
+    pub console_linger: u64,
+
+    /// Print every SMP frame exchanged with the device
+    #[arg(long)]
+    pub trace: bool,
+
+    /// Print statistics about the link to the device after the command
+    ///
+    /// Shows the transferred frames and bytes, the encoding overhead,
+    /// transmission errors and the round-trip times of the requests.
+    #[arg(long, verbatim_doc_comment)]
+    pub stats: bool,
+
     /// Settings that customize runtime behaviour
     #[command(flatten)]
     pub common: CommonArgs,
@@ -51,5 +162,189 @@ pub struct App {
     ///
     /// If missing, run a connection test
     #[command(subcommand)]
-    pub group: Option<Group>,
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Expose the device to other machines via SMP over UDP
+    Proxy(ProxyArgs),
+    #[command(flatten)]
+    Group(Group),
+    /// Measure latency and throughput to find the optimal frame size
+    Bench(BenchArgs),
+    /// Diagnose connection problems and print configuration hints
+    ///
+    /// Without a backend, all USB serial ports are checked.
+    #[command(verbatim_doc_comment)]
+    Doctor(DoctorArgs),
+    /// Collect everything about the device, for example for support tickets
+    ///
+    /// Use `--json` to store the snapshot in a file.
+    #[command(verbatim_doc_comment)]
+    Inventory {
+        #[command(subcommand)]
+        command: Option<InventoryCommand>,
+    },
+    /// Operations on many devices at once
+    Fleet {
+        #[command(subcommand)]
+        command: FleetCommand,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ProxyArgs {
+    /// The local address to accept SMP requests on
+    #[arg(short, long, value_name = "HOST:PORT", default_value = "0.0.0.0:1337")]
+    pub listen: String,
+
+    /// Connection to the device
+    ///
+    /// If missing, use the backend given before the `proxy` command.
+    #[command(flatten)]
+    pub backend: BackendArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// The scratch file to upload to and download from
+    ///
+    /// MCUmgr cannot delete files, so the file remains on the device.
+    #[arg(
+        short,
+        long,
+        verbatim_doc_comment,
+        default_value = "/lfs/mcumgr_bench.bin"
+    )]
+    pub file: String,
+    /// Upload to the secondary image slot instead of a file
+    ///
+    /// The slot gets erased before and after the benchmark.
+    /// Downloads are not measured.
+    #[arg(long, verbatim_doc_comment, conflicts_with = "file")]
+    pub image_slot: bool,
+    /// The SMP frame sizes to measure, separated by commas
+    ///
+    /// If missing, use powers of two up to the buffer size of the device.
+    #[arg(long, verbatim_doc_comment, value_delimiter = ',')]
+    pub frame_sizes: Vec<usize>,
+    /// The number of bytes to transfer per measurement
+    #[arg(long, default_value_t = 32 * 1024)]
+    pub size: usize,
+    /// The number of echo requests to measure the latency with
+    #[arg(long, default_value_t = 20)]
+    pub echo_count: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    /// How long to listen for console output on each serial port (in ms)
+    #[arg(long, default_value_t = 1000)]
+    pub listen_time: u64,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum InventoryCommand {
+    /// Compare two snapshots created with `inventory --json`
+    Diff {
+        /// The old snapshot file. '-' for stdin.
+        old: String,
+        /// The new snapshot file. '-' for stdin.
+        new: String,
+    },
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum FleetCommand {
+    /// Perform a firmware update on all matching USB serial devices in parallel
+    Update {
+        /// The firmware image file to update to. '-' for stdin.
+        firmware_file: String,
+        /// A regex that matches `vid:pid` or `vid:pid:iface` of the devices
+        ///
+        /// May match multiple ports, unlike the global `--usb-serial`.
+        /// If missing, use the global `--usb-serial`.
+        #[arg(short, long, verbatim_doc_comment)]
+        usb_serial: Option<String>,
+        /// Specify the bootloader type
+        ///
+        /// Auto-detect if not specified
+        #[arg(short, long)]
+        bootloader: Option<BootloaderType>,
+        /// Do not reboot after the update
+        #[arg(long)]
+        skip_reboot: bool,
+        /// Skip test boot and confirm directly
+        #[arg(long)]
+        force_confirm: bool,
+        /// Prevent firmware downgrades
+        #[arg(long)]
+        upgrade_only: bool,
+        /// SHA-256 checksum of the image file
+        #[arg(long, value_parser=parse_sha256)]
+        checksum: Option<[u8; 32]>,
+    },
+}
+
+/// The framing of SMP frames on a Unix domain socket.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum UnixFraming {
+    /// Raw SMP frames
+    Raw,
+    /// The base64 line encoding of SMP over UART
+    Serial,
+}
+
+#[cfg(unix)]
+impl From<UnixFraming> for mcumgr_toolkit::transport::unix::UnixSocketFraming {
+    fn from(value: UnixFraming) -> Self {
+        match value {
+            UnixFraming::Raw => Self::Raw,
+            UnixFraming::Serial => Self::Serial,
+        }
+    }
+}
+
+/// The baud rate of the serial port.
+#[derive(Debug, Clone, Copy)]
+pub enum BaudRate {
+    /// Detect the baud rate of the device
+    Auto,
+    /// Use the given baud rate
+    Fixed(u32),
+}
+
+impl std::str::FromStr for BaudRate {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            Ok(Self::Auto)
+        } else {
+            s.parse().map(Self::Fixed)
+        }
+    }
+}
+
+impl std::fmt::Display for BaudRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => f.write_str("auto"),
+            Self::Fixed(baud_rate) => baud_rate.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::App;
+
+    #[test]
+    fn verify_app() {
+        App::command().debug_assert();
+    }
 }
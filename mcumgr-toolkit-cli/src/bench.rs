@@ -0,0 +1,86 @@
+use console::style;
+use indicatif::{DecimalBytes, MultiProgress};
+use mcumgr_toolkit::client::bench::{BenchParams, BenchTarget};
+
+use crate::{
+    args::{BenchArgs, CommonArgs},
+    client::Client,
+    errors::CliError,
+    progress::StepProgressHandler,
+};
+
+fn format_throughput(bytes_per_second: f64) -> String {
+    format!("{}/s", DecimalBytes(bytes_per_second as u64))
+}
+
+pub fn run(
+    client: &Client,
+    multiprogress: &MultiProgress,
+    common: CommonArgs,
+    args: BenchArgs,
+) -> Result<(), CliError> {
+    let client = client.get()?;
+
+    let params = BenchParams {
+        target: if args.image_slot {
+            BenchTarget::ImageSlot
+        } else {
+            BenchTarget::File(args.file)
+        },
+        frame_sizes: args.frame_sizes,
+        transfer_size: args.size,
+        echo_count: args.echo_count,
+    };
+
+    let result = if common.quiet {
+        client.bench(&params, None)
+    } else {
+        let mut progress_handler = StepProgressHandler::new(multiprogress);
+        client.bench(
+            &params,
+            Some(&mut move |step, progress| progress_handler.update(&step.to_string(), progress)),
+        )
+    }?;
+
+    if common.json {
+        let json_str = serde_json::to_string_pretty(&result).map_err(CliError::JsonEncodeError)?;
+        println!("{json_str}");
+        return Ok(());
+    }
+
+    println!();
+    if let Some(echo) = &result.echo {
+        println!(
+            "Echo latency: min {:.1} ms, median {:.1} ms, max {:.1} ms ({} requests)",
+            echo.min_ms, echo.median_ms, echo.max_ms, echo.count
+        );
+        println!();
+    }
+
+    println!("{:>10}  {:>12}", "Frame size", "Upload");
+    for run in &result.upload {
+        let line = format!(
+            "{:>10}  {:>12}",
+            run.frame_size,
+            format_throughput(run.bytes_per_second)
+        );
+        if Some(run.frame_size) == result.optimal_frame_size {
+            println!("{}", style(line).green());
+        } else {
+            println!("{line}");
+        }
+    }
+
+    if let Some(download) = result.download_bytes_per_second {
+        println!();
+        println!("Download: {}", format_throughput(download));
+    }
+
+    if let Some(optimal_frame_size) = result.optimal_frame_size {
+        println!();
+        println!("Optimal frame size: {optimal_frame_size}");
+    }
+    println!();
+
+    Ok(())
+}
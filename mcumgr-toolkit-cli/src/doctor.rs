@@ -0,0 +1,604 @@
+use std::{
+    io::{ErrorKind, Read},
+    time::{Duration, Instant},
+};
+
+use console::style;
+use mcumgr_toolkit::{
+    MCUmgrClient, MCUmgrGroup,
+    bootloader::{BootloaderInfo, MCUbootMode},
+    client::{
+        COMMON_BAUD_RATES, MCUmgrClientError, SerialProbeError, SerialProbeResult,
+        find_usb_serial_port, probe_serial_port,
+    },
+    commands::McuMgrCommand,
+    connection::ExecuteError,
+    transport::ReceiveError,
+};
+use serialport::SerialPortType;
+
+use crate::{
+    args::{BackendArgs, BaudRate, DoctorArgs},
+    client::Client,
+    errors::CliError,
+    formatting::describe_error,
+    serial_transport_builder,
+};
+
+/// Devices with smaller SMP buffers work, but transfer files and images slowly.
+const SMALL_BUFFER_SIZE: u32 = 512;
+
+/// A serial port that should be diagnosed.
+struct PortCandidate {
+    name: String,
+    description: String,
+    /// Whether the user selected this port explicitly
+    selected: bool,
+}
+
+/// What the device sends on its own, without being asked.
+enum Traffic {
+    Silent,
+    Console { lines: usize },
+    SmpFrames,
+    Garbage,
+}
+
+fn classify_traffic(data: &[u8]) -> Traffic {
+    if data.is_empty() {
+        return Traffic::Silent;
+    }
+
+    // Start markers of the SMP over UART frames and their continuations
+    if data
+        .windows(2)
+        .any(|marker| marker == [0x06, 0x09] || marker == [0x04, 0x14])
+    {
+        return Traffic::SmpFrames;
+    }
+
+    // Leave some room for the ANSI escape codes of colored log output
+    let printable = data
+        .iter()
+        .filter(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+        .count();
+    if printable * 10 >= data.len() * 9 {
+        let lines = data
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .count();
+        Traffic::Console { lines }
+    } else {
+        Traffic::Garbage
+    }
+}
+
+/// A command that is used to check whether a group is enabled on the device.
+///
+/// The requests are harmless: they either only read,
+/// or get rejected because of their empty payload.
+struct GroupProbe {
+    name: &'static str,
+    group_id: u16,
+    command_id: u8,
+    write: bool,
+    kconfig: &'static str,
+    /// The `mcumgrctl` command that requires this group
+    required_by: Option<&'static str>,
+}
+
+impl McuMgrCommand for GroupProbe {
+    type Payload = ciborium::Value;
+    type Response = ciborium::Value;
+
+    fn is_write_operation(&self) -> bool {
+        self.write
+    }
+
+    fn group_id(&self) -> u16 {
+        self.group_id
+    }
+
+    fn command_id(&self) -> u8 {
+        self.command_id
+    }
+
+    fn data(&self) -> &ciborium::Value {
+        const EMPTY: &ciborium::Value = &ciborium::Value::Map(vec![]);
+        EMPTY
+    }
+}
+
+const GROUP_PROBES: &[GroupProbe] = &[
+    GroupProbe {
+        name: "image",
+        group_id: MCUmgrGroup::MGMT_GROUP_ID_IMAGE as u16,
+        command_id: 0,
+        write: false,
+        kconfig: "CONFIG_MCUMGR_GRP_IMG",
+        required_by: Some("mcumgrctl image"),
+    },
+    GroupProbe {
+        name: "stat",
+        group_id: MCUmgrGroup::MGMT_GROUP_ID_STAT as u16,
+        command_id: 1,
+        write: false,
+        kconfig: "CONFIG_MCUMGR_GRP_STAT",
+        required_by: None,
+    },
+    GroupProbe {
+        name: "settings",
+        group_id: MCUmgrGroup::MGMT_GROUP_ID_SETTINGS as u16,
+        command_id: 0,
+        write: false,
+        kconfig: "CONFIG_MCUMGR_GRP_SETTINGS",
+        required_by: None,
+    },
+    GroupProbe {
+        name: "fs",
+        group_id: MCUmgrGroup::MGMT_GROUP_ID_FS as u16,
+        command_id: 1,
+        write: false,
+        kconfig: "CONFIG_MCUMGR_GRP_FS",
+        required_by: Some("mcumgrctl fs"),
+    },
+    GroupProbe {
+        name: "shell",
+        group_id: MCUmgrGroup::MGMT_GROUP_ID_SHELL as u16,
+        command_id: 0,
+        write: true,
+        kconfig: "CONFIG_MCUMGR_GRP_SHELL",
+        required_by: Some("mcumgrctl shell"),
+    },
+    GroupProbe {
+        name: "enum",
+        group_id: MCUmgrGroup::MGMT_GROUP_ID_ENUM as u16,
+        command_id: 0,
+        write: false,
+        kconfig: "CONFIG_MCUMGR_GRP_ENUM",
+        required_by: None,
+    },
+];
+
+/// Detects the baud rate and connects with the serial transport settings of the command line.
+fn probe_client(
+    serial: Box<dyn serialport::SerialPort>,
+    args: &BackendArgs,
+) -> Result<(MCUmgrClient, SerialProbeResult), SerialProbeError> {
+    let (serial, result) =
+        probe_serial_port(serial, COMMON_BAUD_RATES, &serial_transport_builder(args))?;
+
+    let client = MCUmgrClient::new_from_transport(serial_transport_builder(args).build(serial));
+    client
+        .set_timeout(Duration::from_millis(args.timeout))
+        .map_err(SerialProbeError::SetTimeoutFailed)?;
+    if let Some(frame_size) = result.frame_size {
+        client.set_frame_size(frame_size);
+    }
+
+    Ok((client, result))
+}
+
+fn is_timeout(err: &MCUmgrClientError) -> bool {
+    matches!(
+        err,
+        MCUmgrClientError::ExecuteError(ExecuteError::ReceiveFailed(
+            ReceiveError::TransportError(e)
+        )) if e.kind() == ErrorKind::TimedOut
+    )
+}
+
+/// Collects the results of the checks of one connection and prints the diagnosis.
+struct Report {
+    failed: bool,
+    hints: Vec<String>,
+}
+
+impl Report {
+    fn new(title: &str) -> Self {
+        println!();
+        println!("{}", style(title).bold());
+        Self {
+            failed: false,
+            hints: vec![],
+        }
+    }
+
+    fn ok(&self, msg: impl AsRef<str>) {
+        println!("  {} {}", style("✔").green(), msg.as_ref());
+    }
+
+    fn info(&self, msg: impl AsRef<str>) {
+        println!("  {} {}", style("-").dim(), msg.as_ref());
+    }
+
+    fn warn(&mut self, msg: impl AsRef<str>, hint: Option<String>) {
+        println!("  {} {}", style("!").yellow(), msg.as_ref());
+        self.hints.extend(hint);
+    }
+
+    fn fail(&mut self, msg: impl AsRef<str>, hint: Option<String>) {
+        println!("  {} {}", style("✘").red(), msg.as_ref());
+        self.failed = true;
+        self.hints.extend(hint);
+    }
+
+    fn finish(self) {
+        println!();
+        if self.hints.is_empty() {
+            if self.failed {
+                println!("  No diagnosis available, see the errors above.");
+            } else {
+                println!("  No problems found.");
+            }
+        } else {
+            println!("  {}", style("Diagnosis:").bold());
+            for hint in self.hints {
+                println!("  - {hint}");
+            }
+        }
+    }
+}
+
+fn check_device(client: &MCUmgrClient, report: &mut Report) {
+    match client.os_mcumgr_parameters() {
+        Ok(params) => {
+            let msg = format!(
+                "MCUmgr parameters: buffer size {}, buffer count {}",
+                params.buf_size, params.buf_count
+            );
+            if params.buf_size < SMALL_BUFFER_SIZE {
+                report.warn(
+                    msg,
+                    Some(format!(
+                        "The SMP buffer of the device is small, which makes transfers slow. Increase `CONFIG_MCUMGR_TRANSPORT_NETBUF_SIZE` to at least {SMALL_BUFFER_SIZE}."
+                    )),
+                );
+            } else {
+                report.ok(msg);
+            }
+        }
+        Err(e) if e.command_not_supported() => report.fail(
+            "MCUmgr parameters: not supported",
+            Some("mcumgrctl has to fall back to a slow default frame size. Make sure that `CONFIG_MCUMGR_GRP_OS_MCUMGR_PARAMS` is enabled.".to_string()),
+        ),
+        Err(e) => report.fail(format!("MCUmgr parameters: {}", describe_error(&e)), None),
+    }
+
+    match client.os_bootloader_info() {
+        Ok(BootloaderInfo::MCUboot { mode, .. }) => {
+            let mode = MCUbootMode::from_repr(mode)
+                .map(|mode| mode.to_string())
+                .unwrap_or_else(|| mode.to_string());
+            report.ok(format!("Bootloader: MCUboot ({mode})"));
+        }
+        Ok(BootloaderInfo::Unknown { name }) => report.ok(format!("Bootloader: {name}")),
+        Err(e) if e.command_not_supported() => report.fail(
+            "Bootloader information: not supported",
+            Some("`mcumgrctl firmware update` needs to know the bootloader. Make sure that `CONFIG_MCUMGR_GRP_OS_BOOTLOADER_INFO` is enabled.".to_string()),
+        ),
+        Err(e) => report.fail(format!("Bootloader information: {}", describe_error(&e)), None),
+    }
+
+    match client.image_slot_info() {
+        Ok(images) => {
+            let images = images
+                .iter()
+                .map(|image| {
+                    let slots = image
+                        .slots
+                        .iter()
+                        .map(|slot| slot.slot.to_string())
+                        .collect::<Vec<_>>();
+                    format!("image {} with slots {}", image.image, slots.join(", "))
+                })
+                .collect::<Vec<_>>();
+            report.ok(format!("Image slots: {}", images.join("; ")));
+        }
+        Err(e) if e.command_not_supported() => report.warn(
+            "Image slot information: not supported",
+            Some("Make sure that `CONFIG_MCUMGR_GRP_IMG_SLOT_INFO` is enabled to query the image slots.".to_string()),
+        ),
+        Err(e) => report.fail(format!("Image slot information: {}", describe_error(&e)), None),
+    }
+
+    for probe in GROUP_PROBES {
+        match client.raw_command(probe) {
+            Ok(_) => report.ok(format!("Group {}: available", probe.name)),
+            Err(e) if e.command_not_supported() => {
+                let hint = probe.required_by.map(|command| {
+                    format!("`{command}` requires `{}` to be enabled.", probe.kconfig)
+                });
+                let msg = format!("Group {}: not supported", probe.name);
+                if hint.is_some() {
+                    report.fail(msg, hint);
+                } else {
+                    report.info(msg);
+                }
+            }
+            Err(e) if is_timeout(&e) => report.fail(
+                format!("Group {}: no response", probe.name),
+                Some(format!(
+                    "The device stopped responding while probing the `{}` group. Check its console output for a crash.",
+                    probe.name
+                )),
+            ),
+            // The device rejected the empty request, so the group exists
+            Err(MCUmgrClientError::ExecuteError(ExecuteError::ErrorResponse(_))) => {
+                report.ok(format!("Group {}: available", probe.name))
+            }
+            Err(e) => report.fail(format!("Group {}: {}", probe.name, describe_error(&e)), None),
+        }
+    }
+}
+
+/// Diagnoses a device that is connected through a non-serial backend.
+pub fn run(client: &Client) -> Result<(), CliError> {
+    let client = client.get()?;
+
+    let mut report = Report::new("Checking device");
+
+    match client.check_connection() {
+        Ok(()) => report.ok("Device responds to SMP requests"),
+        // The device answered, it just cannot echo
+        Err(e) if e.command_not_supported() => report.fail(
+            "Echo: not supported",
+            Some("mcumgrctl uses echo requests to check the connection. Make sure that `CONFIG_MCUMGR_GRP_OS_ECHO` is enabled.".to_string()),
+        ),
+        Err(e) => {
+            report.fail(
+                format!("Device does not respond: {}", describe_error(&e)),
+                Some(
+                    "Make sure that the device is running and that SMP is enabled on the selected transport."
+                        .to_string(),
+                ),
+            );
+            report.finish();
+            return Ok(());
+        }
+    }
+
+    check_device(client, &mut report);
+    report.finish();
+
+    Ok(())
+}
+
+/// Whether `mcumgrctl doctor` should diagnose serial ports instead of the selected backend.
+pub fn uses_serial_ports(args: &BackendArgs) -> bool {
+    #[cfg(unix)]
+    if args.pty.is_some() || args.unix.is_some() {
+        return false;
+    }
+
+    args.replay.is_none() && args.tcp.is_none() && args.udp.is_none()
+}
+
+fn port_candidates(args: &BackendArgs) -> Result<Vec<PortCandidate>, CliError> {
+    if let Some(port_name) = args.serial.as_ref().filter(|name| !name.is_empty()) {
+        return Ok(vec![PortCandidate {
+            name: port_name.clone(),
+            description: String::new(),
+            selected: true,
+        }]);
+    }
+
+    if let Some(identifier) = args.usb_serial.as_ref().filter(|id| !id.is_empty()) {
+        let port = find_usb_serial_port(identifier)?;
+        return Ok(vec![PortCandidate {
+            name: port.port_name,
+            description: port.identifier,
+            selected: true,
+        }]);
+    }
+
+    let ports = serialport::available_ports()
+        .map_err(CliError::ListSerialPortsFailed)?
+        .into_iter()
+        .filter_map(|port| match port.port_type {
+            SerialPortType::UsbPort(info) => {
+                let mut description = format!("{:04x}:{:04x}", info.vid, info.pid);
+                if let Some(product) = info.product {
+                    description.push(' ');
+                    description.push_str(&product);
+                }
+                Some(PortCandidate {
+                    name: port.port_name,
+                    description,
+                    selected: true,
+                })
+            }
+            // Probing all the legacy ports of a PC takes ages
+            _ if args.usb_serial.is_some() => None,
+            _ => Some(PortCandidate {
+                name: port.port_name,
+                description: String::new(),
+                selected: false,
+            }),
+        })
+        .collect();
+
+    Ok(ports)
+}
+
+fn listen(serial: &mut dyn Read, duration: Duration) -> std::io::Result<Vec<u8>> {
+    let start = Instant::now();
+    let mut data = vec![];
+    let mut buffer = [0; 256];
+
+    while start.elapsed() < duration {
+        match serial.read(&mut buffer) {
+            Ok(received) => data.extend_from_slice(&buffer[..received]),
+            Err(e) if e.kind() == ErrorKind::TimedOut => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(data)
+}
+
+fn check_port(port: &PortCandidate, args: &BackendArgs, doctor_args: &DoctorArgs) {
+    let title = if port.description.is_empty() {
+        format!("Checking serial port {}", port.name)
+    } else {
+        format!("Checking serial port {} ({})", port.name, port.description)
+    };
+    let mut report = Report::new(&title);
+
+    let listen_baud_rate = match args.baud {
+        BaudRate::Fixed(baud_rate) => baud_rate,
+        BaudRate::Auto => COMMON_BAUD_RATES[0],
+    };
+
+    let mut serial = match serialport::new(&port.name, listen_baud_rate)
+        .timeout(Duration::from_millis(100))
+        .open()
+    {
+        Ok(serial) => {
+            report.ok("Port opened");
+            serial
+        }
+        Err(e) => {
+            let hint = match e.kind() {
+                serialport::ErrorKind::Io(ErrorKind::PermissionDenied) => {
+                    if cfg!(target_os = "linux") {
+                        "Permission denied. Add your user to the `dialout` group (`sudo usermod -aG dialout $USER`) and log in again."
+                    } else {
+                        "Permission denied. Make sure that your user is allowed to access serial ports."
+                    }
+                }
+                serialport::ErrorKind::NoDevice => {
+                    "The port is missing or used by another program, like a serial terminal. Close all other programs that use the port."
+                }
+                _ => "Make sure that the port exists and is not used by another program.",
+            };
+            report.fail(
+                format!("Failed to open port: {}", describe_error(&e)),
+                Some(hint.to_string()),
+            );
+            report.finish();
+            return;
+        }
+    };
+
+    let traffic = match listen(&mut serial, Duration::from_millis(doctor_args.listen_time)) {
+        Ok(data) => classify_traffic(&data),
+        Err(e) => {
+            report.fail(
+                format!("Failed to read from port: {}", describe_error(&e)),
+                None,
+            );
+            report.finish();
+            return;
+        }
+    };
+    match traffic {
+        Traffic::Silent => report.info(format!(
+            "No unsolicited data received at {listen_baud_rate} baud"
+        )),
+        Traffic::Console { lines } => report.ok(format!(
+            "Console output received at {listen_baud_rate} baud ({lines} lines)"
+        )),
+        Traffic::SmpFrames => report.warn(
+            format!("Unsolicited SMP frames received at {listen_baud_rate} baud"),
+            Some("Another program seems to talk to the device. Close all other programs that use the port.".to_string()),
+        ),
+        Traffic::Garbage => report.warn(
+            format!("Unreadable data received at {listen_baud_rate} baud"),
+            None,
+        ),
+    }
+
+    let (client, result) = match probe_client(serial, args) {
+        Ok(probed) => probed,
+        Err(SerialProbeError::NoResponse { .. }) => {
+            let hint = match traffic {
+                Traffic::Console { .. } => {
+                    "The device prints console output, but does not answer SMP requests. Make sure that `CONFIG_MCUMGR_TRANSPORT_UART` is enabled and that the `zephyr,uart-mcumgr` chosen node points to this UART."
+                }
+                Traffic::Garbage => {
+                    "The device sends data at a baud rate that is not supported by mcumgrctl. Check the `current-speed` property of the UART in the devicetree."
+                }
+                Traffic::Silent | Traffic::SmpFrames => {
+                    "Make sure that the device is running and that `CONFIG_MCUMGR_TRANSPORT_UART` is enabled. If the device uses a different serial frame size, pass it with `--serial-mtu` (Zephyr: `MCUMGR_SERIAL_MAX_FRAME`)."
+                }
+            };
+            report.fail(
+                "Device does not respond to SMP requests at any common baud rate",
+                Some(hint.to_string()),
+            );
+            report.finish();
+            return;
+        }
+        Err(e @ SerialProbeError::SetBaudRateFailed { .. }) => {
+            report.fail(
+                format!("Failed to probe baud rates: {}", describe_error(&e)),
+                Some("The port does not support all common baud rates. For the pseudo-terminal of a simulated device, use `--pty` instead of `--serial`.".to_string()),
+            );
+            report.finish();
+            return;
+        }
+        Err(e) => {
+            report.fail(
+                format!("Failed to probe baud rates: {}", describe_error(&e)),
+                None,
+            );
+            report.finish();
+            return;
+        }
+    };
+
+    let msg = format!(
+        "Device responds to SMP requests at {} baud",
+        result.baud_rate
+    );
+    match args.baud {
+        BaudRate::Fixed(baud_rate) if baud_rate != result.baud_rate => report.warn(
+            msg,
+            Some(format!(
+                "The device does not use {baud_rate} baud. Pass `--baud {}` or `--baud auto`.",
+                result.baud_rate
+            )),
+        ),
+        _ => report.ok(msg),
+    }
+
+    check_device(&client, &mut report);
+    report.finish();
+}
+
+/// Diagnoses the selected serial port, or all available ones.
+pub fn run_serial_ports(args: &BackendArgs, doctor_args: &DoctorArgs) -> Result<(), CliError> {
+    let ports = port_candidates(args)?;
+
+    if ports.is_empty() {
+        println!();
+        println!("No serial ports available.");
+        println!(
+            "  - Make sure that the device is connected and that its USB driver is installed."
+        );
+        println!();
+        return Ok(());
+    }
+
+    let mut skipped = vec![];
+    for port in &ports {
+        if port.selected {
+            check_port(port, args, doctor_args);
+        } else {
+            skipped.push(port.name.as_str());
+        }
+    }
+
+    if !skipped.is_empty() {
+        println!();
+        println!(
+            "Skipped serial ports that are not connected through USB: {}",
+            skipped.join(", ")
+        );
+        println!("  - Select one with `--serial <PORT>` to check it.");
+    }
+    println!();
+
+    Ok(())
+}
@@ -3,8 +3,12 @@ use thiserror::Error;
 
 use mcumgr_toolkit::{
     Errno,
-    client::{FirmwareUpdateError, MCUmgrClientError, UsbSerialError},
+    client::{
+        DeviceSnapshotError, FirmwareUpdateError, MCUmgrClientError, SerialProbeError,
+        UsbSerialError, bench::BenchError, fleet::FleetOpenError, time_sync::DriftError,
+    },
     mcuboot::ImageParseError,
+    transport::udp::ProxyError,
 };
 
 /// Possible CLI errors.
@@ -23,7 +27,7 @@ pub enum CliError {
     // #[diagnostic(code(mcumgrctl::set_timeout_failed))]
     // SetTimeoutFailed(#[source] Box<dyn miette::Diagnostic + Send + Sync + 'static>),
     #[error("Command execution failed")]
-    #[diagnostic(code(mcumgrctl::execution_failed))]
+    #[diagnostic(code(mcumgrctl::execution_failed), forward(0))]
     CommandExecutionFailed(#[from] MCUmgrClientError),
     #[error("Json encode failed")]
     #[diagnostic(code(mcumgrctl::json_encode))]
@@ -50,6 +54,63 @@ pub enum CliError {
     #[diagnostic(code(mcumgrctl::image_parse))]
     ImageParseFailed(#[from] ImageParseError),
     #[error("Firmware update failed")]
-    #[diagnostic(code(mcumgrctl::firmware_update))]
+    #[diagnostic(code(mcumgrctl::firmware_update), forward(0))]
     FirmwareUpdateFailed(#[from] FirmwareUpdateError),
+    #[error("Failed to detect the baud rate")]
+    #[diagnostic(code(mcumgrctl::baud_rate_detection))]
+    BaudRateDetectionFailed(#[from] SerialProbeError),
+    #[error("Failed to create session recording")]
+    #[diagnostic(code(mcumgrctl::record))]
+    RecordingFailed(#[source] std::io::Error),
+    #[error("Failed to load session recording")]
+    #[diagnostic(code(mcumgrctl::replay))]
+    ReplayLoadFailed(#[source] std::io::Error),
+    #[error("Failed to connect to TCP serial bridge")]
+    #[diagnostic(code(mcumgrctl::open_tcp_failed))]
+    OpenTcpFailed(#[source] std::io::Error),
+    #[error("Failed to set the baud rate")]
+    #[diagnostic(code(mcumgrctl::set_baud_rate_failed))]
+    SetBaudRateFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[cfg(unix)]
+    #[error("Failed to open pseudo-terminal")]
+    #[diagnostic(code(mcumgrctl::open_pty_failed))]
+    OpenPtyFailed(#[source] std::io::Error),
+    #[cfg(unix)]
+    #[error("Failed to connect to Unix domain socket")]
+    #[diagnostic(code(mcumgrctl::open_unix_socket_failed))]
+    OpenUnixSocketFailed(#[source] std::io::Error),
+    #[error("Failed to open UDP socket")]
+    #[diagnostic(code(mcumgrctl::open_udp_failed))]
+    OpenUdpFailed(#[source] std::io::Error),
+    #[error("SMP proxy failed")]
+    #[diagnostic(code(mcumgrctl::proxy))]
+    ProxyFailed(#[from] ProxyError),
+    #[error("Benchmark failed")]
+    #[diagnostic(code(mcumgrctl::bench))]
+    BenchFailed(#[from] BenchError),
+    #[error("Drift measurement failed")]
+    #[diagnostic(code(mcumgrctl::measure_drift))]
+    DriftMeasurementFailed(#[source] DriftError),
+    #[error("Failed to collect the device snapshot")]
+    #[diagnostic(code(mcumgrctl::inventory))]
+    DeviceSnapshotFailed(#[from] DeviceSnapshotError),
+    #[error("Failed to parse snapshot '{file}'")]
+    #[diagnostic(code(mcumgrctl::snapshot_parse))]
+    SnapshotParseFailed {
+        file: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Failed to open the devices of the fleet")]
+    #[diagnostic(code(mcumgrctl::fleet_open))]
+    FleetOpenFailed(#[from] FleetOpenError),
+    #[error("Baud rate detection is not supported for fleets")]
+    #[diagnostic(code(mcumgrctl::fleet_baud_rate))]
+    FleetBaudRateDetectionUnsupported,
+    #[error("Firmware update failed on {failed} of {total} devices")]
+    #[diagnostic(code(mcumgrctl::fleet_update))]
+    FleetUpdateFailed { failed: usize, total: usize },
+    #[error("Failed to read from the terminal")]
+    #[diagnostic(code(mcumgrctl::readline))]
+    ReadlineFailed(#[source] rustyline::error::ReadlineError),
 }
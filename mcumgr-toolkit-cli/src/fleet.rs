@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use console::style;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use mcumgr_toolkit::client::{FirmwareUpdateParams, fleet};
+
+use crate::{
+    args::{BackendArgs, BaudRate, CommonArgs, FleetCommand},
+    errors::CliError,
+    file_read_write::read_input_file,
+    formatting::describe_error,
+};
+
+const STEP_TEMPLATE: &str = "{prefix:.bold} {msg}";
+const TRANSFER_TEMPLATE: &str =
+    "{prefix:.bold} {msg} {wide_bar} {decimal_bytes:>9} / {decimal_total_bytes:9}";
+
+fn create_progress_bar(multiprogress: &MultiProgress, name: &str) -> ProgressBar {
+    let progressbar = multiprogress.add(ProgressBar::new(0));
+    progressbar.set_style(ProgressStyle::with_template(STEP_TEMPLATE).unwrap());
+    progressbar.set_prefix(name.to_string());
+    progressbar
+}
+
+fn update_progress_bar(progressbar: &ProgressBar, msg: String, progress: Option<(u64, u64)>) {
+    progressbar.set_message(msg);
+
+    if let Some((current, total)) = progress {
+        progressbar.set_style(ProgressStyle::with_template(TRANSFER_TEMPLATE).unwrap());
+        progressbar.set_length(total);
+        progressbar.set_position(current);
+    } else {
+        progressbar.set_style(ProgressStyle::with_template(STEP_TEMPLATE).unwrap());
+    }
+}
+
+pub fn run(
+    backend_args: &BackendArgs,
+    common: &CommonArgs,
+    multiprogress: &MultiProgress,
+    command: &FleetCommand,
+) -> Result<(), CliError> {
+    match command {
+        FleetCommand::Update {
+            firmware_file,
+            usb_serial,
+            bootloader,
+            skip_reboot,
+            force_confirm,
+            upgrade_only,
+            checksum,
+        } => {
+            let identifier = usb_serial
+                .as_ref()
+                .or(backend_args.usb_serial.as_ref())
+                .ok_or(CliError::NoBackendSelected)?;
+            let BaudRate::Fixed(baud_rate) = backend_args.baud else {
+                return Err(CliError::FleetBaudRateDetectionUnsupported);
+            };
+
+            let (firmware, _source_filename) = read_input_file(firmware_file)?;
+
+            let devices = fleet::open_usb_serial(
+                identifier,
+                baud_rate,
+                Duration::from_millis(backend_args.timeout),
+            )?;
+
+            let params = FirmwareUpdateParams {
+                bootloader_type: bootloader.map(Into::into),
+                skip_reboot: *skip_reboot,
+                force_confirm: *force_confirm,
+                upgrade_only: *upgrade_only,
+            };
+
+            let results = if common.quiet {
+                fleet::firmware_update(&devices, firmware, *checksum, params, None)
+            } else {
+                let progressbars = devices
+                    .iter()
+                    .map(|device| create_progress_bar(multiprogress, &device.name))
+                    .collect::<Vec<_>>();
+
+                let results = fleet::firmware_update(
+                    &devices,
+                    firmware,
+                    *checksum,
+                    params,
+                    Some(&|index, step, progress| {
+                        update_progress_bar(&progressbars[index], step.to_string(), progress);
+                        true
+                    }),
+                );
+
+                for (progressbar, result) in progressbars.iter().zip(&results) {
+                    update_progress_bar(
+                        progressbar,
+                        match result {
+                            Ok(()) => style("Success.").green().to_string(),
+                            Err(e) => style(format!("Failed: {e}")).red().to_string(),
+                        },
+                        None,
+                    );
+                    progressbar.finish();
+                }
+
+                results
+            };
+
+            let failed = results.iter().filter(|result| result.is_err()).count();
+
+            if common.json {
+                let summary = devices
+                    .iter()
+                    .zip(&results)
+                    .map(|(device, result)| {
+                        serde_json::json!({
+                            "device": device.name,
+                            "success": result.is_ok(),
+                            "error": result.as_ref().err().map(|e| describe_error(e)),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&summary).map_err(CliError::JsonEncodeError)?
+                );
+            } else {
+                multiprogress.suspend(|| {
+                    println!();
+                    println!("{}", style("Summary:").bold());
+                    for (device, result) in devices.iter().zip(&results) {
+                        match result {
+                            Ok(()) => println!("  {} {}", style("✔").green(), device.name),
+                            Err(e) => println!(
+                                "  {} {}: {}",
+                                style("✘").red(),
+                                device.name,
+                                describe_error(e)
+                            ),
+                        }
+                    }
+                    println!();
+                    println!(
+                        "{} of {} devices updated successfully.",
+                        devices.len() - failed,
+                        devices.len()
+                    );
+                    if failed == 0 && !skip_reboot {
+                        println!("Devices should reboot with new firmware.");
+                    }
+                });
+            }
+
+            if failed > 0 {
+                return Err(CliError::FleetUpdateFailed {
+                    failed,
+                    total: devices.len(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
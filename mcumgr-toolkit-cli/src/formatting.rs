@@ -132,3 +132,15 @@ pub fn structured_print(
     }
     Ok(())
 }
+
+/// Formats an error together with all of its causes.
+pub fn describe_error(err: &dyn std::error::Error) -> String {
+    let mut description = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        description.push_str(": ");
+        description.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    description
+}
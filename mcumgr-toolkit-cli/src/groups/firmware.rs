@@ -1,10 +1,10 @@
 use clap::ValueEnum;
-use indicatif::{MultiProgress, ProgressBar, ProgressFinish, ProgressStyle};
+use indicatif::MultiProgress;
 use mcumgr_toolkit::client::FirmwareUpdateParams;
 
 use crate::{
     args::CommonArgs, client::Client, errors::CliError, file_read_write::read_input_file,
-    formatting::structured_print, groups::parse_sha256,
+    formatting::structured_print, groups::parse_sha256, progress::StepProgressHandler,
 };
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -53,57 +53,6 @@ pub enum FirmwareCommand {
     },
 }
 
-struct FirmwareUpgradeProgressHandler<'a> {
-    previous_message: String,
-    multiprogress: &'a MultiProgress,
-    progressbar: Option<ProgressBar>,
-}
-
-impl<'a> FirmwareUpgradeProgressHandler<'a> {
-    fn new(multiprogress: &'a MultiProgress) -> Self {
-        Self {
-            previous_message: "".to_string(),
-            multiprogress,
-            progressbar: None,
-        }
-    }
-    fn update(&mut self, msg: &str, progress: Option<(u64, u64)>) -> bool {
-        if msg != self.previous_message {
-            self.previous_message = msg.to_string();
-            self.multiprogress.println(msg).ok();
-        }
-
-        if let Some((current, total)) = progress {
-            let progressbar = self.progressbar.get_or_insert_with(||{
-                let progressbar = self.multiprogress.add(ProgressBar::new(total)).with_finish(ProgressFinish::AndClear);
-                progressbar.set_style(
-                ProgressStyle::with_template(
-                    "{wide_bar} {decimal_bytes:>9} / {decimal_total_bytes:9} ({decimal_bytes_per_sec:9})",
-                )
-                .unwrap());
-                progressbar
-            });
-
-            progressbar.set_length(total);
-            progressbar.set_position(current);
-        } else if let Some(progressbar) = self.progressbar.take() {
-            progressbar.finish_and_clear();
-            self.multiprogress.remove(&progressbar);
-        }
-
-        true
-    }
-}
-
-impl Drop for FirmwareUpgradeProgressHandler<'_> {
-    fn drop(&mut self) {
-        if let Some(progressbar) = self.progressbar.take() {
-            progressbar.finish_and_clear();
-            self.multiprogress.remove(&progressbar);
-        }
-    }
-}
-
 pub fn run(
     client: &Client,
     multiprogress: &MultiProgress,
@@ -149,7 +98,7 @@ pub fn run(
             if args.quiet {
                 client.firmware_update(firmware, checksum, params, None)
             } else {
-                let mut progress_handler = FirmwareUpgradeProgressHandler::new(multiprogress);
+                let mut progress_handler = StepProgressHandler::new(multiprogress);
                 client.firmware_update(
                     firmware,
                     checksum,
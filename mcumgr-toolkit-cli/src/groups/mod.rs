@@ -10,6 +10,8 @@ mod raw;
 mod shell;
 mod zephyr;
 
+pub use firmware::BootloaderType;
+
 #[derive(Debug, clap::Subcommand)]
 pub enum Group {
     /// Default/OS Management
@@ -34,8 +36,19 @@ pub enum Group {
     },
     /// Shell command execution
     Shell {
+        /// Start an interactive shell session
+        ///
+        /// Supports line editing, a persistent command history
+        /// and tab completion of the device's shell commands.
+        #[arg(short, long, verbatim_doc_comment)]
+        interactive: bool,
+
         /// The shell command to execute
-        #[arg(required = true, trailing_var_arg = true)]
+        #[arg(
+            required_unless_present = "interactive",
+            conflicts_with = "interactive",
+            trailing_var_arg = true
+        )]
         argv: Vec<String>,
     },
     /// Zephyr Management
@@ -58,13 +71,15 @@ pub fn run(
         Group::Image { command } => image::run(client, multiprogress, args, command),
         Group::Firmware { command } => firmware::run(client, multiprogress, args, command),
         Group::Fs { command } => fs::run(client, multiprogress, args, command),
-        Group::Shell { argv } => shell::run(client, multiprogress, args, argv),
+        Group::Shell { interactive, argv } => {
+            shell::run(client, multiprogress, args, interactive, argv)
+        }
         Group::Zephyr { command } => zephyr::run(client, multiprogress, args, command),
         Group::Raw(raw_command) => raw::run(client, multiprogress, args, raw_command),
     }
 }
 
-fn parse_sha256(s: &str) -> Result<[u8; 32], hex::FromHexError> {
+pub fn parse_sha256(s: &str) -> Result<[u8; 32], hex::FromHexError> {
     let mut data = [0u8; 32];
     hex::decode_to_slice(s, &mut data)?;
     Ok(data)
@@ -1,15 +1,15 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
-use indicatif::MultiProgress;
+use indicatif::{MultiProgress, ProgressBar, ProgressFinish, ProgressStyle};
 use mcumgr_toolkit::{
     bootloader::{BootloaderInfo, MCUbootMode},
-    client::MCUmgrClientError,
     commands::os::ThreadStateFlags,
-    connection::ExecuteError,
 };
 
 use crate::{args::CommonArgs, client::Client, errors::CliError, formatting::structured_print};
 
+mod top;
+
 #[derive(Debug, clap::Subcommand)]
 pub enum OsCommand {
     /// Executes an echo command on the device
@@ -19,6 +19,18 @@ pub enum OsCommand {
     },
     /// Queries live task statistics
     TaskStatistics,
+    /// Queries the statistics of the memory pools
+    #[command(alias = "mpstat")]
+    MemoryPoolStatistics,
+    /// Continuously monitors the tasks, similar to `top`
+    ///
+    /// Shows the CPU usage of every task since the previous sample,
+    /// its stack usage and its state.
+    ///
+    /// Requires `CONFIG_MCUMGR_GRP_OS_TASKSTAT`; CPU usage additionally
+    /// requires `CONFIG_SCHED_THREAD_USAGE`.
+    #[command(verbatim_doc_comment)]
+    Top(top::TopArgs),
     /// Set the device's RTC datetime
     SetDatetime {
         /// The datetime value, as RFC3339; host time if omitted
@@ -29,6 +41,25 @@ pub enum OsCommand {
     },
     /// Retrieve the device's RTC datetime
     GetDatetime,
+    /// Set the device's RTC to the host time, compensating for the transport latency
+    ///
+    /// Reports the offset of the device clock before and after.
+    SyncTime {
+        /// Use UTC time instead of local time
+        #[arg(long)]
+        utc: bool,
+    },
+    /// Measure the drift of the device's RTC against the host clock
+    ///
+    /// The longer the duration, the more accurate the result.
+    MeasureDrift {
+        /// How long to measure, in seconds
+        #[arg(short, long, default_value_t = 60)]
+        duration: u64,
+        /// The time between two samples, in seconds
+        #[arg(short, long, default_value_t = 5)]
+        interval: u64,
+    },
     /// Issue a system reset
     SystemReset {
         /// Issue a force reset
@@ -133,7 +164,7 @@ impl ApplicationInfoFlags {
 
 pub fn run(
     client: &Client,
-    _multiprogress: &MultiProgress,
+    multiprogress: &MultiProgress,
     args: CommonArgs,
     command: OsCommand,
 ) -> Result<(), CliError> {
@@ -169,8 +200,7 @@ pub fn run(
                             if let (Some(stkuse), Some(stksiz)) = (stats.stkuse, stats.stksiz) {
                                 s.key_value(
                                     "Stack Usage",
-                                    if stksiz != 0 {
-                                        let pct = stkuse * 100 / stksiz;
+                                    if let Some(pct) = (stkuse * 100).checked_div(stksiz) {
                                         format!("{stkuse} / {stksiz} bytes ({pct} %)")
                                     } else {
                                         format!("{stkuse} / {stksiz} bytes")
@@ -188,6 +218,36 @@ pub fn run(
                 })?;
             }
         }
+        OsCommand::MemoryPoolStatistics => {
+            let pools_map = client.os_memory_pool_statistics()?;
+
+            if args.json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&pools_map).map_err(CliError::JsonEncodeError)?
+                );
+            } else {
+                let mut pools = pools_map.iter().collect::<Vec<_>>();
+                pools.sort_by_key(|(name, _)| *name);
+
+                println!();
+                println!(
+                    "{:<20} {:>10} {:>8} {:>8} {:>8} {:>8}",
+                    "Pool", "Block size", "Blocks", "Free", "Min free", "Peak %"
+                );
+                for (name, stats) in pools {
+                    let peak = (stats.nblks.saturating_sub(stats.min) * 100)
+                        .checked_div(stats.nblks)
+                        .map_or_else(|| "-".to_string(), |pct| pct.to_string());
+                    println!(
+                        "{:<20} {:>10} {:>8} {:>8} {:>8} {:>8}",
+                        name, stats.blksiz, stats.nblks, stats.nfree, stats.min, peak
+                    );
+                }
+                println!();
+            }
+        }
+        OsCommand::Top(top_args) => top::run(client, top_args)?,
         OsCommand::SetDatetime { value, utc } => {
             use chrono::{DateTime, FixedOffset, NaiveDateTime};
 
@@ -225,6 +285,64 @@ pub fn run(
                 println!("{:?}", datetime);
             }
         }
+        OsCommand::SyncTime { utc } => {
+            let result = client.os_sync_time(utc)?;
+
+            if args.json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&result).map_err(CliError::JsonEncodeError)?
+                );
+            } else {
+                println!("Round trip:      {:>12.1} ms", result.round_trip_ms);
+                println!("Offset before:   {:>12.1} ms", result.offset_before_ms);
+                println!("Offset after:    {:>12.1} ms", result.residual_offset_ms);
+            }
+        }
+        OsCommand::MeasureDrift { duration, interval } => {
+            let duration = Duration::from_secs(duration);
+            let interval = Duration::from_secs(interval);
+
+            let result = if args.quiet {
+                client.os_measure_drift(duration, interval, None)
+            } else {
+                let progressbar = multiprogress
+                    .add(ProgressBar::new(duration.as_secs()))
+                    .with_finish(ProgressFinish::AndClear);
+                progressbar.set_style(
+                    ProgressStyle::with_template(
+                        "Measuring drift ... {wide_bar} {pos:>5} / {len:5} s",
+                    )
+                    .unwrap(),
+                );
+
+                let result = client.os_measure_drift(
+                    duration,
+                    interval,
+                    Some(&mut |elapsed, _total| {
+                        progressbar.set_position(elapsed);
+                        true
+                    }),
+                );
+
+                progressbar.finish_and_clear();
+                multiprogress.remove(&progressbar);
+                result
+            }
+            .map_err(CliError::DriftMeasurementFailed)?;
+
+            if args.json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&result).map_err(CliError::JsonEncodeError)?
+                );
+            } else {
+                println!(
+                    "Drift: {:+.1} ppm ({:+.1} ms in {:.0} s, {} samples)",
+                    result.drift_ppm, result.offset_change_ms, result.duration_s, result.samples
+                );
+            }
+        }
         OsCommand::SystemReset { force, bootmode } => {
             client.os_system_reset(force, bootmode)?;
         }
@@ -241,36 +359,22 @@ pub fn run(
 
             if flags.is_empty() {
                 // Fetch everything and do a detailed print
-
-                let kernel_name = client.os_application_info(Some("s"))?;
-                let node_name = client.os_application_info(Some("n"))?;
-                let kernel_release = client.os_application_info(Some("r"))?;
-                let kernel_version = client.os_application_info(Some("v"))?;
-                let build_time = match client.os_application_info(Some("b")) {
-                    Ok(val) => Some(val),
-                    Err(MCUmgrClientError::ExecuteError(ExecuteError::ErrorResponse(e))) => {
-                        log::debug!("Failed to fetch build time: {e}");
-                        None
-                    }
-                    Err(e) => Err(e)?,
-                };
-                let machine = client.os_application_info(Some("m"))?;
-                let processor = client.os_application_info(Some("p"))?;
-                let hardware_platform = client.os_application_info(Some("i"))?;
-                let operating_system = client.os_application_info(Some("o"))?;
+                let info = client.os_application_info_structured()?;
 
                 structured_print(Some("OS/Application Info".to_string()), args.json, |s| {
-                    s.key_value("Kernel name", kernel_name);
-                    s.key_value("Node name", node_name);
-                    s.key_value("Kernel release", kernel_release);
-                    s.key_value("Kernel version", kernel_version);
-                    if let Some(build_time) = build_time {
+                    s.key_value("Kernel name", info.kernel_name);
+                    s.key_value("Node name", info.node_name);
+                    s.key_value("Kernel release", info.kernel_release);
+                    s.key_value("Kernel version", info.kernel_version);
+                    if let Some(build_time) = info.build_time {
+                        s.key_value("Build time", build_time.to_rfc3339());
+                    } else if let Some(build_time) = info.build_time_raw {
                         s.key_value("Build time", build_time);
                     }
-                    s.key_value("Machine", machine);
-                    s.key_value("Processor", processor);
-                    s.key_value("Hardware platform", hardware_platform);
-                    s.key_value("Operating system", operating_system);
+                    s.key_value("Machine", info.machine);
+                    s.key_value("Processor", info.processor);
+                    s.key_value("Hardware platform", info.hardware_platform);
+                    s.key_value("Operating system", info.operating_system);
                 })?;
             } else {
                 let output = client.os_application_info(Some(&flags.iter().collect::<String>()))?;
@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use console::{Term, style};
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    commands::os::{TaskStatisticsEntry, ThreadStateFlags},
+};
+
+use crate::errors::CliError;
+
+/// The column to sort the task list by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TopSortKey {
+    /// CPU usage, highest first
+    Cpu,
+    /// Stack usage, highest first
+    Stack,
+    /// Task name
+    Name,
+    /// Task priority, most urgent first
+    Prio,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct TopArgs {
+    /// The polling interval, in ms
+    #[arg(short, long, default_value_t = 1000)]
+    interval: u64,
+    /// The column to sort by
+    #[arg(short, long, value_enum, default_value_t = TopSortKey::Cpu)]
+    sort: TopSortKey,
+    /// Highlight tasks whose stack usage is above this value, in percent
+    #[arg(long, default_value_t = 80.0)]
+    stack_threshold: f64,
+    /// Stop after this number of samples
+    #[arg(short = 'n', long)]
+    count: Option<u64>,
+    /// Print one JSON object per sample instead of the live view
+    ///
+    /// Useful for logging during long-running tests.
+    #[arg(long, verbatim_doc_comment)]
+    json_lines: bool,
+}
+
+/// A task of one sample, with the values derived from the previous sample.
+struct TaskRow {
+    name: String,
+    stats: TaskStatisticsEntry,
+    cpu_percent: Option<f64>,
+    stack_percent: Option<f64>,
+}
+
+/// Computes the rows of a sample.
+///
+/// The CPU usage of a task is its share of the `runtime` that passed
+/// in all tasks since the previous sample. It is unknown for the first
+/// sample, and for tasks that did not exist or got reset in between.
+fn compute_rows(
+    previous: Option<&HashMap<String, TaskStatisticsEntry>>,
+    current: &HashMap<String, TaskStatisticsEntry>,
+    sort: TopSortKey,
+) -> Vec<TaskRow> {
+    let runtime_delta = |name: &String, stats: &TaskStatisticsEntry| {
+        let previous = previous?.get(name)?;
+        stats.runtime?.checked_sub(previous.runtime?)
+    };
+
+    let total_delta = current
+        .iter()
+        .filter_map(|(name, stats)| runtime_delta(name, stats))
+        .sum::<u64>();
+
+    let mut rows = current
+        .iter()
+        .map(|(name, stats)| TaskRow {
+            name: name.clone(),
+            stats: stats.clone(),
+            cpu_percent: runtime_delta(name, stats)
+                .filter(|_| total_delta > 0)
+                .map(|delta| delta as f64 * 100.0 / total_delta as f64),
+            stack_percent: match (stats.stkuse, stats.stksiz) {
+                (Some(stkuse), Some(stksiz)) if stksiz > 0 => {
+                    Some(stkuse as f64 * 100.0 / stksiz as f64)
+                }
+                _ => None,
+            },
+        })
+        .collect::<Vec<_>>();
+
+    // Ties are broken by name, to keep the order stable between samples
+    let by_percent =
+        |a: Option<f64>, b: Option<f64>| b.unwrap_or(-1.0).total_cmp(&a.unwrap_or(-1.0));
+    rows.sort_by(|a, b| {
+        match sort {
+            TopSortKey::Cpu => by_percent(a.cpu_percent, b.cpu_percent),
+            TopSortKey::Stack => by_percent(a.stack_percent, b.stack_percent),
+            TopSortKey::Name => std::cmp::Ordering::Equal,
+            TopSortKey::Prio => a.stats.prio.cmp(&b.stats.prio),
+        }
+        .then_with(|| a.name.cmp(&b.name))
+    });
+
+    rows
+}
+
+fn format_percent(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{value:.1}"))
+}
+
+fn draw(term: &Term, rows: &[TaskRow], args: &TopArgs, sample: u64) -> Result<(), std::io::Error> {
+    term.clear_screen()?;
+    term.write_line(&format!(
+        "{}  {} tasks, sample {sample}, every {} ms, sorted by {}, Ctrl+C to quit",
+        style("mcumgrctl os top").bold(),
+        rows.len(),
+        args.interval,
+        format!("{:?}", args.sort).to_lowercase(),
+    ))?;
+    term.write_line("")?;
+    term.write_line(
+        &style(format!(
+            "{:<20} {:>5} {:>6} {:>7} {:>15} {:>10}  {}",
+            "NAME", "PRIO", "CPU%", "STACK%", "STACK", "SWITCHES", "STATE"
+        ))
+        .reverse()
+        .to_string(),
+    )?;
+
+    for row in rows {
+        let stack = match (row.stats.stkuse, row.stats.stksiz) {
+            (Some(stkuse), Some(stksiz)) => format!("{stkuse}/{stksiz}"),
+            _ => "-".to_string(),
+        };
+        let line = format!(
+            "{:<20} {:>5} {:>6} {:>7} {:>15} {:>10}  {}",
+            row.name,
+            row.stats.prio,
+            format_percent(row.cpu_percent),
+            format_percent(row.stack_percent),
+            stack,
+            row.stats
+                .cswcnt
+                .map_or_else(|| "-".to_string(), |cswcnt| cswcnt.to_string()),
+            ThreadStateFlags::pretty_print(row.stats.state as u8),
+        );
+
+        if row
+            .stack_percent
+            .is_some_and(|pct| pct >= args.stack_threshold)
+        {
+            term.write_line(&style(line).red().bold().to_string())?;
+        } else {
+            term.write_line(&line)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn json_line(rows: &[TaskRow], stack_threshold: f64) -> serde_json::Value {
+    let tasks = rows
+        .iter()
+        .map(|row| {
+            serde_json::json!({
+                "name": row.name,
+                "prio": row.stats.prio,
+                "tid": row.stats.tid,
+                "state": row.stats.state,
+                "state_flags": ThreadStateFlags::pretty_print(row.stats.state as u8),
+                "stkuse": row.stats.stkuse,
+                "stksiz": row.stats.stksiz,
+                "stack_percent": row.stack_percent,
+                "stack_above_threshold": row
+                    .stack_percent
+                    .is_some_and(|pct| pct >= stack_threshold),
+                "cswcnt": row.stats.cswcnt,
+                "runtime": row.stats.runtime,
+                "cpu_percent": row.cpu_percent,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::json!({
+        "timestamp": chrono::Local::now().to_rfc3339(),
+        "tasks": tasks,
+    })
+}
+
+/// Periodically polls the task statistics and displays them.
+pub fn run(client: &MCUmgrClient, args: TopArgs) -> Result<(), CliError> {
+    let interval = Duration::from_millis(args.interval);
+    let term = Term::stdout();
+
+    let mut previous = None;
+    let mut sample = 0;
+    loop {
+        let start = Instant::now();
+
+        let current = client.os_task_statistics()?;
+        sample += 1;
+
+        let rows = compute_rows(previous.as_ref(), &current, args.sort);
+        if args.json_lines {
+            println!("{}", json_line(&rows, args.stack_threshold));
+        } else {
+            draw(&term, &rows, &args, sample).map_err(CliError::OutputWriteFailed)?;
+        }
+        previous = Some(current);
+
+        if args.count.is_some_and(|count| sample >= count) {
+            return Ok(());
+        }
+
+        std::thread::sleep(interval.saturating_sub(start.elapsed()));
+    }
+}
@@ -2,13 +2,21 @@ use indicatif::MultiProgress;
 
 use crate::{args::CommonArgs, client::Client, errors::CliError};
 
+mod interactive;
+
 pub fn run(
     client: &Client,
     _multiprogress: &MultiProgress,
     _args: CommonArgs,
+    interactive: bool,
     argv: Vec<String>,
 ) -> Result<(), CliError> {
     let client = client.get()?;
+
+    if interactive {
+        return interactive::run(client);
+    }
+
     let (returncode, output) = client.shell_execute(&argv)?;
     println!("{output}");
     if returncode < 0 {
@@ -0,0 +1,243 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf};
+
+use console::style;
+use mcumgr_toolkit::{Errno, MCUmgrClient};
+use rustyline::{
+    Context, Editor, Helper,
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+};
+
+use crate::errors::CliError;
+
+const PROMPT: &str = "smp:~$ ";
+
+/// Runs an interactive shell session on the device.
+pub fn run(client: &MCUmgrClient) -> Result<(), CliError> {
+    let mut editor =
+        Editor::<ShellHelper, DefaultHistory>::new().map_err(CliError::ReadlineFailed)?;
+    editor.set_helper(Some(ShellHelper::new(client)));
+
+    let history_file = history_file();
+    if let Some(history_file) = &history_file {
+        if history_file.exists() {
+            if let Err(e) = editor.load_history(history_file) {
+                log::warn!("Failed to load shell history: {e}");
+            }
+        }
+    }
+
+    println!("Interactive device shell. Press Ctrl+D or type `exit` to quit.");
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(CliError::ReadlineFailed(e)),
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor
+            .add_history_entry(line)
+            .map_err(CliError::ReadlineFailed)?;
+
+        if matches!(line, "exit" | "quit") {
+            break;
+        }
+
+        let Some(argv) = shlex::split(line) else {
+            println!("{}", style("Error: Unbalanced quotes").red());
+            continue;
+        };
+
+        match client.shell_execute(&argv) {
+            Ok((returncode, output)) => {
+                let output = output.trim_end();
+                if !output.is_empty() {
+                    println!("{output}");
+                }
+                print_returncode(returncode);
+            }
+            Err(e) => {
+                println!("{:?}", miette::Report::new(e));
+            }
+        }
+    }
+
+    if let Some(history_file) = &history_file {
+        if let Some(parent) = history_file.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        if let Err(e) = editor.save_history(history_file) {
+            log::warn!("Failed to save shell history: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_returncode(returncode: i32) {
+    let text = format!("[ret: {returncode}]");
+    if returncode == 0 {
+        println!("{}", style(text).dim());
+    } else if returncode < 0 {
+        println!(
+            "{} {}",
+            style(text).red(),
+            style(Errno::errno_to_string(returncode)).red()
+        );
+    } else {
+        println!("{}", style(text).yellow());
+    }
+}
+
+fn history_file() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("mcumgrctl").join("shell_history"))
+}
+
+/// Provides tab completion based on the `help` output of the device.
+struct ShellHelper<'a> {
+    client: &'a MCUmgrClient,
+    /// Known subcommands, indexed by their parent command
+    subcommands: RefCell<HashMap<Vec<String>, Vec<String>>>,
+}
+
+impl<'a> ShellHelper<'a> {
+    fn new(client: &'a MCUmgrClient) -> Self {
+        Self {
+            client,
+            subcommands: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Queries the subcommands of the given command from the device.
+    ///
+    /// An empty command queries the list of top level commands.
+    fn subcommands(&self, command: &[String]) -> Vec<String> {
+        self.subcommands
+            .borrow_mut()
+            .entry(command.to_vec())
+            .or_insert_with(|| {
+                let argv = if command.is_empty() {
+                    vec!["help".to_string()]
+                } else {
+                    let mut argv = command.to_vec();
+                    argv.push("-h".to_string());
+                    argv
+                };
+
+                match self.client.shell_execute(&argv) {
+                    Ok((_, output)) => parse_help_output(&output),
+                    Err(e) => {
+                        log::debug!("Failed to query shell completions: {e}");
+                        vec![]
+                    }
+                }
+            })
+            .clone()
+    }
+}
+
+/// Extracts the command names from a Zephyr shell help text.
+///
+/// Zephyr lists them below an `Available commands:` or `Subcommands:`
+/// line, one indented `name : description` entry per line.
+fn parse_help_output(output: &str) -> Vec<String> {
+    let mut commands = vec![];
+    let mut in_command_list = false;
+
+    for line in output.lines() {
+        let line = strip_ansi_escapes(line);
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.ends_with("commands:") || line.ends_with("Subcommands:") {
+            in_command_list = true;
+        } else if in_command_list {
+            if !line.starts_with(char::is_whitespace) {
+                in_command_list = false;
+            } else if let Some(name) = line.split_whitespace().next() {
+                if name != ":" {
+                    commands.push(name.to_string());
+                }
+            }
+        }
+    }
+
+    commands
+}
+
+fn strip_ansi_escapes(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+impl Completer for ShellHelper<'_> {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let word_start = line
+            .rfind(char::is_whitespace)
+            .map(|pos| pos + 1)
+            .unwrap_or(0);
+        let (command, prefix) = line.split_at(word_start);
+
+        let command = command
+            .split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        let candidates = self
+            .subcommands(&command)
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair {
+                replacement: format!("{name} "),
+                display: name,
+            })
+            .collect();
+
+        Ok((word_start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper<'_> {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper<'_> {}
+
+impl Validator for ShellHelper<'_> {}
+
+impl Helper for ShellHelper<'_> {}
@@ -0,0 +1,148 @@
+use std::collections::BTreeSet;
+
+use console::style;
+use serde_json::Value;
+
+use crate::{
+    args::CommonArgs,
+    client::Client,
+    errors::CliError,
+    file_read_write::read_input_file,
+    formatting::{StructuredPrint, structured_print},
+};
+
+fn add_json_value(s: &mut StructuredPrint, key: String, value: Value) {
+    match value {
+        Value::Object(map) => s.sublist(key, |s| {
+            for (key, value) in map {
+                add_json_value(s, key, value);
+            }
+        }),
+        Value::Array(list) => s.sublist(key, |s| {
+            for (index, value) in list.into_iter().enumerate() {
+                add_json_value(s, index.to_string(), value);
+            }
+        }),
+        value => s.key_value(key, value),
+    }
+}
+
+pub fn run(client: &Client, args: CommonArgs) -> Result<(), CliError> {
+    let client = client.get()?;
+
+    let snapshot = client.device_snapshot()?;
+
+    if args.json {
+        let json_str =
+            serde_json::to_string_pretty(&snapshot).map_err(CliError::JsonEncodeError)?;
+        println!("{json_str}");
+        return Ok(());
+    }
+
+    let Value::Object(snapshot) =
+        serde_json::to_value(&snapshot).map_err(CliError::JsonEncodeError)?
+    else {
+        unreachable!("snapshot should serialize to an object");
+    };
+
+    structured_print(Some("Device Inventory".to_string()), false, |s| {
+        for (key, value) in snapshot {
+            add_json_value(s, key, value);
+        }
+    })
+}
+
+/// A value that differs between two snapshots.
+struct Change {
+    path: String,
+    old: Option<Value>,
+    new: Option<Value>,
+}
+
+fn collect_changes(
+    path: String,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<Change>,
+) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+            for key in keys {
+                collect_changes(join(key), old.get(key), new.get(key), changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for index in 0..old.len().max(new.len()) {
+                collect_changes(
+                    format!("{path}[{index}]"),
+                    old.get(index),
+                    new.get(index),
+                    changes,
+                );
+            }
+        }
+        (old, new) if old == new => {}
+        (old, new) => changes.push(Change {
+            path,
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+    }
+}
+
+fn load_snapshot(file: &str) -> Result<Value, CliError> {
+    let (data, _source_filename) = read_input_file(file)?;
+    serde_json::from_slice(&data).map_err(|source| CliError::SnapshotParseFailed {
+        file: file.to_string(),
+        source,
+    })
+}
+
+pub fn diff(json: bool, old: &str, new: &str) -> Result<(), CliError> {
+    let old = load_snapshot(old)?;
+    let new = load_snapshot(new)?;
+
+    let mut changes = vec![];
+    collect_changes(String::new(), Some(&old), Some(&new), &mut changes);
+
+    if json {
+        let changes = changes
+            .into_iter()
+            .map(|change| {
+                serde_json::json!({
+                    "path": change.path,
+                    "old": change.old,
+                    "new": change.new,
+                })
+            })
+            .collect::<Vec<_>>();
+        let json_str = serde_json::to_string_pretty(&changes).map_err(CliError::JsonEncodeError)?;
+        println!("{json_str}");
+        return Ok(());
+    }
+
+    println!();
+    if changes.is_empty() {
+        println!("Snapshots are identical.");
+    }
+    for change in changes {
+        if let Some(old) = change.old {
+            println!("{}", style(format!("- {}: {old}", change.path)).red());
+        }
+        if let Some(new) = change.new {
+            println!("{}", style(format!("+ {}: {new}", change.path)).green());
+        }
+    }
+    println!();
+
+    Ok(())
+}
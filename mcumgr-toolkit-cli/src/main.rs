@@ -1,72 +1,236 @@
 #![forbid(unsafe_code)]
 
 mod args;
+mod bench;
 mod client;
+mod doctor;
 mod errors;
 mod file_read_write;
+mod fleet;
 mod formatting;
 mod groups;
+mod inventory;
 mod progress;
+mod proxy;
 
 use client::Client;
 use indicatif::MultiProgress;
 use indicatif_log_bridge::LogWrapper;
 
-use std::time::Duration;
+use std::{
+    fs::File,
+    io::{BufReader, Read, Write},
+    path::Path,
+    time::Duration,
+};
 
-use clap::Parser;
-use mcumgr_toolkit::{MCUmgrClient, client::UsbSerialError};
+use clap::{CommandFactory, Parser, error::ErrorKind};
+use console::style;
+use mcumgr_toolkit::{
+    MCUmgrClient, MCUmgrGroup,
+    client::{COMMON_BAUD_RATES, UsbSerialError, find_usb_serial_port, probe_serial_port},
+    connection::{FrameDirection, FrameEvent, LinkStats},
+    transport::{
+        SmpVersion, Transport,
+        record::{RecordingTransport, ReplayTransport},
+        serial::{ConfigurableBaudRate, ConfigurableTimeout, SerialTransportBuilder},
+        tcp::TcpSerial,
+        udp::UdpTransport,
+    },
+};
 
-use crate::errors::CliError;
+#[cfg(unix)]
+use mcumgr_toolkit::transport::{pty::PtySerial, unix::UnixSocketTransport};
+
+use crate::{
+    args::{BackendArgs, BaudRate, Command, InventoryCommand},
+    errors::CliError,
+};
+
+/// The opened connection to the device.
+struct Backend {
+    transport: Box<dyn Transport + Send>,
+    /// The SMP frame size that was detected while opening the connection
+    frame_size: Option<usize>,
+}
+
+impl Backend {
+    fn new(transport: impl Transport + Send + 'static) -> Self {
+        Self {
+            transport: Box::new(transport),
+            frame_size: None,
+        }
+    }
+
+    /// Records all frames into the given session log.
+    fn record(self, path: &Path) -> Result<Self, CliError> {
+        let log = File::create(path).map_err(CliError::RecordingFailed)?;
+        let transport =
+            RecordingTransport::new(self.transport, log).map_err(CliError::RecordingFailed)?;
+        Ok(Self {
+            transport: Box::new(transport),
+            ..self
+        })
+    }
+}
+
+/// A command that talks to the device through the client.
+enum DeviceCommand {
+    Group(groups::Group),
+    Bench(args::BenchArgs),
+    Doctor,
+    Inventory,
+    /// No command given; check that the device responds
+    ConnectionTest,
+}
 
 fn cli_main(multiprogress: &MultiProgress) -> Result<(), CliError> {
     let args = args::App::parse();
 
-    let client = if let Some(serial_name) = args.serial {
-        if serial_name.is_empty() {
-            let ports = serialport::available_ports()
-                .map_err(CliError::ListSerialPortsFailed)?
-                .into_iter()
-                .map(|port| port.port_name)
-                .collect::<Vec<_>>();
-            if args.common.json {
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&ports).map_err(CliError::JsonEncodeError)?
-                );
+    let command = match args.command {
+        Some(Command::Proxy(proxy_args)) => {
+            for (flag, used) in [("--trace", args.trace), ("--stats", args.stats)] {
+                if used {
+                    args::App::command()
+                        .bin_name(env!("CARGO_BIN_NAME"))
+                        .error(
+                            ErrorKind::ArgumentConflict,
+                            format!("the argument '{flag}' cannot be used with 'proxy'"),
+                        )
+                        .exit();
+                }
+            }
+
+            let backend_args = if proxy_args.backend.is_selected() {
+                &proxy_args.backend
+            } else {
+                &args.backend
+            };
+            if list_ports(backend_args, args.common.json)? {
+                return Ok(());
+            }
+            let backend =
+                open_backend(backend_args, multiprogress)?.ok_or(CliError::NoBackendSelected)?;
+            return proxy::run(backend.transport, &proxy_args.listen);
+        }
+        Some(Command::Fleet { command }) => {
+            return fleet::run(&args.backend, &args.common, multiprogress, &command);
+        }
+        Some(Command::Inventory {
+            command: Some(InventoryCommand::Diff { old, new }),
+        }) => return inventory::diff(args.common.json, &old, &new),
+        Some(Command::Doctor(doctor_args)) if doctor::uses_serial_ports(&args.backend) => {
+            return doctor::run_serial_ports(&args.backend, &doctor_args);
+        }
+        Some(Command::Group(group)) => DeviceCommand::Group(group),
+        Some(Command::Bench(bench_args)) => DeviceCommand::Bench(bench_args),
+        Some(Command::Doctor(_)) => DeviceCommand::Doctor,
+        Some(Command::Inventory { command: None }) => DeviceCommand::Inventory,
+        None => DeviceCommand::ConnectionTest,
+    };
+
+    if list_ports(&args.backend, args.common.json)? {
+        return Ok(());
+    }
+    let backend = open_backend(&args.backend, multiprogress)?;
+
+    let frame_size = backend.as_ref().and_then(|backend| backend.frame_size);
+    let client = match backend {
+        Some(backend) => Client::new(MCUmgrClient::new_from_transport(backend.transport)),
+        None => Client::default(),
+    };
+
+    if let Ok(client) = client.get() {
+        if args.trace {
+            let multiprogress = multiprogress.clone();
+            client.add_frame_observer(move |event: &FrameEvent| {
+                multiprogress.suspend(|| print_trace(event))
+            });
+        }
+
+        match args.smp_version {
+            Some(1) => client.set_smp_version(Some(SmpVersion::V1)),
+            Some(2) => client.set_smp_version(Some(SmpVersion::V2)),
+            _ => {}
+        }
+
+        if let Some(frame_size) = frame_size {
+            client.set_frame_size(frame_size);
+        } else if let Err(e) = client.use_auto_frame_size() {
+            log::warn!("Failed to read SMP frame size from device, using slow default");
+            log::warn!("Reason: {e}");
+            log::warn!("Hint: Make sure that `CONFIG_MCUMGR_GRP_OS_MCUMGR_PARAMS` is enabled.");
+        }
+    }
+
+    let result = match command {
+        DeviceCommand::Group(group) => groups::run(&client, multiprogress, args.common, group),
+        DeviceCommand::Bench(bench_args) => {
+            bench::run(&client, multiprogress, args.common, bench_args)
+        }
+        DeviceCommand::Doctor => doctor::run(&client),
+        DeviceCommand::Inventory => inventory::run(&client, args.common),
+        DeviceCommand::ConnectionTest => client.get().and_then(|client| {
+            client.check_connection()?;
+            println!("Device alive and responsive.");
+            Ok(())
+        }),
+    };
+
+    // The statistics are most interesting if the command failed
+    if args.stats {
+        if let Ok(client) = client.get() {
+            print_link_stats(&client.link_stats());
+        }
+    }
+    result?;
+
+    if args.console_linger > 0 {
+        if let Ok(client) = client.get() {
+            client.receive_console(Duration::from_millis(args.console_linger))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the available ports if `--serial` or `--usb-serial` has no argument.
+///
+/// Returns whether the ports were listed.
+fn list_ports(args: &BackendArgs, json: bool) -> Result<bool, CliError> {
+    if args.serial.as_ref().is_some_and(|name| name.is_empty()) {
+        let ports = serialport::available_ports()
+            .map_err(CliError::ListSerialPortsFailed)?
+            .into_iter()
+            .map(|port| port.port_name)
+            .collect::<Vec<_>>();
+        if json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&ports).map_err(CliError::JsonEncodeError)?
+            );
+        } else {
+            println!();
+            if ports.is_empty() {
+                println!("No serial ports available.");
             } else {
+                println!("Available serial ports:");
                 println!();
-                if ports.is_empty() {
-                    println!("No serial ports available.");
-                } else {
-                    println!("Available serial ports:");
-                    println!();
-                    for port in ports {
-                        println!(" - {port}");
-                    }
+                for port in ports {
+                    println!(" - {port}");
                 }
-                println!();
             }
-            return Ok(());
+            println!();
         }
+        return Ok(true);
+    }
 
-        let serial = serialport::new(serial_name, args.baud)
-            .timeout(Duration::from_millis(args.timeout))
-            .open()
-            .map_err(CliError::OpenSerialFailed)?;
-        Client::new(MCUmgrClient::new_from_serial(serial))
-    } else if let Some(identifier) = args.usb_serial {
-        let result = MCUmgrClient::new_from_usb_serial(
-            identifier,
-            args.baud,
-            Duration::from_millis(args.timeout),
-        );
-
-        if let Err(UsbSerialError::IdentifierEmpty { ports }) = &result {
-            if args.common.json {
+    if let Some(identifier) = args.usb_serial.as_ref().filter(|id| id.is_empty()) {
+        if let Err(UsbSerialError::IdentifierEmpty { ports }) = find_usb_serial_port(identifier) {
+            if json {
                 println!(
                     "{}",
-                    serde_json::to_string_pretty(ports).map_err(CliError::JsonEncodeError)?
+                    serde_json::to_string_pretty(&ports).map_err(CliError::JsonEncodeError)?
                 );
             } else {
                 println!();
@@ -78,30 +242,241 @@ fn cli_main(multiprogress: &MultiProgress) -> Result<(), CliError> {
                 }
                 println!();
             }
-            return Ok(());
+            return Ok(true);
         }
+    }
+
+    Ok(false)
+}
 
-        Client::new(result?)
+/// Opens the selected backend, if any.
+fn open_backend(
+    args: &BackendArgs,
+    multiprogress: &MultiProgress,
+) -> Result<Option<Backend>, CliError> {
+    let backend = if let Some(replay) = &args.replay {
+        let log = File::open(replay).map_err(CliError::ReplayLoadFailed)?;
+        let transport =
+            ReplayTransport::new(BufReader::new(log)).map_err(CliError::ReplayLoadFailed)?;
+        Some(Backend::new(transport))
+    } else if let Some(addr) = &args.tcp {
+        Some(open_tcp_serial(addr, args, multiprogress)?)
+    } else if let Some(backend) = open_unix_backend(args, multiprogress)? {
+        Some(backend)
+    } else if let Some(addr) = &args.udp {
+        let transport = UdpTransport::new(addr, Duration::from_millis(args.timeout))
+            .map_err(CliError::OpenUdpFailed)?;
+        Some(Backend::new(transport))
+    } else if let Some(serial_name) = &args.serial {
+        Some(open_serial(serial_name, args, multiprogress)?)
+    } else if let Some(identifier) = &args.usb_serial {
+        let port = find_usb_serial_port(identifier)?;
+        Some(open_serial(&port.port_name, args, multiprogress)?)
     } else {
-        Client::default()
+        None
     };
 
-    if let Ok(client) = client.get() {
-        if let Err(e) = client.use_auto_frame_size() {
-            log::warn!("Failed to read SMP frame size from device, using slow default");
-            log::warn!("Reason: {e}");
-            log::warn!("Hint: Make sure that `CONFIG_MCUMGR_GRP_OS_MCUMGR_PARAMS` is enabled.");
-        }
+    match (backend, &args.record) {
+        (Some(backend), Some(record)) => backend.record(record).map(Some),
+        (backend, _) => Ok(backend),
     }
+}
 
-    if let Some(group) = args.group {
-        groups::run(&client, multiprogress, args.common, group)?;
-    } else {
-        client.get()?.check_connection()?;
-        println!("Device alive and responsive.");
+fn open_serial(
+    port_name: &str,
+    args: &BackendArgs,
+    multiprogress: &MultiProgress,
+) -> Result<Backend, CliError> {
+    let baud_rate = match args.baud {
+        BaudRate::Fixed(baud_rate) => baud_rate,
+        BaudRate::Auto => COMMON_BAUD_RATES[0],
+    };
+
+    let serial = serialport::new(port_name, baud_rate)
+        .timeout(Duration::from_millis(args.timeout))
+        .open()
+        .map_err(CliError::OpenSerialFailed)?;
+
+    match args.baud {
+        BaudRate::Fixed(_) => serial_transport(serial, args, multiprogress),
+        BaudRate::Auto => probe_serial_transport(serial, args, multiprogress),
     }
+}
 
-    Ok(())
+#[cfg(unix)]
+fn open_unix_backend(
+    args: &BackendArgs,
+    multiprogress: &MultiProgress,
+) -> Result<Option<Backend>, CliError> {
+    let timeout = Duration::from_millis(args.timeout);
+
+    if let Some(path) = &args.pty {
+        let serial = PtySerial::open(path, timeout).map_err(CliError::OpenPtyFailed)?;
+        return serial_transport(serial, args, multiprogress).map(Some);
+    }
+
+    if let Some(path) = &args.unix {
+        let transport = UnixSocketTransport::connect(path, args.unix_framing.into(), timeout)
+            .map_err(CliError::OpenUnixSocketFailed)?;
+        return Ok(Some(Backend::new(transport)));
+    }
+
+    Ok(None)
+}
+
+#[cfg(not(unix))]
+fn open_unix_backend(
+    _args: &BackendArgs,
+    _multiprogress: &MultiProgress,
+) -> Result<Option<Backend>, CliError> {
+    Ok(None)
+}
+
+fn serial_transport_builder(args: &BackendArgs) -> SerialTransportBuilder {
+    SerialTransportBuilder::new()
+        .mtu(args.serial_mtu.into())
+        .tolerate_crlf(args.serial_tolerate_crlf)
+}
+
+fn serial_transport<T>(
+    serial: T,
+    args: &BackendArgs,
+    multiprogress: &MultiProgress,
+) -> Result<Backend, CliError>
+where
+    T: Read + Write + ConfigurableTimeout + Send + 'static,
+{
+    let mut transport = serial_transport_builder(args).build(serial);
+
+    if args.show_console {
+        let multiprogress = multiprogress.clone();
+        transport.set_console_sink(move |line| {
+            multiprogress.suspend(|| eprintln!("{} {line}", style("console:").dim()))
+        });
+    }
+
+    Ok(Backend::new(transport))
+}
+
+fn open_tcp_serial(
+    addr: &str,
+    args: &BackendArgs,
+    multiprogress: &MultiProgress,
+) -> Result<Backend, CliError> {
+    let timeout = Duration::from_millis(args.timeout);
+
+    if !args.rfc2217 {
+        let serial = TcpSerial::connect(addr, timeout).map_err(CliError::OpenTcpFailed)?;
+        return serial_transport(serial, args, multiprogress);
+    }
+
+    let mut serial = TcpSerial::connect_rfc2217(addr, timeout).map_err(CliError::OpenTcpFailed)?;
+
+    match args.baud {
+        BaudRate::Fixed(baud_rate) => {
+            serial
+                .set_baud_rate(baud_rate)
+                .map_err(CliError::SetBaudRateFailed)?;
+            serial_transport(serial, args, multiprogress)
+        }
+        BaudRate::Auto => probe_serial_transport(serial, args, multiprogress),
+    }
+}
+
+/// Detects the baud rate of the serial port and opens the transport on it.
+fn probe_serial_transport<T>(
+    serial: T,
+    args: &BackendArgs,
+    multiprogress: &MultiProgress,
+) -> Result<Backend, CliError>
+where
+    T: Send + Read + Write + ConfigurableTimeout + ConfigurableBaudRate + 'static,
+{
+    log::info!("Detecting baud rate ...");
+
+    let (serial, result) =
+        probe_serial_port(serial, COMMON_BAUD_RATES, &serial_transport_builder(args))?;
+
+    log::info!("Detected baud rate: {}", result.baud_rate);
+
+    let mut backend = serial_transport(serial, args, multiprogress)?;
+    backend.frame_size = result.frame_size;
+    Ok(backend)
+}
+
+fn print_trace(event: &FrameEvent) {
+    let header = &event.header;
+
+    let direction = match event.direction {
+        FrameDirection::Tx => style("TX").cyan(),
+        FrameDirection::Rx => style("RX").green(),
+    };
+    let command_name = MCUmgrGroup::command_name(header.group_id, header.command_id)
+        .map(str::to_string)
+        .unwrap_or_else(|| header.command_id.to_string());
+    let elapsed = match event.direction {
+        FrameDirection::Tx => String::new(),
+        FrameDirection::Rx => format!(", {} ms", event.elapsed.as_millis()),
+    };
+    let ignored = if event.ignored { ", ignored" } else { "" };
+
+    eprintln!(
+        "{direction} {} {}/{command_name} {}",
+        style(format!("[seq {}]", header.sequence_num)).dim(),
+        MCUmgrGroup::group_id_to_string(header.group_id),
+        style(format!(
+            "({}, {}, {} bytes{elapsed}{ignored})",
+            header.op,
+            header.version,
+            event.payload.len()
+        ))
+        .dim(),
+    );
+    eprintln!("   {}", event.cbor_diagnostic());
+}
+
+fn print_link_stats(stats: &LinkStats) {
+    let format_ms = |duration: Duration| format!("{:.1} ms", duration.as_secs_f64() * 1000.0);
+
+    eprintln!();
+    eprintln!("{}", style("Link statistics:").bold());
+    eprintln!(
+        "  Frames:        {} sent, {} received, {} ignored",
+        stats.frames_sent, stats.frames_received, stats.ignored_frames
+    );
+    eprintln!(
+        "  Bytes:         {} sent, {} received",
+        stats.bytes_sent, stats.bytes_received
+    );
+    if let Some(transport) = stats.transport {
+        let overhead = |encoded: u64, raw: u64| {
+            if raw == 0 {
+                String::new()
+            } else {
+                format!(" (+{:.0}%)", (encoded as f64 / raw as f64 - 1.0) * 100.0)
+            }
+        };
+        eprintln!(
+            "  Encoded bytes: {} sent{}, {} received{}",
+            transport.encoded_bytes_sent,
+            overhead(transport.encoded_bytes_sent, stats.bytes_sent),
+            transport.encoded_bytes_received,
+            overhead(transport.encoded_bytes_received, stats.bytes_received),
+        );
+        eprintln!("  CRC errors:    {}", transport.crc_errors);
+    }
+    eprintln!("  Timeouts:      {}", stats.timeouts);
+    if let Some(round_trip) = stats.round_trip {
+        eprintln!(
+            "  Round trip:    min {}, p50 {}, p90 {}, p99 {}, max {} ({} requests)",
+            format_ms(round_trip.min),
+            format_ms(round_trip.p50),
+            format_ms(round_trip.p90),
+            format_ms(round_trip.p99),
+            format_ms(round_trip.max),
+            round_trip.count,
+        );
+    }
 }
 
 fn main() -> miette::Result<()> {
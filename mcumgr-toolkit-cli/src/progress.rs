@@ -43,3 +43,55 @@ pub fn with_progress_bar<T>(
         action(None)
     }
 }
+
+/// Prints the steps of a multi-step operation and shows the progress of the current one.
+pub struct StepProgressHandler<'a> {
+    previous_message: String,
+    multiprogress: &'a MultiProgress,
+    progressbar: Option<ProgressBar>,
+}
+
+impl<'a> StepProgressHandler<'a> {
+    pub fn new(multiprogress: &'a MultiProgress) -> Self {
+        Self {
+            previous_message: "".to_string(),
+            multiprogress,
+            progressbar: None,
+        }
+    }
+    pub fn update(&mut self, msg: &str, progress: Option<(u64, u64)>) -> bool {
+        if msg != self.previous_message {
+            self.previous_message = msg.to_string();
+            self.multiprogress.println(msg).ok();
+        }
+
+        if let Some((current, total)) = progress {
+            let progressbar = self.progressbar.get_or_insert_with(||{
+                let progressbar = self.multiprogress.add(ProgressBar::new(total)).with_finish(ProgressFinish::AndClear);
+                progressbar.set_style(
+                ProgressStyle::with_template(
+                    "{wide_bar} {decimal_bytes:>9} / {decimal_total_bytes:9} ({decimal_bytes_per_sec:9})",
+                )
+                .unwrap());
+                progressbar
+            });
+
+            progressbar.set_length(total);
+            progressbar.set_position(current);
+        } else if let Some(progressbar) = self.progressbar.take() {
+            progressbar.finish_and_clear();
+            self.multiprogress.remove(&progressbar);
+        }
+
+        true
+    }
+}
+
+impl Drop for StepProgressHandler<'_> {
+    fn drop(&mut self) {
+        if let Some(progressbar) = self.progressbar.take() {
+            progressbar.finish_and_clear();
+            self.multiprogress.remove(&progressbar);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use mcumgr_toolkit::transport::{Transport, udp::UdpProxy};
+
+use crate::errors::CliError;
+
+pub fn run(transport: Box<dyn Transport + Send>, listen: &str) -> Result<(), CliError> {
+    let mut proxy = UdpProxy::bind(transport, listen).map_err(CliError::OpenUdpFailed)?;
+
+    log::info!(
+        "Listening for SMP requests on {}",
+        proxy.local_addr().map_err(CliError::OpenUdpFailed)?
+    );
+
+    proxy.run()?;
+
+    Ok(())
+}
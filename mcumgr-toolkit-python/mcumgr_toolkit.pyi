@@ -1,12 +1,304 @@
 # This file is automatically generated by pyo3_stub_gen
 # ruff: noqa: E501, F401
 
+import asyncio
 import builtins
 import collections.abc
 import datetime
 import enum
 import typing
 
+@typing.final
+class ApplicationInfo:
+    r"""
+    Return value of `MCUmgrClient.os_application_info_structured`.
+    """
+    @property
+    def kernel_name(self) -> builtins.str:
+        r"""
+        Kernel name, like `Zephyr`
+        """
+    @property
+    def node_name(self) -> builtins.str:
+        r"""
+        Node name, usually the hostname
+        """
+    @property
+    def kernel_release(self) -> builtins.str:
+        r"""
+        Kernel release
+        """
+    @property
+    def kernel_version(self) -> builtins.str:
+        r"""
+        Kernel version
+        """
+    @property
+    def build_time(self) -> typing.Optional[datetime.datetime]:
+        r"""
+        Build date and time of the application, if available
+        """
+    @property
+    def build_time_raw(self) -> typing.Optional[builtins.str]:
+        r"""
+        Build date and time of the application, as reported by the device
+        
+        Also available if the value could not be parsed into `build_time`.
+        """
+    @property
+    def machine(self) -> builtins.str:
+        r"""
+        Machine, like `arm`
+        """
+    @property
+    def processor(self) -> builtins.str:
+        r"""
+        Processor, like `cortex-m33`
+        """
+    @property
+    def hardware_platform(self) -> builtins.str:
+        r"""
+        Hardware platform, like the board name
+        """
+    @property
+    def operating_system(self) -> builtins.str:
+        r"""
+        Operating system, like `Zephyr`
+        """
+
+@typing.final
+class AsyncMCUmgrClient:
+    r"""
+    An asyncio based client for Zephyr's MCUmgr SMP functionality
+    
+    Provides the same functionality as `MCUmgrClient`, but all methods that
+    communicate with the device are coroutines. The requests are
+    executed on a worker thread, so they do not block the event loop.
+    
+    Progress callbacks are called on the event loop.
+    
+    Cancelling an upload or a download aborts the transfer.
+    Other requests cannot be interrupted; cancelling them only discards their result.
+    """
+    @staticmethod
+    def serial(serial: builtins.str, baud_rate: builtins.int = 115200, timeout_ms: builtins.int = 10000, mtu: builtins.int = 127, tolerate_crlf: builtins.bool = False) -> 'AsyncMCUmgrClient':
+        r"""
+        Creates a new serial port based Zephyr MCUmgr SMP client.
+        
+        For the arguments, see `MCUmgrClient.serial`.
+        """
+    @staticmethod
+    def usb_serial(identifier: builtins.str, baud_rate: builtins.int = 115200, timeout_ms: builtins.int = 10000, mtu: builtins.int = 127, tolerate_crlf: builtins.bool = False) -> 'AsyncMCUmgrClient':
+        r"""
+        Creates a Zephyr MCUmgr SMP client based on a USB serial port identified by VID:PID.
+        
+        For the arguments, see `MCUmgrClient.usb_serial`.
+        """
+    @staticmethod
+    def custom_transport(transport: Transport, timeout_ms: builtins.int = 10000) -> 'AsyncMCUmgrClient':
+        r"""
+        Creates a Zephyr MCUmgr SMP client based on a custom transport that exchanges raw SMP frames.
+        
+        For the arguments, see `MCUmgrClient.custom_transport`.
+        
+        The methods of the transport get called from worker threads.
+        """
+    @staticmethod
+    def custom_serial(transport: Transport, timeout_ms: builtins.int = 10000, mtu: builtins.int = 127, tolerate_crlf: builtins.bool = False) -> 'AsyncMCUmgrClient':
+        r"""
+        Creates a Zephyr MCUmgr SMP client based on a custom transport that transfers
+        the bytes of a serial link.
+        
+        For the arguments, see `MCUmgrClient.custom_serial`.
+        
+        The methods of the transport get called from worker threads.
+        """
+    def set_frame_size(self, smp_frame_size: builtins.int) -> None:
+        r"""
+        Configures the maximum SMP frame size that we can send to the device.
+        
+        See `MCUmgrClient.set_frame_size`.
+        """
+    def use_auto_frame_size(self) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Configures the maximum SMP frame size automatically.
+        
+        See `MCUmgrClient.use_auto_frame_size`.
+        """
+    def set_timeout_ms(self, timeout_ms: builtins.int) -> None:
+        r"""
+        Changes the communication timeout.
+        
+        See `MCUmgrClient.set_timeout_ms`.
+        """
+    def link_stats(self) -> LinkStats:
+        r"""
+        Returns statistics about the link to the device.
+        
+        See `MCUmgrClient.link_stats`.
+        """
+    def reset_link_stats(self) -> None:
+        r"""
+        Resets the statistics returned by `link_stats`.
+        """
+    def check_connection(self) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Checks if the device is alive and responding.
+        
+        See `MCUmgrClient.check_connection`.
+        """
+    def firmware_update(self, firmware: bytes, checksum: typing.Optional[builtins.str | builtins.bytes] = None, bootloader_type: typing.Optional[typing.Literal['MCUboot']] = None, skip_reboot: builtins.bool = False, force_confirm: builtins.bool = False, upgrade_only: builtins.bool = False, progress: typing.Optional[collections.abc.Callable[[builtins.str, typing.Optional[builtins.tuple[builtins.int, builtins.int]]], None]] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        High-level firmware update routine.
+        
+        See `MCUmgrClient.firmware_update`.
+        
+        Cancelling aborts the update.
+        """
+    def bench(self, file: builtins.str = '/lfs/mcumgr_bench.bin', image_slot: builtins.bool = False, frame_sizes: typing.Optional[builtins.list[builtins.int]] = None, transfer_size: builtins.int = 32768, echo_count: builtins.int = 20, progress: typing.Optional[collections.abc.Callable[[builtins.str, typing.Optional[builtins.tuple[builtins.int, builtins.int]]], None]] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, typing.Any]:
+        r"""
+        Measures the latency and the throughput of the connection.
+        
+        See `MCUmgrClient.bench`.
+        
+        Cancelling aborts the measurement.
+        """
+    def os_echo(self, msg: builtins.str) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.str]:
+        r"""
+        Sends a message to the device and expects the same message back as response.
+        """
+    def os_task_statistics(self) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.dict[builtins.str, TaskStatistics]]:
+        r"""
+        Queries live task statistics
+        
+        See `MCUmgrClient.os_task_statistics`.
+        """
+    def os_memory_pool_statistics(self) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.dict[builtins.str, MemoryPoolStatistics]]:
+        r"""
+        Queries the statistics of the memory pools
+        """
+    def os_set_datetime(self, datetime: datetime.datetime) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Sets the RTC of the device to the given datetime.
+        
+        Uses the contained local time and discards timezone information.
+        """
+    def os_get_datetime(self) -> collections.abc.Coroutine[typing.Any, typing.Any, datetime.datetime]:
+        r"""
+        Retrieves the device RTC's datetime.
+        
+        Will not contain timezone information.
+        """
+    def os_system_reset(self, force: builtins.bool = False, boot_mode: typing.Optional[builtins.int] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Issues a system reset.
+        
+        See `MCUmgrClient.os_system_reset`.
+        """
+    def os_mcumgr_parameters(self) -> collections.abc.Coroutine[typing.Any, typing.Any, MCUmgrParameters]:
+        r"""
+        Fetch parameters from the MCUmgr library
+        """
+    def os_application_info(self, format: typing.Optional[builtins.str] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.str]:
+        r"""
+        Fetch information on the running image
+        
+        See `MCUmgrClient.os_application_info`.
+        """
+    def os_application_info_structured(self) -> collections.abc.Coroutine[typing.Any, typing.Any, ApplicationInfo]:
+        r"""
+        Fetch information on the running image, parsed into its fields
+        
+        See `MCUmgrClient.os_application_info_structured`.
+        """
+    def os_bootloader_info(self) -> collections.abc.Coroutine[typing.Any, typing.Any, typing.Any]:
+        r"""
+        Fetch information on the device's bootloader
+        """
+    def image_get_state(self) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.list[ImageState]]:
+        r"""
+        Obtain a list of images with their current state.
+        """
+    def image_set_state(self, hash: typing.Optional[builtins.str | builtins.bytes] = None, confirm: builtins.bool = False) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.list[ImageState]]:
+        r"""
+        Modify the current image state and return the new state
+        
+        See `MCUmgrClient.image_set_state`.
+        """
+    def image_upload(self, data: bytes, image: typing.Optional[builtins.int] = None, checksum: typing.Optional[builtins.str | builtins.bytes] = None, upgrade_only: builtins.bool = False, progress: typing.Optional[collections.abc.Callable[[builtins.int, builtins.int], None]] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Upload a firmware image to an image slot.
+        
+        See `MCUmgrClient.image_upload`.
+        
+        Cancelling aborts the upload.
+        """
+    def image_erase(self, slot: typing.Optional[builtins.int] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Erase image slot on target device.
+        
+        See `MCUmgrClient.image_erase`.
+        """
+    def image_slot_info(self) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.list[SlotInfoImage]]:
+        r"""
+        Obtain a list of available image slots.
+        """
+    def fs_file_download(self, name: builtins.str, progress: typing.Optional[collections.abc.Callable[[builtins.int, builtins.int], None]] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.bytes]:
+        r"""
+        Load a file from the device.
+        
+        See `MCUmgrClient.fs_file_download`.
+        
+        Cancelling aborts the download.
+        """
+    def fs_file_upload(self, name: builtins.str, data: bytes, progress: typing.Optional[collections.abc.Callable[[builtins.int, builtins.int], None]] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Write a file to the device.
+        
+        See `MCUmgrClient.fs_file_upload`.
+        
+        Cancelling aborts the upload.
+        """
+    def fs_file_status(self, name: builtins.str) -> collections.abc.Coroutine[typing.Any, typing.Any, FileStatus]:
+        r"""
+        Queries the file status
+        """
+    def fs_file_checksum(self, name: builtins.str, algorithm: typing.Optional[builtins.str] = None, offset: builtins.int = 0, length: typing.Optional[builtins.int] = None) -> collections.abc.Coroutine[typing.Any, typing.Any, FileChecksum]:
+        r"""
+        Computes the hash/checksum of a file
+        
+        See `MCUmgrClient.fs_file_checksum`.
+        """
+    def fs_supported_checksum_types(self) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.dict[builtins.str, FileChecksumProperties]]:
+        r"""
+        Queries which hash/checksum algorithms are available on the target
+        """
+    def fs_file_close(self) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Close all device files MCUmgr has currently open
+        """
+    def shell_execute(self, argv: builtins.list[builtins.str]) -> collections.abc.Coroutine[typing.Any, typing.Any, builtins.str]:
+        r"""
+        Run a shell command.
+        
+        See `MCUmgrClient.shell_execute`.
+        """
+    def zephyr_erase_storage(self) -> collections.abc.Coroutine[typing.Any, typing.Any, None]:
+        r"""
+        Erase the `storage_partition` flash partition.
+        """
+    def raw_command(self, write_operation: builtins.bool, group_id: builtins.int, command_id: builtins.int, data: typing.Any) -> collections.abc.Coroutine[typing.Any, typing.Any, typing.Any]:
+        r"""
+        Execute a raw MCUmgrCommand.
+        
+        See `MCUmgrClient.raw_command`.
+        """
+    def __aenter__(self) -> asyncio.Future[AsyncMCUmgrClient]: ...
+    def __aexit__(self, _exc_type: typing.Any, _exc_value: typing.Any, _traceback: typing.Any) -> asyncio.Future[builtins.bool]:
+        r"""
+        Closes the connection
+        """
+
 @typing.final
 class FileChecksum:
     r"""
@@ -115,13 +407,99 @@ class ImageState:
         true if image is to stay in primary slot after the next boot
         """
 
+@typing.final
+class LinkStats:
+    r"""
+    Return value of `MCUmgrClient.link_stats`.
+    
+    Byte counts refer to complete SMP frames, including their header.
+    """
+    @property
+    def frames_sent(self) -> builtins.int:
+        r"""
+        number of sent SMP frames
+        """
+    @property
+    def frames_received(self) -> builtins.int:
+        r"""
+        number of received SMP frames, including ignored ones
+        """
+    @property
+    def bytes_sent(self) -> builtins.int:
+        r"""
+        number of bytes of the sent SMP frames
+        """
+    @property
+    def bytes_received(self) -> builtins.int:
+        r"""
+        number of bytes of the received SMP frames
+        """
+    @property
+    def ignored_frames(self) -> builtins.int:
+        r"""
+        number of received frames that were ignored because of a wrong sequence number
+        """
+    @property
+    def timeouts(self) -> builtins.int:
+        r"""
+        number of requests that did not receive a response in time
+        """
+    @property
+    def encoded_bytes_sent(self) -> typing.Optional[builtins.int]:
+        r"""
+        number of bytes written to the link, including the encoding overhead;
+        `None` if the transport does not encode frames
+        """
+    @property
+    def encoded_bytes_received(self) -> typing.Optional[builtins.int]:
+        r"""
+        number of bytes read from the link, including the encoding overhead;
+        `None` if the transport does not encode frames
+        """
+    @property
+    def crc_errors(self) -> typing.Optional[builtins.int]:
+        r"""
+        number of received frames with a wrong checksum;
+        `None` if the transport does not encode frames
+        """
+    @property
+    def round_trip_count(self) -> builtins.int:
+        r"""
+        number of requests that received a response
+        """
+    @property
+    def round_trip_min_ms(self) -> typing.Optional[builtins.float]:
+        r"""
+        fastest round trip in milliseconds
+        """
+    @property
+    def round_trip_p50_ms(self) -> typing.Optional[builtins.float]:
+        r"""
+        median round trip in milliseconds
+        """
+    @property
+    def round_trip_p90_ms(self) -> typing.Optional[builtins.float]:
+        r"""
+        90th percentile of the round trips in milliseconds
+        """
+    @property
+    def round_trip_p99_ms(self) -> typing.Optional[builtins.float]:
+        r"""
+        99th percentile of the round trips in milliseconds
+        """
+    @property
+    def round_trip_max_ms(self) -> typing.Optional[builtins.float]:
+        r"""
+        slowest round trip in milliseconds
+        """
+
 @typing.final
 class MCUmgrClient:
     r"""
     A high-level client for Zephyr's MCUmgr SMP functionality
     """
     @staticmethod
-    def serial(serial: builtins.str, baud_rate: builtins.int = 115200, timeout_ms: builtins.int = 10000) -> 'MCUmgrClient':
+    def serial(serial: builtins.str, baud_rate: builtins.int = 115200, timeout_ms: builtins.int = 10000, mtu: builtins.int = 127, tolerate_crlf: builtins.bool = False) -> 'MCUmgrClient':
         r"""
         Creates a new serial port based Zephyr MCUmgr SMP client.
         
@@ -130,9 +508,11 @@ class MCUmgrClient:
         * `serial` - The identifier of the serial device. (Windows: `COMxx`, Linux: `/dev/ttyXX`)
         * `baud_rate` - The baud rate of the serial port.
         * `timeout_ms` - The communication timeout, in ms.
+        * `mtu` - The maximum line length of the serial transport, in bytes.
+        * `tolerate_crlf` - Accept frames that are terminated with `\r\n` instead of `\n`.
         """
     @staticmethod
-    def usb_serial(identifier: builtins.str, baud_rate: builtins.int = 115200, timeout_ms: builtins.int = 10000) -> 'MCUmgrClient':
+    def usb_serial(identifier: builtins.str, baud_rate: builtins.int = 115200, timeout_ms: builtins.int = 10000, mtu: builtins.int = 127, tolerate_crlf: builtins.bool = False) -> 'MCUmgrClient':
         r"""
         Creates a Zephyr MCUmgr SMP client based on a USB serial port identified by VID:PID.
         
@@ -144,6 +524,8 @@ class MCUmgrClient:
         * `identifier` - A regex that identifies the device.
         * `baud_rate` - The baud rate the port should operate at.
         * `timeout_ms` - The communication timeout, in ms.
+        * `mtu` - The maximum line length of the serial transport, in bytes.
+        * `tolerate_crlf` - Accept frames that are terminated with `\r\n` instead of `\n`.
         
         ### Identifier examples
         
@@ -151,6 +533,36 @@ class MCUmgrClient:
         - `1234:89AB:12` - Vendor ID 1234, Product ID 89AB, Interface 12.
         - `1234:.*:[2-3]` - Vendor ID 1234, any Product Id, Interface 2 or 3.
         """
+    @staticmethod
+    def custom_transport(transport: Transport, timeout_ms: builtins.int = 10000) -> 'MCUmgrClient':
+        r"""
+        Creates a Zephyr MCUmgr SMP client based on a custom transport that exchanges raw SMP frames.
+        
+        Useful for links that are not supported natively, like frames tunneled through a lab setup.
+        
+        ### Arguments
+        
+        * `transport` - An object that implements the `Transport` protocol.
+                        Every call of `send` and `recv` transfers exactly one SMP frame.
+        * `timeout_ms` - The communication timeout, in ms. Passed to `recv` in seconds.
+        """
+    @staticmethod
+    def custom_serial(transport: Transport, timeout_ms: builtins.int = 10000, mtu: builtins.int = 127, tolerate_crlf: builtins.bool = False) -> 'MCUmgrClient':
+        r"""
+        Creates a Zephyr MCUmgr SMP client based on a custom transport that transfers
+        the bytes of a serial link.
+        
+        The SMP frames get encoded like on a serial port, so this works with anything
+        that is connected to the SMP UART of the device, like a relay board.
+        
+        ### Arguments
+        
+        * `transport` - An object that implements the `Transport` protocol.
+                        `recv` may return any number of bytes.
+        * `timeout_ms` - The communication timeout, in ms. Passed to `recv` in seconds.
+        * `mtu` - The maximum line length of the serial transport, in bytes.
+        * `tolerate_crlf` - Accept frames that are terminated with `\r\n` instead of `\n`.
+        """
     def set_frame_size(self, smp_frame_size: builtins.int) -> None:
         r"""
         Configures the maximum SMP frame size that we can send to the device.
@@ -171,6 +583,17 @@ class MCUmgrClient:
         When the device does not respond to packets within the set
         duration, an error will be raised.
         """
+    def link_stats(self) -> LinkStats:
+        r"""
+        Returns statistics about the link to the device.
+        
+        Helps to find the cause of slow transfers, like retransmissions
+        after timeouts, transmission errors or a high device latency.
+        """
+    def reset_link_stats(self) -> None:
+        r"""
+        Resets the statistics returned by `link_stats`.
+        """
     def check_connection(self) -> None:
         r"""
         Checks if the device is alive and responding.
@@ -193,6 +616,26 @@ class MCUmgrClient:
         * `upgrade_only` - Prevent firmware downgrades.
         * `progress` - A callback that receives progress updates.
         """
+    def bench(self, file: builtins.str = '/lfs/mcumgr_bench.bin', image_slot: builtins.bool = False, frame_sizes: typing.Optional[typing.Sequence[builtins.int]] = None, transfer_size: builtins.int = 32768, echo_count: builtins.int = 20, progress: typing.Optional[collections.abc.Callable[[builtins.str, typing.Optional[builtins.tuple[builtins.int, builtins.int]]], None]] = None) -> typing.Any:
+        r"""
+        Measures the latency and the throughput of the connection.
+        
+        Measures the round-trip time of echo requests and the upload throughput
+        for several frame sizes, to find the optimal frame size for the device.
+        
+        ### Arguments
+        
+        * `file` - The scratch file to upload to and download from. It remains on the device.
+        * `image_slot` - Upload to the secondary image slot instead of a file. The slot gets erased.
+        * `frame_sizes` - The SMP frame sizes to measure. Powers of two up to the buffer size of the device if missing.
+        * `transfer_size` - The number of bytes to transfer per measurement.
+        * `echo_count` - The number of echo requests to measure the latency with.
+        * `progress` - A callback that receives progress updates.
+        
+        ### Return
+        
+        A dict with the keys `echo`, `upload`, `download_bytes_per_second` and `optimal_frame_size`.
+        """
     def os_echo(self, msg: builtins.str) -> builtins.str:
         r"""
         Sends a message to the device and expects the same message back as response.
@@ -212,6 +655,14 @@ class MCUmgrClient:
         
         A map of task names with their respective statistics
         """
+    def os_memory_pool_statistics(self) -> 'builtins.dict[builtins.str, MemoryPoolStatistics]':
+        r"""
+        Queries the statistics of the memory pools
+        
+        ### Return
+        
+        A map of pool names with their respective statistics
+        """
     def os_set_datetime(self, datetime: datetime.datetime) -> None:
         r"""
         Sets the RTC of the device to the given datetime.
@@ -256,6 +707,13 @@ class MCUmgrClient:
         For more information about the format specifier fields, see
         the [SMP documentation](https://docs.zephyrproject.org/latest/services/device_mgmt/smp_groups/smp_group_0.html#os-application-info-request).
         """
+    def os_application_info_structured(self) -> 'ApplicationInfo':
+        r"""
+        Fetch information on the running image, parsed into its fields
+        
+        Every field is queried separately.
+        `build_time` is `None` if the device does not report it.
+        """
     def os_bootloader_info(self) -> typing.Any:
         r"""
         Fetch information on the device's bootloader
@@ -460,6 +918,32 @@ class McubootImageInfo:
         verification purposes.
         """
 
+@typing.final
+class MemoryPoolStatistics:
+    r"""
+    Statistics of an MCU memory pool
+    """
+    @property
+    def blksiz(self) -> builtins.int:
+        r"""
+        size of the memory blocks in the pool
+        """
+    @property
+    def nblks(self) -> builtins.int:
+        r"""
+        number of blocks in the pool
+        """
+    @property
+    def nfree(self) -> builtins.int:
+        r"""
+        number of free blocks
+        """
+    @property
+    def min(self) -> builtins.int:
+        r"""
+        lowest number of free blocks the pool reached
+        """
+
 @typing.final
 class SlotInfoImage:
     r"""
@@ -557,6 +1041,96 @@ class FileChecksumDataFormat(enum.Enum):
     Data is a bytes array
     """
 
+class AlreadyInstalled(MCUmgrError):
+    r"""
+    The device is already running the given firmware
+    """
+    ...
+
+class ChecksumMismatch(MCUmgrError):
+    r"""
+    A checksum of transferred data did not match
+    """
+    ...
+
+class CommandNotSupported(DeviceError):
+    r"""
+    The device does not support the command
+    """
+    ...
+
+class DeviceError(MCUmgrError):
+    r"""
+    The device responded with an error code
+    """
+    group: typing.Optional[builtins.int]
+    r"""
+    The group id of an SMP v2 error; `None` for SMP v1 errors
+    """
+    rc: builtins.int
+    r"""
+    The error code; group based for SMP v2 errors
+    """
+    kind: typing.Optional[builtins.str]
+    r"""
+    The name of the error code, like `IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER`; `None` if the code is unknown
+    """
+    help: typing.Optional[builtins.str]
+    r"""
+    A hint on how to fix the error, if available
+    """
+    is_image_downgrade_rejected: builtins.bool
+    r"""
+    The device rejected an image because the running image is newer
+    """
+    is_image_already_pending: builtins.bool
+    r"""
+    The device refused an image operation because another image is pending
+    """
+    is_not_found: builtins.bool
+    r"""
+    A file or entry does not exist on the device
+    """
+    is_rtc_not_set: builtins.bool
+    r"""
+    The RTC of the device has not been set yet
+    """
+
+class MCUmgrError(builtins.RuntimeError):
+    r"""
+    Base class of all errors raised by this library
+    """
+    ...
+
+@typing.runtime_checkable
+class Transport(typing.Protocol):
+    r"""
+    A custom transport, see `MCUmgrClient.custom_transport` and `MCUmgrClient.custom_serial`
+    """
+    def send(self, data: bytes) -> None:
+        r"""
+        Sends the given data to the device
+        """
+    def recv(self, timeout: builtins.float) -> bytes:
+        r"""
+        Receives data from the device
+        
+        Raises `TimeoutError` or returns no data if nothing arrived
+        within `timeout` seconds.
+        """
+
+class TransportError(MCUmgrError):
+    r"""
+    Sending a request or receiving its response failed
+    """
+    ...
+
+class TransportTimeout(TransportError):
+    r"""
+    The device did not respond in time
+    """
+    ...
+
 def mcuboot_get_image_info(image_data: bytes) -> 'McubootImageInfo':
     r"""
     Extract information from an MCUboot image file
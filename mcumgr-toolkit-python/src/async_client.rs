@@ -0,0 +1,823 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyBytes, PyCFunction, PyDateTime, PyTuple};
+use pyo3::{BoundObject, IntoPyObjectExt, prelude::*};
+use pyo3_stub_gen::derive::*;
+
+use crate::errors::err_to_pyerr;
+use crate::raw_py_any_command::RawPyAnyCommand;
+use crate::return_types::*;
+use crate::sha256_type::Sha256;
+
+type Client = ::mcumgr_toolkit::MCUmgrClient;
+
+/// The connection between an asyncio future and the worker thread that computes its result.
+struct Task {
+    event_loop: Py<PyAny>,
+    future: Py<PyAny>,
+    cancelled: Arc<AtomicBool>,
+    callback_error: Arc<Mutex<Option<PyErr>>>,
+}
+
+impl Task {
+    fn new(py: Python<'_>) -> PyResult<Self> {
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let on_done = {
+            let cancelled = Arc::clone(&cancelled);
+            PyCFunction::new_closure(py, None, None, move |args, _kwargs| -> PyResult<()> {
+                if args.get_item(0)?.call_method0("cancelled")?.extract()? {
+                    cancelled.store(true, Ordering::Relaxed);
+                }
+                Ok(())
+            })?
+        };
+        future.call_method1("add_done_callback", (on_done,))?;
+
+        Ok(Self {
+            event_loop: event_loop.unbind(),
+            future: future.unbind(),
+            cancelled,
+            callback_error: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Whether the operation should be aborted, because the future got
+    /// cancelled or the progress callback raised an exception.
+    fn aborted(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.callback_error.lock().unwrap().is_some()
+    }
+
+    /// Schedules a call of the progress callback on the event loop.
+    ///
+    /// Returns `false` if the operation should be aborted.
+    fn report_progress<A>(&self, progress: Option<&Py<PyAny>>, args: A) -> bool
+    where
+        A: for<'py> IntoPyObject<'py, Target = PyTuple> + Send + 'static,
+    {
+        if self.aborted() {
+            return false;
+        }
+
+        if let Some(progress) = progress {
+            let scheduled = Python::attach(|py| {
+                let progress = progress.clone_ref(py);
+                let args = args.into_pyobject(py).map_err(Into::into)?.unbind();
+                let callback_error = Arc::clone(&self.callback_error);
+                let call = PyCFunction::new_closure(py, None, None, move |call_args, _kwargs| {
+                    let py = call_args.py();
+                    if let Err(e) = progress.call1(py, args.bind(py)) {
+                        callback_error.lock().unwrap().get_or_insert(e);
+                    }
+                })?;
+                self.event_loop
+                    .call_method1(py, "call_soon_threadsafe", (call,))
+                    .map(drop)
+            });
+            if let Err(e) = scheduled {
+                self.callback_error.lock().unwrap().get_or_insert(e);
+            }
+        }
+
+        !self.aborted()
+    }
+
+    /// Resolves the future on the event loop, unless it got cancelled in the meantime.
+    fn resolve(self, result: PyResult<Py<PyAny>>) {
+        Python::attach(|py| {
+            let future = self.future;
+            let callback_error = self.callback_error;
+            let result = Mutex::new(Some(result));
+            let set_result = PyCFunction::new_closure(py, None, None, move |args, _kwargs| {
+                let py = args.py();
+                let Some(mut result) = result.lock().unwrap().take() else {
+                    return Ok(());
+                };
+                // Checked on the event loop, after all scheduled progress callbacks ran.
+                // An exception of the progress callback is the cause of the abort.
+                if let Some(e) = callback_error.lock().unwrap().take() {
+                    result = Err(e);
+                }
+                if future.call_method0(py, "done")?.extract(py)? {
+                    return Ok(());
+                }
+                match result {
+                    Ok(value) => future.call_method1(py, "set_result", (value,)),
+                    Err(e) => future.call_method1(py, "set_exception", (e.into_value(py),)),
+                }
+                .map(drop)
+            });
+
+            // Fails if the event loop is already closed; then nobody waits for the result
+            let _ = set_result.and_then(|set_result| {
+                self.event_loop
+                    .call_method1(py, "call_soon_threadsafe", (set_result,))
+            });
+        });
+    }
+}
+
+/// Wraps an awaitable returned by a function into a coroutine,
+/// so it can be used with `asyncio.create_task`.
+static RUN_COROUTINE: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+
+/// Creates a future that is already resolved with the given value.
+fn ready_future<'py>(py: Python<'py>, value: Py<PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let future = py
+        .import("asyncio")?
+        .call_method0("get_running_loop")?
+        .call_method0("create_future")?;
+    future.call_method1("set_result", (value,))?;
+    Ok(future)
+}
+
+/// An asyncio based client for Zephyr's MCUmgr SMP functionality
+///
+/// Provides the same functionality as `MCUmgrClient`, but all methods that
+/// communicate with the device are coroutines. The requests are
+/// executed on a worker thread, so they do not block the event loop.
+///
+/// Progress callbacks are called on the event loop.
+///
+/// Cancelling an upload or a download aborts the transfer.
+/// Other requests cannot be interrupted; cancelling them only discards their result.
+#[gen_stub_pyclass]
+#[pyclass(frozen)]
+pub(crate) struct AsyncMCUmgrClient {
+    // Mutex<Option<Arc<>>> so we can delete the client in __aexit__()
+    client: Mutex<Option<Arc<Client>>>,
+}
+
+impl AsyncMCUmgrClient {
+    fn get_client(&self) -> PyResult<Arc<Client>> {
+        let locked_client = self.client.lock().unwrap();
+        locked_client
+            .as_ref()
+            .map(Arc::clone)
+            .ok_or_else(|| PyRuntimeError::new_err("Client already closed"))
+    }
+
+    /// Returns a coroutine that runs `f` on a worker thread and returns its result.
+    ///
+    /// The worker thread is started when the coroutine is first awaited.
+    fn spawn<'py, T, F>(&self, py: Python<'py>, f: F) -> PyResult<Bound<'py, PyAny>>
+    where
+        T: for<'a> IntoPyObject<'a> + Send + 'static,
+        F: FnOnce(&Client, &Task) -> PyResult<T> + Send + 'static,
+    {
+        let client = self.get_client()?;
+        let f = Mutex::new(Some(f));
+
+        let start = PyCFunction::new_closure(py, None, None, move |args, _kwargs| {
+            let py = args.py();
+            let f = f
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or_else(|| PyRuntimeError::new_err("Coroutine already awaited"))?;
+
+            let task = Task::new(py)?;
+            let future = task.future.clone_ref(py);
+
+            let client = Arc::clone(&client);
+            std::thread::spawn(move || {
+                let result = f(&client, &task);
+                let result = Python::attach(|py| result.and_then(|value| value.into_py_any(py)));
+                task.resolve(result);
+            });
+
+            Ok::<_, PyErr>(future)
+        })?;
+
+        RUN_COROUTINE
+            .get_or_try_init(py, || -> PyResult<_> {
+                let module = PyModule::from_code(
+                    py,
+                    c"async def run(start):\n    return await start()\n",
+                    c"mcumgr_toolkit_async.py",
+                    c"mcumgr_toolkit_async",
+                )?;
+                Ok(module.getattr("run")?.unbind())
+            })?
+            .bind(py)
+            .call1((start,))
+    }
+}
+
+#[gen_stub_pymethods]
+#[pymethods]
+impl AsyncMCUmgrClient {
+    /// Creates a new serial port based Zephyr MCUmgr SMP client.
+    ///
+    /// For the arguments, see `MCUmgrClient.serial`.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (serial, baud_rate=115200, timeout_ms=10000, mtu=127, tolerate_crlf=false))]
+    fn serial(
+        serial: &str,
+        baud_rate: u32,
+        timeout_ms: u64,
+        mtu: usize,
+        tolerate_crlf: bool,
+    ) -> PyResult<Self> {
+        let client = crate::open_serial(serial, baud_rate, timeout_ms, mtu, tolerate_crlf)?;
+        Ok(Self {
+            client: Mutex::new(Some(Arc::new(client))),
+        })
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on a USB serial port identified by VID:PID.
+    ///
+    /// For the arguments, see `MCUmgrClient.usb_serial`.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (identifier, baud_rate=115200, timeout_ms=10000, mtu=127, tolerate_crlf=false))]
+    fn usb_serial(
+        identifier: &str,
+        baud_rate: u32,
+        timeout_ms: u64,
+        mtu: usize,
+        tolerate_crlf: bool,
+    ) -> PyResult<Self> {
+        let client = crate::open_usb_serial(identifier, baud_rate, timeout_ms, mtu, tolerate_crlf)?;
+        Ok(Self {
+            client: Mutex::new(Some(Arc::new(client))),
+        })
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on a custom transport that exchanges raw SMP frames.
+    ///
+    /// For the arguments, see `MCUmgrClient.custom_transport`.
+    ///
+    /// The methods of the transport get called from worker threads.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (transport, timeout_ms=10000))]
+    fn custom_transport(
+        #[gen_stub(override_type(type_repr = "Transport"))] transport: &Bound<'_, PyAny>,
+        timeout_ms: u64,
+    ) -> PyResult<Self> {
+        let client = crate::open_custom_transport(transport, timeout_ms)?;
+        Ok(Self {
+            client: Mutex::new(Some(Arc::new(client))),
+        })
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on a custom transport that transfers
+    /// the bytes of a serial link.
+    ///
+    /// For the arguments, see `MCUmgrClient.custom_serial`.
+    ///
+    /// The methods of the transport get called from worker threads.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (transport, timeout_ms=10000, mtu=127, tolerate_crlf=false))]
+    fn custom_serial(
+        #[gen_stub(override_type(type_repr = "Transport"))] transport: &Bound<'_, PyAny>,
+        timeout_ms: u64,
+        mtu: usize,
+        tolerate_crlf: bool,
+    ) -> PyResult<Self> {
+        let client = crate::open_custom_serial(transport, timeout_ms, mtu, tolerate_crlf)?;
+        Ok(Self {
+            client: Mutex::new(Some(Arc::new(client))),
+        })
+    }
+
+    /// Configures the maximum SMP frame size that we can send to the device.
+    ///
+    /// See `MCUmgrClient.set_frame_size`.
+    fn set_frame_size(&self, smp_frame_size: usize) -> PyResult<()> {
+        self.get_client()?.set_frame_size(smp_frame_size);
+        Ok(())
+    }
+
+    /// Configures the maximum SMP frame size automatically.
+    ///
+    /// See `MCUmgrClient.use_auto_frame_size`.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn use_auto_frame_size<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client.use_auto_frame_size().map_err(err_to_pyerr)
+        })
+    }
+
+    /// Changes the communication timeout.
+    ///
+    /// See `MCUmgrClient.set_timeout_ms`.
+    fn set_timeout_ms(&self, timeout_ms: u64) -> PyResult<()> {
+        self.get_client()?
+            .set_timeout(Duration::from_millis(timeout_ms))
+            .map_err(err_to_pyerr)
+    }
+
+    /// Returns statistics about the link to the device.
+    ///
+    /// See `MCUmgrClient.link_stats`.
+    fn link_stats(&self) -> PyResult<LinkStats> {
+        Ok(self.get_client()?.link_stats().into())
+    }
+
+    /// Resets the statistics returned by `link_stats`.
+    fn reset_link_stats(&self) -> PyResult<()> {
+        self.get_client()?.reset_link_stats();
+        Ok(())
+    }
+
+    /// Checks if the device is alive and responding.
+    ///
+    /// See `MCUmgrClient.check_connection`.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn check_connection<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client.check_connection().map_err(err_to_pyerr)
+        })
+    }
+
+    /// High-level firmware update routine.
+    ///
+    /// See `MCUmgrClient.firmware_update`.
+    ///
+    /// Cancelling aborts the update.
+    ///
+    #[pyo3(signature = (firmware, checksum=None, bootloader_type=None, skip_reboot=false, force_confirm=false, upgrade_only=false, progress=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn firmware_update<'py>(
+        &self,
+        py: Python<'py>,
+        firmware: &Bound<'py, PyBytes>,
+        checksum: Option<Sha256>,
+        #[gen_stub(override_type(type_repr="typing.Optional[typing.Literal['MCUboot']]", imports=("typing")))]
+        bootloader_type: Option<String>,
+        skip_reboot: bool,
+        force_confirm: bool,
+        upgrade_only: bool,
+        #[gen_stub(override_type(type_repr="typing.Optional[collections.abc.Callable[[builtins.str, typing.Optional[builtins.tuple[builtins.int, builtins.int]]], None]]", imports=("builtins", "collections.abc", "typing")))]
+        progress: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let firmware = firmware.as_bytes().to_vec();
+        let checksum = checksum.map(|val| val.0);
+        let params = crate::firmware_update_params(
+            bootloader_type,
+            skip_reboot,
+            force_confirm,
+            upgrade_only,
+        )?;
+
+        self.spawn(py, move |client, task| {
+            let mut cb = |msg: ::mcumgr_toolkit::client::FirmwareUpdateStep, prog| {
+                task.report_progress(progress.as_ref(), (msg.to_string(), prog))
+            };
+            client
+                .firmware_update(firmware, checksum, params, Some(&mut cb))
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Measures the latency and the throughput of the connection.
+    ///
+    /// See `MCUmgrClient.bench`.
+    ///
+    /// Cancelling aborts the measurement.
+    ///
+    #[pyo3(signature = (file="/lfs/mcumgr_bench.bin", image_slot=false, frame_sizes=None, transfer_size=32768, echo_count=20, progress=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, typing.Any]", imports=("collections.abc", "typing")))]
+    fn bench<'py>(
+        &self,
+        py: Python<'py>,
+        file: &str,
+        image_slot: bool,
+        frame_sizes: Option<Vec<usize>>,
+        transfer_size: usize,
+        echo_count: usize,
+        #[gen_stub(override_type(type_repr="typing.Optional[collections.abc.Callable[[builtins.str, typing.Optional[builtins.tuple[builtins.int, builtins.int]]], None]]", imports=("builtins", "collections.abc", "typing")))]
+        progress: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let params = crate::bench_params(file, image_slot, frame_sizes, transfer_size, echo_count);
+
+        self.spawn(py, move |client, task| {
+            let mut cb = |step: ::mcumgr_toolkit::client::bench::BenchStep, prog| {
+                task.report_progress(progress.as_ref(), (step.to_string(), prog))
+            };
+            let result = client.bench(&params, Some(&mut cb)).map_err(err_to_pyerr)?;
+            Python::attach(|py| {
+                serde_pyobject::to_pyobject(py, &result)
+                    .map(Bound::unbind)
+                    .map_err(Into::into)
+            })
+        })
+    }
+
+    /// Sends a message to the device and expects the same message back as response.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.str]", imports=("builtins", "collections.abc", "typing")))]
+    fn os_echo<'py>(&self, py: Python<'py>, msg: String) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            client.os_echo(msg).map_err(err_to_pyerr)
+        })
+    }
+
+    /// Queries live task statistics
+    ///
+    /// See `MCUmgrClient.os_task_statistics`.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.dict[builtins.str, TaskStatistics]]", imports=("builtins", "collections.abc", "typing")))]
+    fn os_task_statistics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client
+                .os_task_statistics()
+                .map(|tasks| {
+                    tasks
+                        .into_iter()
+                        .map(|(name, stats)| (name, TaskStatistics::from(stats)))
+                        .collect::<HashMap<_, _>>()
+                })
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Queries the statistics of the memory pools
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.dict[builtins.str, MemoryPoolStatistics]]", imports=("builtins", "collections.abc", "typing")))]
+    fn os_memory_pool_statistics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client
+                .os_memory_pool_statistics()
+                .map(|pools| {
+                    pools
+                        .into_iter()
+                        .map(|(name, stats)| (name, MemoryPoolStatistics::from(stats)))
+                        .collect::<HashMap<_, _>>()
+                })
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Sets the RTC of the device to the given datetime.
+    ///
+    /// Uses the contained local time and discards timezone information.
+    ///
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn os_set_datetime<'py>(
+        &self,
+        py: Python<'py>,
+        datetime: Bound<'py, PyDateTime>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let datetime: chrono::NaiveDateTime = datetime.extract()?;
+        self.spawn(py, move |client, _| {
+            client.os_set_datetime(datetime).map_err(err_to_pyerr)
+        })
+    }
+
+    /// Retrieves the device RTC's datetime.
+    ///
+    /// Will not contain timezone information.
+    ///
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, datetime.datetime]", imports=("collections.abc", "datetime", "typing")))]
+    fn os_get_datetime<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client.os_get_datetime().map_err(err_to_pyerr)
+        })
+    }
+
+    /// Issues a system reset.
+    ///
+    /// See `MCUmgrClient.os_system_reset`.
+    ///
+    #[pyo3(signature = (force=false, boot_mode=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn os_system_reset<'py>(
+        &self,
+        py: Python<'py>,
+        force: bool,
+        boot_mode: Option<u8>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            client
+                .os_system_reset(force, boot_mode)
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Fetch parameters from the MCUmgr library
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, MCUmgrParameters]", imports=("collections.abc", "typing")))]
+    fn os_mcumgr_parameters<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client
+                .os_mcumgr_parameters()
+                .map(MCUmgrParameters::from)
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Fetch information on the running image
+    ///
+    /// See `MCUmgrClient.os_application_info`.
+    ///
+    #[pyo3(signature = (format=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.str]", imports=("builtins", "collections.abc", "typing")))]
+    fn os_application_info<'py>(
+        &self,
+        py: Python<'py>,
+        format: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            client
+                .os_application_info(format.as_deref())
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Fetch information on the running image, parsed into its fields
+    ///
+    /// See `MCUmgrClient.os_application_info_structured`.
+    ///
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, ApplicationInfo]", imports=("collections.abc", "typing")))]
+    fn os_application_info_structured<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client
+                .os_application_info_structured()
+                .map(ApplicationInfo::from)
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Fetch information on the device's bootloader
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, typing.Any]", imports=("collections.abc", "typing")))]
+    fn os_bootloader_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            let info = client.os_bootloader_info().map_err(err_to_pyerr)?;
+            Python::attach(|py| {
+                serde_pyobject::to_pyobject(py, &info)
+                    .map(Bound::unbind)
+                    .map_err(Into::into)
+            })
+        })
+    }
+
+    /// Obtain a list of images with their current state.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.list[ImageState]]", imports=("builtins", "collections.abc", "typing")))]
+    fn image_get_state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            let images = client.image_get_state().map_err(err_to_pyerr)?;
+            Ok(Python::attach(|py| {
+                images
+                    .into_iter()
+                    .map(|val| ImageState::from_response(py, val))
+                    .collect::<Vec<_>>()
+            }))
+        })
+    }
+
+    /// Modify the current image state and return the new state
+    ///
+    /// See `MCUmgrClient.image_set_state`.
+    ///
+    #[pyo3(signature = (hash=None, confirm=false))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.list[ImageState]]", imports=("builtins", "collections.abc", "typing")))]
+    fn image_set_state<'py>(
+        &self,
+        py: Python<'py>,
+        hash: Option<Sha256>,
+        confirm: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let hash = hash.map(|val| val.0);
+        self.spawn(py, move |client, _| {
+            let images = client
+                .image_set_state(hash, confirm)
+                .map_err(err_to_pyerr)?;
+            Ok(Python::attach(|py| {
+                images
+                    .into_iter()
+                    .map(|val| ImageState::from_response(py, val))
+                    .collect::<Vec<_>>()
+            }))
+        })
+    }
+
+    /// Upload a firmware image to an image slot.
+    ///
+    /// See `MCUmgrClient.image_upload`.
+    ///
+    /// Cancelling aborts the upload.
+    ///
+    #[pyo3(signature = (data, image=None, checksum=None, upgrade_only=false, progress=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn image_upload<'py>(
+        &self,
+        py: Python<'py>,
+        data: &Bound<'py, PyBytes>,
+        image: Option<u32>,
+        checksum: Option<Sha256>,
+        upgrade_only: bool,
+        #[gen_stub(override_type(type_repr="typing.Optional[collections.abc.Callable[[builtins.int, builtins.int], None]]", imports=("builtins", "collections.abc", "typing")))]
+        progress: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let data = data.as_bytes().to_vec();
+        let checksum = checksum.map(|val| val.0);
+        self.spawn(py, move |client, task| {
+            let mut cb = |current, total| task.report_progress(progress.as_ref(), (current, total));
+            client
+                .image_upload(data, image, checksum, upgrade_only, Some(&mut cb))
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Erase image slot on target device.
+    ///
+    /// See `MCUmgrClient.image_erase`.
+    ///
+    #[pyo3(signature = (slot=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn image_erase<'py>(&self, py: Python<'py>, slot: Option<u32>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            client.image_erase(slot).map_err(err_to_pyerr)
+        })
+    }
+
+    /// Obtain a list of available image slots.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.list[SlotInfoImage]]", imports=("builtins", "collections.abc", "typing")))]
+    fn image_slot_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            let images = client.image_slot_info().map_err(err_to_pyerr)?;
+            Python::attach(|py| {
+                images
+                    .into_iter()
+                    .map(|val| SlotInfoImage::from_response(py, val))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+        })
+    }
+
+    /// Load a file from the device.
+    ///
+    /// See `MCUmgrClient.fs_file_download`.
+    ///
+    /// Cancelling aborts the download.
+    ///
+    #[pyo3(signature = (name, progress=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.bytes]", imports=("builtins", "collections.abc", "typing")))]
+    fn fs_file_download<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        #[gen_stub(override_type(type_repr="typing.Optional[collections.abc.Callable[[builtins.int, builtins.int], None]]", imports=("builtins", "collections.abc", "typing")))]
+        progress: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, task| {
+            let mut data = vec![];
+            let mut cb = |current, total| task.report_progress(progress.as_ref(), (current, total));
+            client
+                .fs_file_download(name, &mut data, Some(&mut cb))
+                .map_err(err_to_pyerr)?;
+            Ok(Python::attach(|py| PyBytes::new(py, &data).unbind()))
+        })
+    }
+
+    /// Write a file to the device.
+    ///
+    /// See `MCUmgrClient.fs_file_upload`.
+    ///
+    /// Cancelling aborts the upload.
+    ///
+    #[pyo3(signature = (name, data, progress=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn fs_file_upload<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        data: &Bound<'py, PyBytes>,
+        #[gen_stub(override_type(type_repr="typing.Optional[collections.abc.Callable[[builtins.int, builtins.int], None]]", imports=("builtins", "collections.abc", "typing")))]
+        progress: Option<Py<PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let data = data.as_bytes().to_vec();
+        self.spawn(py, move |client, task| {
+            let mut cb = |current, total| task.report_progress(progress.as_ref(), (current, total));
+            client
+                .fs_file_upload(name, data.as_slice(), data.len() as u64, Some(&mut cb))
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Queries the file status
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, FileStatus]", imports=("collections.abc", "typing")))]
+    fn fs_file_status<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            client
+                .fs_file_status(name)
+                .map(FileStatus::from)
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Computes the hash/checksum of a file
+    ///
+    /// See `MCUmgrClient.fs_file_checksum`.
+    ///
+    #[pyo3(signature = (name, algorithm=None, offset=0, length=None))]
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, FileChecksum]", imports=("collections.abc", "typing")))]
+    fn fs_file_checksum<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        algorithm: Option<String>,
+        offset: u64,
+        length: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            let checksum = client
+                .fs_file_checksum(name, algorithm, offset, length)
+                .map_err(err_to_pyerr)?;
+            Ok(Python::attach(|py| {
+                FileChecksum::from_response(py, checksum)
+            }))
+        })
+    }
+
+    /// Queries which hash/checksum algorithms are available on the target
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.dict[builtins.str, FileChecksumProperties]]", imports=("builtins", "collections.abc", "typing")))]
+    fn fs_supported_checksum_types<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client
+                .fs_supported_checksum_types()
+                .map(|val| {
+                    val.into_iter()
+                        .map(|(key, value)| (key, FileChecksumProperties::from(value)))
+                        .collect::<HashMap<_, _>>()
+                })
+                .map_err(err_to_pyerr)
+        })
+    }
+
+    /// Close all device files MCUmgr has currently open
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn fs_file_close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| client.fs_file_close().map_err(err_to_pyerr))
+    }
+
+    /// Run a shell command.
+    ///
+    /// See `MCUmgrClient.shell_execute`.
+    ///
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, builtins.str]", imports=("builtins", "collections.abc", "typing")))]
+    fn shell_execute<'py>(
+        &self,
+        py: Python<'py>,
+        argv: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, move |client, _| {
+            let (exitcode, data) = client.shell_execute(&argv).map_err(err_to_pyerr)?;
+            crate::shell_output(exitcode, data)
+        })
+    }
+
+    /// Erase the `storage_partition` flash partition.
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, None]", imports=("collections.abc", "typing")))]
+    fn zephyr_erase_storage<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.spawn(py, |client, _| {
+            client.zephyr_erase_storage().map_err(err_to_pyerr)
+        })
+    }
+
+    /// Execute a raw MCUmgrCommand.
+    ///
+    /// See `MCUmgrClient.raw_command`.
+    ///
+    #[gen_stub(override_return_type(type_repr="collections.abc.Coroutine[typing.Any, typing.Any, typing.Any]", imports=("collections.abc", "typing")))]
+    fn raw_command<'py>(
+        &self,
+        py: Python<'py>,
+        write_operation: bool,
+        group_id: u16,
+        command_id: u8,
+        data: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let command = RawPyAnyCommand::new(write_operation, group_id, command_id, data)?;
+        self.spawn(py, move |client, _| {
+            let result = client.raw_command(&command).map_err(err_to_pyerr)?;
+            Python::attach(|py| RawPyAnyCommand::convert_result(py, result).map(Bound::unbind))
+        })
+    }
+
+    #[gen_stub(override_return_type(type_repr="asyncio.Future[AsyncMCUmgrClient]", imports=("asyncio")))]
+    fn __aenter__<'py>(slf: Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        ready_future(slf.py(), slf.into_any().unbind())
+    }
+
+    /// Closes the connection
+    #[gen_stub(override_return_type(type_repr="asyncio.Future[builtins.bool]", imports=("asyncio", "builtins")))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: Py<PyAny>,
+        _exc_value: Py<PyAny>,
+        _traceback: Py<PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.client.lock().unwrap().take();
+        ready_future(py, false.into_py_any(py)?)
+    }
+}
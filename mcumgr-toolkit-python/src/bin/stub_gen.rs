@@ -4,5 +4,17 @@ fn main() -> Result<()> {
     // `stub_info` is a function defined by `define_stub_info_gatherer!` macro.
     let stub = mcumgr_toolkit_python::stub_info()?;
     stub.generate()?;
+
+    // The stub generator does not support class decorators,
+    // but `Transport` is checked with `isinstance` and has to be runtime checkable.
+    let path = stub.python_root.join("mcumgr_toolkit.pyi");
+    let content = std::fs::read_to_string(&path)?;
+    let content = content.replacen(
+        "class Transport(typing.Protocol):",
+        "@typing.runtime_checkable\nclass Transport(typing.Protocol):",
+        1,
+    );
+    std::fs::write(&path, content)?;
+
     Ok(())
 }
@@ -1,4 +1,14 @@
+use std::io::ErrorKind;
+
+use ::mcumgr_toolkit::{
+    client::{FirmwareUpdateError, MCUmgrClientError},
+    connection::ExecuteError,
+    smp_errors,
+    transport::{ReceiveError, SendError},
+};
 use miette::Diagnostic;
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use pyo3_stub_gen::PyStubType;
 use thiserror::Error;
 
 /// Possible Python library errors.
@@ -8,3 +18,229 @@ pub(crate) enum McubootPythonError {
     #[diagnostic(code(mcumgr_toolkit::python::parse_bootloader_type))]
     InvalidBootloaderString(#[source] strum::ParseError, String),
 }
+
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    MCUmgrError,
+    PyRuntimeError,
+    "Base class of all errors raised by this library"
+);
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    TransportError,
+    MCUmgrError,
+    "Sending a request or receiving its response failed"
+);
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    TransportTimeout,
+    TransportError,
+    "The device did not respond in time"
+);
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    DeviceError,
+    MCUmgrError,
+    "The device responded with an error code"
+);
+
+const fn device_error_attr(
+    name: &'static str,
+    r#type: fn() -> pyo3_stub_gen::TypeInfo,
+    doc: &'static str,
+) -> pyo3_stub_gen::type_info::MemberInfo {
+    pyo3_stub_gen::type_info::MemberInfo {
+        name,
+        r#type,
+        doc,
+        default: None,
+        deprecated: None,
+    }
+}
+
+// The attributes get set at runtime in `device_error_to_pyerr`,
+// so they have to be declared to the stub generator by hand.
+pyo3_stub_gen::inventory::submit! {
+    pyo3_stub_gen::type_info::PyMethodsInfo {
+        struct_id: std::any::TypeId::of::<DeviceError>,
+        attrs: &[
+            device_error_attr(
+                "group",
+                <Option<u16> as PyStubType>::type_output,
+                "The group id of an SMP v2 error; `None` for SMP v1 errors",
+            ),
+            device_error_attr(
+                "rc",
+                <i32 as PyStubType>::type_output,
+                "The error code; group based for SMP v2 errors",
+            ),
+            device_error_attr(
+                "kind",
+                <Option<String> as PyStubType>::type_output,
+                "The name of the error code, like `IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER`; `None` if the code is unknown",
+            ),
+            device_error_attr(
+                "help",
+                <Option<String> as PyStubType>::type_output,
+                "A hint on how to fix the error, if available",
+            ),
+            device_error_attr(
+                "is_image_downgrade_rejected",
+                <bool as PyStubType>::type_output,
+                "The device rejected an image because the running image is newer",
+            ),
+            device_error_attr(
+                "is_image_already_pending",
+                <bool as PyStubType>::type_output,
+                "The device refused an image operation because another image is pending",
+            ),
+            device_error_attr(
+                "is_not_found",
+                <bool as PyStubType>::type_output,
+                "A file or entry does not exist on the device",
+            ),
+            device_error_attr(
+                "is_rtc_not_set",
+                <bool as PyStubType>::type_output,
+                "The RTC of the device has not been set yet",
+            ),
+        ],
+        getters: &[],
+        setters: &[],
+        methods: &[],
+        file: file!(),
+        line: line!(),
+        column: column!(),
+    }
+}
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    CommandNotSupported,
+    DeviceError,
+    "The device does not support the command"
+);
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    ChecksumMismatch,
+    MCUmgrError,
+    "A checksum of transferred data did not match"
+);
+pyo3_stub_gen::create_exception!(
+    mcumgr_toolkit,
+    AlreadyInstalled,
+    MCUmgrError,
+    "The device is already running the given firmware"
+);
+
+fn is_timeout(err: &ExecuteError) -> bool {
+    match err {
+        ExecuteError::SendFailed(SendError::TransportError(e))
+        | ExecuteError::ReceiveFailed(ReceiveError::TransportError(e)) => {
+            e.kind() == ErrorKind::TimedOut
+        }
+        _ => false,
+    }
+}
+
+fn device_error_to_pyerr(
+    err: &smp_errors::DeviceError,
+    command_not_supported: bool,
+    message: String,
+) -> PyErr {
+    let pyerr = if command_not_supported {
+        CommandNotSupported::new_err(message)
+    } else {
+        DeviceError::new_err(message)
+    };
+
+    let (group, rc, kind) = match err {
+        smp_errors::DeviceError::V1 { rc, .. } => (
+            None,
+            *rc,
+            smp_errors::MCUmgrErr::from_repr(*rc).map(|code| code.to_string()),
+        ),
+        smp_errors::DeviceError::V2 { group, rc } => {
+            (Some(*group), *rc, err.kind().map(|kind| kind.to_string()))
+        }
+    };
+    let help = err.help().map(|help| help.to_string());
+
+    Python::attach(|py| {
+        let value = pyerr.value(py);
+        let result = (|| {
+            value.setattr("group", group)?;
+            value.setattr("rc", rc)?;
+            value.setattr("kind", kind)?;
+            value.setattr("help", help)?;
+            value.setattr(
+                "is_image_downgrade_rejected",
+                err.is_image_downgrade_rejected(),
+            )?;
+            value.setattr("is_image_already_pending", err.is_image_already_pending())?;
+            value.setattr("is_not_found", err.is_not_found())?;
+            value.setattr("is_rtc_not_set", err.is_rtc_not_set())
+        })();
+        match result {
+            Ok(()) => pyerr,
+            Err(e) => e,
+        }
+    })
+}
+
+/// Finds the Python exception that caused the error, like one raised by a custom transport.
+fn python_cause(report: &miette::Report) -> Option<&PyErr> {
+    report
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .find_map(|err| err.get_ref()?.downcast_ref::<PyErr>())
+}
+
+/// Converts an error into the matching Python exception.
+///
+/// The exception type is determined by the first error in the source chain
+/// that has a specific exception type; all other errors raise `MCUmgrError`.
+///
+/// If a Python exception caused the error, it becomes the `__cause__`.
+pub(crate) fn err_to_pyerr<E: Into<miette::Report>>(err: E) -> PyErr {
+    let report: miette::Report = err.into();
+    let pyerr = typed_pyerr(&report, format!("{report:?}"));
+
+    if let Some(cause) = python_cause(&report) {
+        Python::attach(|py| pyerr.set_cause(py, Some(cause.clone_ref(py))));
+    }
+
+    pyerr
+}
+
+fn typed_pyerr(report: &miette::Report, message: String) -> PyErr {
+    for cause in report.chain() {
+        if let Some(FirmwareUpdateError::AlreadyInstalled) = cause.downcast_ref() {
+            return AlreadyInstalled::new_err(message);
+        }
+
+        if let Some(
+            MCUmgrClientError::ChecksumMismatch | MCUmgrClientError::ChecksumMismatchOnDevice,
+        ) = cause.downcast_ref()
+        {
+            return ChecksumMismatch::new_err(message);
+        }
+
+        if let Some(err) = cause.downcast_ref::<ExecuteError>() {
+            return match err {
+                ExecuteError::ErrorResponse(device_error) => {
+                    device_error_to_pyerr(device_error, err.command_not_supported(), message)
+                }
+                ExecuteError::SendFailed(_) | ExecuteError::ReceiveFailed(_) => {
+                    if is_timeout(err) {
+                        TransportTimeout::new_err(message)
+                    } else {
+                        TransportError::new_err(message)
+                    }
+                }
+                _ => MCUmgrError::new_err(message),
+            };
+        }
+    }
+
+    MCUmgrError::new_err(message)
+}
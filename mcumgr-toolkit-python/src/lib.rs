@@ -13,17 +13,24 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use ::mcumgr_toolkit::bootloader::BootloaderType;
-use ::mcumgr_toolkit::client::{FirmwareUpdateParams, FirmwareUpdateStep};
-
-use crate::errors::McubootPythonError;
+use ::mcumgr_toolkit::client::{
+    FirmwareUpdateParams, FirmwareUpdateStep,
+    bench::{BenchParams, BenchStep, BenchTarget},
+};
+use ::mcumgr_toolkit::transport::serial::SerialTransportBuilder;
+
+use crate::errors::{McubootPythonError, err_to_pyerr};
+use crate::py_transport::{PyFrameTransport, PySerialStream};
 use crate::raw_py_any_command::RawPyAnyCommand;
 use crate::sha256_type::Sha256;
 
 mod return_types;
 pub use return_types::*;
 
+mod async_client;
 mod errors;
 mod mcuboot;
+mod py_transport;
 mod raw_py_any_command;
 mod repr_macro;
 mod sha256_type;
@@ -36,9 +43,116 @@ struct MCUmgrClient {
     client: Mutex<Option<Arc<::mcumgr_toolkit::MCUmgrClient>>>,
 }
 
-fn err_to_pyerr<E: Into<miette::Report>>(err: E) -> PyErr {
-    let e: miette::Report = err.into();
-    PyRuntimeError::new_err(format!("{e:?}"))
+fn open_serial(
+    serial: &str,
+    baud_rate: u32,
+    timeout_ms: u64,
+    mtu: usize,
+    tolerate_crlf: bool,
+) -> PyResult<::mcumgr_toolkit::MCUmgrClient> {
+    let serial = serialport::new(serial, baud_rate)
+        .timeout(Duration::from_millis(timeout_ms))
+        .open()
+        .into_diagnostic()
+        .map_err(err_to_pyerr)?;
+    let transport = SerialTransportBuilder::new()
+        .mtu(mtu)
+        .tolerate_crlf(tolerate_crlf)
+        .build(serial);
+    Ok(::mcumgr_toolkit::MCUmgrClient::new_from_transport(
+        transport,
+    ))
+}
+
+fn open_custom_transport(
+    transport: &Bound<'_, PyAny>,
+    timeout_ms: u64,
+) -> PyResult<::mcumgr_toolkit::MCUmgrClient> {
+    let transport = PyFrameTransport::new(transport, Duration::from_millis(timeout_ms))?;
+    Ok(::mcumgr_toolkit::MCUmgrClient::new_from_transport(
+        transport,
+    ))
+}
+
+fn open_custom_serial(
+    transport: &Bound<'_, PyAny>,
+    timeout_ms: u64,
+    mtu: usize,
+    tolerate_crlf: bool,
+) -> PyResult<::mcumgr_toolkit::MCUmgrClient> {
+    let stream = PySerialStream::new(transport, Duration::from_millis(timeout_ms))?;
+    let transport = SerialTransportBuilder::new()
+        .mtu(mtu)
+        .tolerate_crlf(tolerate_crlf)
+        .build(stream);
+    Ok(::mcumgr_toolkit::MCUmgrClient::new_from_transport(
+        transport,
+    ))
+}
+
+fn open_usb_serial(
+    identifier: &str,
+    baud_rate: u32,
+    timeout_ms: u64,
+    mtu: usize,
+    tolerate_crlf: bool,
+) -> PyResult<::mcumgr_toolkit::MCUmgrClient> {
+    let port = ::mcumgr_toolkit::client::find_usb_serial_port(identifier).map_err(err_to_pyerr)?;
+    open_serial(&port.port_name, baud_rate, timeout_ms, mtu, tolerate_crlf)
+}
+
+fn firmware_update_params(
+    bootloader_type: Option<String>,
+    skip_reboot: bool,
+    force_confirm: bool,
+    upgrade_only: bool,
+) -> PyResult<FirmwareUpdateParams> {
+    let bootloader_type = match bootloader_type {
+        Some(bootloader_type) => Some(
+            BootloaderType::from_str(&bootloader_type)
+                .map_err(|e| McubootPythonError::InvalidBootloaderString(e, bootloader_type))
+                .map_err(err_to_pyerr)?,
+        ),
+        None => None,
+    };
+
+    Ok(FirmwareUpdateParams {
+        bootloader_type,
+        skip_reboot,
+        force_confirm,
+        upgrade_only,
+    })
+}
+
+fn bench_params(
+    file: &str,
+    image_slot: bool,
+    frame_sizes: Option<Vec<usize>>,
+    transfer_size: usize,
+    echo_count: usize,
+) -> BenchParams {
+    BenchParams {
+        target: if image_slot {
+            BenchTarget::ImageSlot
+        } else {
+            BenchTarget::File(file.to_string())
+        },
+        frame_sizes: frame_sizes.unwrap_or_default(),
+        transfer_size,
+        echo_count,
+    }
+}
+
+fn shell_output(exitcode: i32, data: String) -> PyResult<String> {
+    if exitcode < 0 {
+        return Err(PyRuntimeError::new_err(format!(
+            "Shell command returned error exit code: {}\n{}",
+            ::mcumgr_toolkit::Errno::errno_to_string(exitcode),
+            data
+        )));
+    }
+
+    Ok(data)
 }
 
 impl MCUmgrClient {
@@ -61,16 +175,19 @@ impl MCUmgrClient {
     /// * `serial` - The identifier of the serial device. (Windows: `COMxx`, Linux: `/dev/ttyXX`)
     /// * `baud_rate` - The baud rate of the serial port.
     /// * `timeout_ms` - The communication timeout, in ms.
+    /// * `mtu` - The maximum line length of the serial transport, in bytes.
+    /// * `tolerate_crlf` - Accept frames that are terminated with `\r\n` instead of `\n`.
     ///
     #[staticmethod]
-    #[pyo3(signature = (serial, baud_rate=115200, timeout_ms=10000))]
-    fn serial(serial: &str, baud_rate: u32, timeout_ms: u64) -> PyResult<Self> {
-        let serial = serialport::new(serial, baud_rate)
-            .timeout(Duration::from_millis(timeout_ms))
-            .open()
-            .into_diagnostic()
-            .map_err(err_to_pyerr)?;
-        let client = ::mcumgr_toolkit::MCUmgrClient::new_from_serial(serial);
+    #[pyo3(signature = (serial, baud_rate=115200, timeout_ms=10000, mtu=127, tolerate_crlf=false))]
+    fn serial(
+        serial: &str,
+        baud_rate: u32,
+        timeout_ms: u64,
+        mtu: usize,
+        tolerate_crlf: bool,
+    ) -> PyResult<Self> {
+        let client = open_serial(serial, baud_rate, timeout_ms, mtu, tolerate_crlf)?;
         Ok(MCUmgrClient {
             client: Mutex::new(Some(Arc::new(client))),
         })
@@ -86,6 +203,8 @@ impl MCUmgrClient {
     /// * `identifier` - A regex that identifies the device.
     /// * `baud_rate` - The baud rate the port should operate at.
     /// * `timeout_ms` - The communication timeout, in ms.
+    /// * `mtu` - The maximum line length of the serial transport, in bytes.
+    /// * `tolerate_crlf` - Accept frames that are terminated with `\r\n` instead of `\n`.
     ///
     /// ### Identifier examples
     ///
@@ -94,14 +213,65 @@ impl MCUmgrClient {
     /// - `1234:.*:[2-3]` - Vendor ID 1234, any Product Id, Interface 2 or 3.
     ///
     #[staticmethod]
-    #[pyo3(signature = (identifier, baud_rate=115200, timeout_ms=10000))]
-    fn usb_serial(identifier: &str, baud_rate: u32, timeout_ms: u64) -> PyResult<Self> {
-        let client = ::mcumgr_toolkit::MCUmgrClient::new_from_usb_serial(
-            identifier,
-            baud_rate,
-            Duration::from_millis(timeout_ms),
-        )
-        .map_err(err_to_pyerr)?;
+    #[pyo3(signature = (identifier, baud_rate=115200, timeout_ms=10000, mtu=127, tolerate_crlf=false))]
+    fn usb_serial(
+        identifier: &str,
+        baud_rate: u32,
+        timeout_ms: u64,
+        mtu: usize,
+        tolerate_crlf: bool,
+    ) -> PyResult<Self> {
+        let client = open_usb_serial(identifier, baud_rate, timeout_ms, mtu, tolerate_crlf)?;
+        Ok(MCUmgrClient {
+            client: Mutex::new(Some(Arc::new(client))),
+        })
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on a custom transport that exchanges raw SMP frames.
+    ///
+    /// Useful for links that are not supported natively, like frames tunneled through a lab setup.
+    ///
+    /// ### Arguments
+    ///
+    /// * `transport` - An object that implements the `Transport` protocol.
+    ///                 Every call of `send` and `recv` transfers exactly one SMP frame.
+    /// * `timeout_ms` - The communication timeout, in ms. Passed to `recv` in seconds.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (transport, timeout_ms=10000))]
+    fn custom_transport(
+        #[gen_stub(override_type(type_repr = "Transport"))] transport: &Bound<'_, PyAny>,
+        timeout_ms: u64,
+    ) -> PyResult<Self> {
+        let client = open_custom_transport(transport, timeout_ms)?;
+        Ok(MCUmgrClient {
+            client: Mutex::new(Some(Arc::new(client))),
+        })
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on a custom transport that transfers
+    /// the bytes of a serial link.
+    ///
+    /// The SMP frames get encoded like on a serial port, so this works with anything
+    /// that is connected to the SMP UART of the device, like a relay board.
+    ///
+    /// ### Arguments
+    ///
+    /// * `transport` - An object that implements the `Transport` protocol.
+    ///                 `recv` may return any number of bytes.
+    /// * `timeout_ms` - The communication timeout, in ms. Passed to `recv` in seconds.
+    /// * `mtu` - The maximum line length of the serial transport, in bytes.
+    /// * `tolerate_crlf` - Accept frames that are terminated with `\r\n` instead of `\n`.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (transport, timeout_ms=10000, mtu=127, tolerate_crlf=false))]
+    fn custom_serial(
+        #[gen_stub(override_type(type_repr = "Transport"))] transport: &Bound<'_, PyAny>,
+        timeout_ms: u64,
+        mtu: usize,
+        tolerate_crlf: bool,
+    ) -> PyResult<Self> {
+        let client = open_custom_serial(transport, timeout_ms, mtu, tolerate_crlf)?;
         Ok(MCUmgrClient {
             client: Mutex::new(Some(Arc::new(client))),
         })
@@ -135,6 +305,20 @@ impl MCUmgrClient {
             .map_err(err_to_pyerr)
     }
 
+    /// Returns statistics about the link to the device.
+    ///
+    /// Helps to find the cause of slow transfers, like retransmissions
+    /// after timeouts, transmission errors or a high device latency.
+    pub fn link_stats(&self) -> PyResult<LinkStats> {
+        Ok(self.get_client()?.link_stats().into())
+    }
+
+    /// Resets the statistics returned by `link_stats`.
+    pub fn reset_link_stats(&self) -> PyResult<()> {
+        self.get_client()?.reset_link_stats();
+        Ok(())
+    }
+
     /// Checks if the device is alive and responding.
     ///
     /// Runs a simple echo with random data and checks if the response matches.
@@ -172,21 +356,8 @@ impl MCUmgrClient {
         let firmware_bytes: &[u8] = firmware.extract()?;
         let checksum = checksum.map(|val| val.0);
 
-        let bootloader_type = match bootloader_type {
-            Some(bootloader_type) => Some(
-                BootloaderType::from_str(&bootloader_type)
-                    .map_err(|e| McubootPythonError::InvalidBootloaderString(e, bootloader_type))
-                    .map_err(err_to_pyerr)?,
-            ),
-            None => None,
-        };
-
-        let params = FirmwareUpdateParams {
-            bootloader_type,
-            skip_reboot,
-            force_confirm,
-            upgrade_only,
-        };
+        let params =
+            firmware_update_params(bootloader_type, skip_reboot, force_confirm, upgrade_only)?;
 
         let mut cb_error = None;
 
@@ -214,6 +385,62 @@ impl MCUmgrClient {
         res.map_err(err_to_pyerr)
     }
 
+    /// Measures the latency and the throughput of the connection.
+    ///
+    /// Measures the round-trip time of echo requests and the upload throughput
+    /// for several frame sizes, to find the optimal frame size for the device.
+    ///
+    /// ### Arguments
+    ///
+    /// * `file` - The scratch file to upload to and download from. It remains on the device.
+    /// * `image_slot` - Upload to the secondary image slot instead of a file. The slot gets erased.
+    /// * `frame_sizes` - The SMP frame sizes to measure. Powers of two up to the buffer size of the device if missing.
+    /// * `transfer_size` - The number of bytes to transfer per measurement.
+    /// * `echo_count` - The number of echo requests to measure the latency with.
+    /// * `progress` - A callback that receives progress updates.
+    ///
+    /// ### Return
+    ///
+    /// A dict with the keys `echo`, `upload`, `download_bytes_per_second` and `optimal_frame_size`.
+    ///
+    #[pyo3(signature = (file="/lfs/mcumgr_bench.bin", image_slot=false, frame_sizes=None, transfer_size=32768, echo_count=20, progress=None))]
+    pub fn bench<'py>(
+        &self,
+        py: Python<'py>,
+        file: &str,
+        image_slot: bool,
+        frame_sizes: Option<Vec<usize>>,
+        transfer_size: usize,
+        echo_count: usize,
+        #[gen_stub(override_type(type_repr="typing.Optional[collections.abc.Callable[[builtins.str, typing.Optional[builtins.tuple[builtins.int, builtins.int]]], None]]", imports=("builtins", "collections.abc", "typing")))]
+        progress: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let params = bench_params(file, image_slot, frame_sizes, transfer_size, echo_count);
+
+        let mut cb_error = None;
+
+        let res = if let Some(progress) = progress {
+            let mut cb = |step: BenchStep, prog| match progress.call((step.to_string(), prog), None)
+            {
+                Ok(_) => true,
+                Err(e) => {
+                    cb_error = Some(e);
+                    false
+                }
+            };
+            self.get_client()?.bench(&params, Some(&mut cb))
+        } else {
+            self.get_client()?.bench(&params, None)
+        };
+
+        if let Some(cb_error) = cb_error {
+            return Err(cb_error);
+        }
+
+        let result = res.map_err(err_to_pyerr)?;
+        serde_pyobject::to_pyobject(py, &result).map_err(Into::into)
+    }
+
     /// Sends a message to the device and expects the same message back as response.
     ///
     /// This can be used as a sanity check for whether the device is connected and responsive.
@@ -243,6 +470,23 @@ impl MCUmgrClient {
             .map_err(err_to_pyerr)
     }
 
+    /// Queries the statistics of the memory pools
+    ///
+    /// ### Return
+    ///
+    /// A map of pool names with their respective statistics
+    fn os_memory_pool_statistics(&self) -> PyResult<HashMap<String, MemoryPoolStatistics>> {
+        self.get_client()?
+            .os_memory_pool_statistics()
+            .map(|pools| {
+                pools
+                    .into_iter()
+                    .map(|(name, stats)| (name, stats.into()))
+                    .collect()
+            })
+            .map_err(err_to_pyerr)
+    }
+
     /// Sets the RTC of the device to the given datetime.
     ///
     /// Uses the contained local time and discards timezone information.
@@ -310,6 +554,18 @@ impl MCUmgrClient {
             .map_err(err_to_pyerr)
     }
 
+    /// Fetch information on the running image, parsed into its fields
+    ///
+    /// Every field is queried separately.
+    /// `build_time` is `None` if the device does not report it.
+    ///
+    pub fn os_application_info_structured(&self) -> PyResult<ApplicationInfo> {
+        self.get_client()?
+            .os_application_info_structured()
+            .map(Into::into)
+            .map_err(err_to_pyerr)
+    }
+
     /// Fetch information on the device's bootloader
     pub fn os_bootloader_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         self.get_client()?
@@ -608,15 +864,7 @@ impl MCUmgrClient {
             .shell_execute(&argv)
             .map_err(err_to_pyerr)?;
 
-        if exitcode < 0 {
-            return Err(PyRuntimeError::new_err(format!(
-                "Shell command returned error exit code: {}\n{}",
-                ::mcumgr_toolkit::Errno::errno_to_string(exitcode),
-                data
-            )));
-        }
-
-        Ok(data)
+        shell_output(exitcode, data)
     }
 
     /// Erase the `storage_partition` flash partition.
@@ -700,6 +948,10 @@ mod mcumgr_toolkit {
     #[pymodule_export]
     use super::MCUmgrClient;
     #[pymodule_export]
+    use super::async_client::AsyncMCUmgrClient;
+    #[pymodule_export]
+    use super::return_types::ApplicationInfo;
+    #[pymodule_export]
     use super::return_types::FileChecksum;
     #[pymodule_export]
     use super::return_types::FileChecksumDataFormat;
@@ -710,22 +962,36 @@ mod mcumgr_toolkit {
     #[pymodule_export]
     use super::return_types::ImageState;
     #[pymodule_export]
+    use super::return_types::LinkStats;
+    #[pymodule_export]
     use super::return_types::MCUmgrParameters;
     #[pymodule_export]
+    use super::return_types::MemoryPoolStatistics;
+    #[pymodule_export]
     use super::return_types::SlotInfoImage;
     #[pymodule_export]
     use super::return_types::SlotInfoImageSlot;
     #[pymodule_export]
     use super::return_types::TaskStatistics;
 
+    #[pymodule_export]
+    use super::errors::{
+        AlreadyInstalled, ChecksumMismatch, CommandNotSupported, DeviceError, MCUmgrError,
+        TransportError, TransportTimeout,
+    };
+
     #[pymodule_export]
     use super::mcuboot::McubootImageInfo;
     #[pymodule_export]
     use super::mcuboot::mcuboot_get_image_info;
 
     #[pymodule_init]
-    fn init(_m: &Bound<'_, PyModule>) -> PyResult<()> {
+    fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
         pyo3_log::init();
+        m.add(
+            "Transport",
+            super::py_transport::transport_protocol(m.py())?,
+        )?;
         Ok(())
     }
 }
@@ -32,7 +32,7 @@ pub fn mcuboot_get_image_info<'py>(
 ) -> PyResult<McubootImageInfo> {
     let data = image_data.as_bytes();
     let image_info = mcumgr_toolkit::mcuboot::get_image_info(std::io::Cursor::new(data))
-        .map_err(super::err_to_pyerr)?;
+        .map_err(crate::errors::err_to_pyerr)?;
 
     Ok(McubootImageInfo {
         version: image_info.version.to_string(),
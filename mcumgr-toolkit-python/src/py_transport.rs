@@ -0,0 +1,272 @@
+use std::borrow::Cow;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use ::mcumgr_toolkit::transport::{
+    ReceiveError, SendError, Transport, serial::ConfigurableTimeout,
+};
+use pyo3::exceptions::{PyTimeoutError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::PyBytes;
+
+/// Source of the `Transport` protocol class, see [`transport_protocol`].
+const TRANSPORT_PROTOCOL: &std::ffi::CStr = cr#"
+import typing
+
+@typing.runtime_checkable
+class Transport(typing.Protocol):
+    """
+    A custom transport, see `MCUmgrClient.custom_transport` and `MCUmgrClient.custom_serial`
+    """
+
+    def send(self, data: bytes) -> None:
+        """
+        Sends the given data to the device
+        """
+        ...
+
+    def recv(self, timeout: float) -> bytes:
+        """
+        Receives data from the device
+
+        Raises `TimeoutError` or returns no data if nothing arrived
+        within `timeout` seconds.
+        """
+        ...
+
+Transport.__module__ = "mcumgr_toolkit"
+"#;
+
+static TRANSPORT_PROTOCOL_CLASS: PyOnceLock<Py<PyAny>> = PyOnceLock::new();
+
+/// The `Transport` protocol class that custom transports implement.
+pub(crate) fn transport_protocol(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    TRANSPORT_PROTOCOL_CLASS
+        .get_or_try_init(py, || -> PyResult<_> {
+            let module = PyModule::from_code(
+                py,
+                TRANSPORT_PROTOCOL,
+                c"mcumgr_toolkit_transport.py",
+                c"mcumgr_toolkit_transport",
+            )?;
+            Ok(module.getattr("Transport")?.unbind())
+        })
+        .map(|protocol| protocol.bind(py).clone())
+}
+
+/// Stub generator counterpart of the `Transport` protocol class.
+///
+/// The class is defined in Python, so its stub has to be declared by hand;
+/// `stub_gen` adds the `@typing.runtime_checkable` decorator.
+pub(crate) struct TransportProtocol;
+
+pyo3_stub_gen::inventory::submit! {
+    pyo3_stub_gen::type_info::PyClassInfo {
+        pyclass_name: "Transport",
+        struct_id: std::any::TypeId::of::<TransportProtocol>,
+        getters: &[],
+        setters: &[],
+        module: Some("mcumgr_toolkit"),
+        doc: "A custom transport, see `MCUmgrClient.custom_transport` and `MCUmgrClient.custom_serial`",
+        bases: &[|| pyo3_stub_gen::TypeInfo::with_module("typing.Protocol", "typing".into())],
+        has_eq: false,
+        has_ord: false,
+        has_hash: false,
+        has_str: false,
+        subclass: true,
+    }
+}
+
+pyo3_stub_gen::inventory::submit! {
+    pyo3_stub_gen::derive::gen_methods_from_python! {
+        r#"
+        import builtins
+
+        class TransportProtocol:
+            def send(self, data: bytes) -> None:
+                """
+                Sends the given data to the device
+                """
+
+            def recv(self, timeout: builtins.float) -> bytes:
+                """
+                Receives data from the device
+
+                Raises `TimeoutError` or returns no data if nothing arrived
+                within `timeout` seconds.
+                """
+        "#
+    }
+}
+
+/// Raises a `TypeError` if the object does not implement the `Transport` protocol.
+fn check_transport(transport: &Bound<'_, PyAny>) -> PyResult<()> {
+    if transport.is_instance(&transport_protocol(transport.py())?)? {
+        Ok(())
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "'{}' does not implement the Transport protocol; it needs `send` and `recv` methods",
+            transport.get_type().name()?
+        )))
+    }
+}
+
+/// Converts a Python exception into an I/O error, keeping it as the source.
+///
+/// `TimeoutError` becomes an error of kind [`io::ErrorKind::TimedOut`].
+fn pyerr_to_io(py: Python<'_>, err: PyErr) -> io::Error {
+    let kind = if err.is_instance_of::<PyTimeoutError>(py) {
+        io::ErrorKind::TimedOut
+    } else {
+        io::ErrorKind::Other
+    };
+    io::Error::new(kind, err)
+}
+
+/// A Python object with `send(bytes)` and `recv(timeout) -> bytes` methods.
+struct PyLink {
+    link: Py<PyAny>,
+    timeout: Duration,
+}
+
+impl PyLink {
+    fn send(&self, data: &[u8]) -> io::Result<()> {
+        Python::attach(|py| {
+            self.link
+                .call_method1(py, "send", (PyBytes::new(py, data),))
+                .map(drop)
+                .map_err(|e| pyerr_to_io(py, e))
+        })
+    }
+
+    /// Receives data, or fails with [`io::ErrorKind::TimedOut`] if there is none.
+    fn recv(&self) -> io::Result<Vec<u8>> {
+        let data = Python::attach(|py| {
+            self.link
+                .call_method1(py, "recv", (self.timeout.as_secs_f64(),))
+                .and_then(|data| {
+                    data.extract::<Option<Cow<'_, [u8]>>>(py)
+                        .map(|data| data.map(Cow::into_owned))
+                        .map_err(PyErr::from)
+                })
+                .map_err(|e| pyerr_to_io(py, e))
+        })?;
+
+        data.filter(|data| !data.is_empty())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::TimedOut))
+    }
+}
+
+/// A transport that exchanges raw SMP frames with a Python object.
+///
+/// Every call of `send` and `recv` transfers exactly one frame.
+pub(crate) struct PyFrameTransport(PyLink);
+
+impl PyFrameTransport {
+    pub(crate) fn new(link: &Bound<'_, PyAny>, timeout: Duration) -> PyResult<Self> {
+        check_transport(link)?;
+        Ok(Self(PyLink {
+            link: link.clone().unbind(),
+            timeout,
+        }))
+    }
+}
+
+impl Transport for PyFrameTransport {
+    fn send_raw_frame(&mut self, header: [u8; 8], data: &[u8]) -> Result<(), SendError> {
+        let frame = [&header[..], data].concat();
+        if frame.len() > u16::MAX as usize {
+            return Err(SendError::DataTooBig);
+        }
+
+        self.0.send(&frame)?;
+        Ok(())
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; u16::MAX as usize],
+    ) -> Result<&'a [u8], ReceiveError> {
+        let frame = self.0.recv()?;
+
+        let buffer = buffer
+            .get_mut(..frame.len())
+            .ok_or(ReceiveError::FrameTooBig)?;
+        buffer.copy_from_slice(&frame);
+        Ok(buffer)
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.0.timeout = timeout;
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.0.timeout)
+    }
+}
+
+/// A byte stream to a Python object, for use with a
+/// [`SerialTransport`](::mcumgr_toolkit::transport::serial::SerialTransport).
+///
+/// `recv` may return any number of bytes; the ones that do not fit
+/// into the read buffer are kept for the next read.
+pub(crate) struct PySerialStream {
+    link: PyLink,
+    pending: Vec<u8>,
+}
+
+impl PySerialStream {
+    pub(crate) fn new(link: &Bound<'_, PyAny>, timeout: Duration) -> PyResult<Self> {
+        check_transport(link)?;
+        Ok(Self {
+            link: PyLink {
+                link: link.clone().unbind(),
+                timeout,
+            },
+            pending: Vec::new(),
+        })
+    }
+}
+
+impl Read for PySerialStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            self.pending = self.link.recv()?;
+        }
+
+        let len = buf.len().min(self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+        Ok(len)
+    }
+}
+
+impl Write for PySerialStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.link.send(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ConfigurableTimeout for PySerialStream {
+    fn set_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.link.timeout = duration;
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.link.timeout)
+    }
+}
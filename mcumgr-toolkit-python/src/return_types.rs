@@ -3,6 +3,7 @@ use pyo3_stub_gen::derive::{gen_stub_pyclass, gen_stub_pyclass_enum};
 
 use ::mcumgr_toolkit::commands;
 use serde::{Serialize, ser::SerializeSeq};
+use std::time::Duration;
 
 use crate::repr_macro::generate_repr_from_serialize;
 
@@ -44,6 +45,62 @@ impl From<commands::os::MCUmgrParametersResponse> for MCUmgrParameters {
     }
 }
 
+/// Return value of `MCUmgrClient.os_application_info_structured`.
+#[gen_stub_pyclass]
+#[pyclass(frozen)]
+#[derive(Serialize)]
+pub struct ApplicationInfo {
+    /// Kernel name, like `Zephyr`
+    #[pyo3(get)]
+    pub kernel_name: String,
+    /// Node name, usually the hostname
+    #[pyo3(get)]
+    pub node_name: String,
+    /// Kernel release
+    #[pyo3(get)]
+    pub kernel_release: String,
+    /// Kernel version
+    #[pyo3(get)]
+    pub kernel_version: String,
+    /// Build date and time of the application, if available
+    #[pyo3(get)]
+    pub build_time: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// Build date and time of the application, as reported by the device
+    ///
+    /// Also available if the value could not be parsed into `build_time`.
+    #[pyo3(get)]
+    pub build_time_raw: Option<String>,
+    /// Machine, like `arm`
+    #[pyo3(get)]
+    pub machine: String,
+    /// Processor, like `cortex-m33`
+    #[pyo3(get)]
+    pub processor: String,
+    /// Hardware platform, like the board name
+    #[pyo3(get)]
+    pub hardware_platform: String,
+    /// Operating system, like `Zephyr`
+    #[pyo3(get)]
+    pub operating_system: String,
+}
+generate_repr_from_serialize!(ApplicationInfo);
+impl From<::mcumgr_toolkit::client::ApplicationInfo> for ApplicationInfo {
+    fn from(value: ::mcumgr_toolkit::client::ApplicationInfo) -> Self {
+        Self {
+            kernel_name: value.kernel_name,
+            node_name: value.node_name,
+            kernel_release: value.kernel_release,
+            kernel_version: value.kernel_version,
+            build_time: value.build_time,
+            build_time_raw: value.build_time_raw,
+            machine: value.machine,
+            processor: value.processor,
+            hardware_platform: value.hardware_platform,
+            operating_system: value.operating_system,
+        }
+    }
+}
+
 /// Return value of `MCUmgrClient.fs_file_checksum`.
 #[gen_stub_pyclass]
 #[pyclass(frozen)]
@@ -169,6 +226,37 @@ impl From<commands::os::TaskStatisticsEntry> for TaskStatistics {
     }
 }
 
+/// Statistics of an MCU memory pool
+#[gen_stub_pyclass]
+#[pyclass(frozen)]
+#[derive(Serialize)]
+pub struct MemoryPoolStatistics {
+    /// size of the memory blocks in the pool
+    #[pyo3(get)]
+    pub blksiz: u64,
+    /// number of blocks in the pool
+    #[pyo3(get)]
+    pub nblks: u64,
+    /// number of free blocks
+    #[pyo3(get)]
+    pub nfree: u64,
+    /// lowest number of free blocks the pool reached
+    #[pyo3(get)]
+    pub min: u64,
+}
+generate_repr_from_serialize!(MemoryPoolStatistics);
+
+impl From<commands::os::MemoryPoolStatisticsEntry> for MemoryPoolStatistics {
+    fn from(value: commands::os::MemoryPoolStatisticsEntry) -> Self {
+        Self {
+            blksiz: value.blksiz,
+            nblks: value.nblks,
+            nfree: value.nfree,
+            min: value.min,
+        }
+    }
+}
+
 /// The state of an image slot
 #[gen_stub_pyclass]
 #[pyclass(frozen)]
@@ -300,3 +388,88 @@ impl SlotInfoImage {
         })
     }
 }
+
+/// Return value of `MCUmgrClient.link_stats`.
+///
+/// Byte counts refer to complete SMP frames, including their header.
+#[gen_stub_pyclass]
+#[pyclass(frozen)]
+#[derive(Serialize)]
+pub struct LinkStats {
+    /// number of sent SMP frames
+    #[pyo3(get)]
+    pub frames_sent: u64,
+    /// number of received SMP frames, including ignored ones
+    #[pyo3(get)]
+    pub frames_received: u64,
+    /// number of bytes of the sent SMP frames
+    #[pyo3(get)]
+    pub bytes_sent: u64,
+    /// number of bytes of the received SMP frames
+    #[pyo3(get)]
+    pub bytes_received: u64,
+    /// number of received frames that were ignored because of a wrong sequence number
+    #[pyo3(get)]
+    pub ignored_frames: u64,
+    /// number of requests that did not receive a response in time
+    #[pyo3(get)]
+    pub timeouts: u64,
+    /// number of bytes written to the link, including the encoding overhead;
+    /// `None` if the transport does not encode frames
+    #[pyo3(get)]
+    pub encoded_bytes_sent: Option<u64>,
+    /// number of bytes read from the link, including the encoding overhead;
+    /// `None` if the transport does not encode frames
+    #[pyo3(get)]
+    pub encoded_bytes_received: Option<u64>,
+    /// number of received frames with a wrong checksum;
+    /// `None` if the transport does not encode frames
+    #[pyo3(get)]
+    pub crc_errors: Option<u64>,
+    /// number of requests that received a response
+    #[pyo3(get)]
+    pub round_trip_count: usize,
+    /// fastest round trip in milliseconds
+    #[pyo3(get)]
+    pub round_trip_min_ms: Option<f64>,
+    /// median round trip in milliseconds
+    #[pyo3(get)]
+    pub round_trip_p50_ms: Option<f64>,
+    /// 90th percentile of the round trips in milliseconds
+    #[pyo3(get)]
+    pub round_trip_p90_ms: Option<f64>,
+    /// 99th percentile of the round trips in milliseconds
+    #[pyo3(get)]
+    pub round_trip_p99_ms: Option<f64>,
+    /// slowest round trip in milliseconds
+    #[pyo3(get)]
+    pub round_trip_max_ms: Option<f64>,
+}
+generate_repr_from_serialize!(LinkStats);
+
+impl From<::mcumgr_toolkit::connection::LinkStats> for LinkStats {
+    fn from(value: ::mcumgr_toolkit::connection::LinkStats) -> Self {
+        let round_trip = value.round_trip;
+        let round_trip_ms = |f: fn(&::mcumgr_toolkit::connection::RoundTripStats) -> Duration| {
+            round_trip.as_ref().map(|r| f(r).as_secs_f64() * 1000.0)
+        };
+
+        Self {
+            frames_sent: value.frames_sent,
+            frames_received: value.frames_received,
+            bytes_sent: value.bytes_sent,
+            bytes_received: value.bytes_received,
+            ignored_frames: value.ignored_frames,
+            timeouts: value.timeouts,
+            encoded_bytes_sent: value.transport.map(|t| t.encoded_bytes_sent),
+            encoded_bytes_received: value.transport.map(|t| t.encoded_bytes_received),
+            crc_errors: value.transport.map(|t| t.crc_errors),
+            round_trip_count: round_trip.map_or(0, |r| r.count),
+            round_trip_min_ms: round_trip_ms(|r| r.min),
+            round_trip_p50_ms: round_trip_ms(|r| r.p50),
+            round_trip_p90_ms: round_trip_ms(|r| r.p90),
+            round_trip_p99_ms: round_trip_ms(|r| r.p99),
+            round_trip_max_ms: round_trip_ms(|r| r.max),
+        }
+    }
+}
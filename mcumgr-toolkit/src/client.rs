@@ -5,6 +5,35 @@ pub use firmware_update::{
     FirmwareUpdateError, FirmwareUpdateParams, FirmwareUpdateProgressCallback, FirmwareUpdateStep,
 };
 
+/// Parsing of the OS/application info
+mod application_info;
+
+pub use application_info::ApplicationInfo;
+
+/// Collection of all device information
+mod device_snapshot;
+
+pub use device_snapshot::{DeviceSnapshot, DeviceSnapshotError};
+
+/// Detection of the serial port configuration of a device
+mod serial_probe;
+
+pub use serial_probe::{COMMON_BAUD_RATES, SerialProbeError, SerialProbeResult};
+
+/// Latency and throughput benchmarks
+pub mod bench;
+
+/// Parallel operations on many devices
+pub mod fleet;
+
+/// Clock synchronisation and drift measurement
+pub mod time_sync;
+
+use bench::{BenchError, BenchParams, BenchProgressCallback, BenchResult};
+use time_sync::{
+    DriftError, DriftMeasurement, DriftProgressCallback, HostClock, SystemClock, TimeSyncResult,
+};
+
 use std::{
     collections::HashMap,
     io::{self, Read, Write},
@@ -23,8 +52,14 @@ use crate::{
     commands::{
         self, fs::file_upload_max_data_chunk_size, image::image_upload_max_data_chunk_size,
     },
-    connection::{Connection, ExecuteError},
-    transport::serial::{ConfigurableTimeout, SerialTransport},
+    connection::{Connection, ExecuteError, FrameObserver, LinkStats},
+    smp_errors::DeviceError,
+    transport::{
+        SmpVersion, Transport,
+        serial::{
+            ConfigurableBaudRate, ConfigurableTimeout, SerialTransport, SerialTransportBuilder,
+        },
+    },
 };
 
 /// The default SMP frame size of Zephyr.
@@ -45,7 +80,7 @@ pub struct MCUmgrClient {
 pub enum MCUmgrClientError {
     /// The command failed in the SMP protocol layer.
     #[error("Command execution failed")]
-    #[diagnostic(code(mcumgr_toolkit::client::execute))]
+    #[diagnostic(code(mcumgr_toolkit::client::execute), forward(0))]
     ExecuteError(#[from] ExecuteError),
     /// A device response contained an unexpected offset value.
     #[error("Received an unexpected offset value")]
@@ -90,6 +125,17 @@ pub enum MCUmgrClientError {
 }
 
 impl MCUmgrClientError {
+    /// The error the device responded with, if any
+    ///
+    /// Use [`DeviceError::kind`] to decode group specific errors.
+    pub fn device_error(&self) -> Option<&DeviceError> {
+        if let Self::ExecuteError(err) = self {
+            err.device_error()
+        } else {
+            None
+        }
+    }
+
     /// Checks if the device reported the command as unsupported
     pub fn command_not_supported(&self) -> bool {
         if let Self::ExecuteError(err) = self {
@@ -174,7 +220,7 @@ impl std::fmt::Debug for UsbSerialPorts {
     }
 }
 
-/// Possible error values of [`MCUmgrClient::new_from_usb_serial`].
+/// Possible error values of [`MCUmgrClient::new_from_usb_serial`], [`find_usb_serial_port`] and [`find_usb_serial_ports`].
 #[derive(Error, Debug, Diagnostic)]
 pub enum UsbSerialError {
     /// Serialport error
@@ -213,6 +259,125 @@ pub enum UsbSerialError {
     RegexError(#[from] regex::Error),
 }
 
+/// Finds the USB serial port identified by VID:PID.
+///
+/// See [`MCUmgrClient::new_from_usb_serial`] for the format of the identifier.
+pub fn find_usb_serial_port(
+    identifier: impl AsRef<str>,
+) -> Result<UsbSerialPortInfo, UsbSerialError> {
+    let identifier = identifier.as_ref();
+
+    let mut matches = find_usb_serial_ports(identifier)?;
+
+    if matches.len() > 1 {
+        return Err(UsbSerialError::MultipleMatchingPorts {
+            identifier: identifier.to_string(),
+            ports: UsbSerialPorts(matches),
+        });
+    }
+
+    Ok(matches.remove(0))
+}
+
+/// Detects the baud rate and SMP frame size of the device on a serial port.
+///
+/// Like [`MCUmgrClient::probe_serial`], but returns the serial port instead of a client,
+/// for example to build a [`SerialTransport`] with custom settings on top of it.
+///
+/// # Arguments
+///
+/// * `serial` - The serial port to probe.
+/// * `candidate_bauds` - The baud rates to try, for example [`COMMON_BAUD_RATES`].
+/// * `transport` - The settings of the serial transport that is used for probing.
+///
+/// # Return
+///
+/// The serial port, configured with the detected baud rate,
+/// together with the detected settings.
+///
+pub fn probe_serial_port<T>(
+    serial: T,
+    candidate_bauds: &[u32],
+    transport: &SerialTransportBuilder,
+) -> Result<(T, SerialProbeResult), SerialProbeError>
+where
+    T: Send + Read + Write + ConfigurableTimeout + ConfigurableBaudRate + 'static,
+{
+    serial_probe::probe_serial_port(serial, candidate_bauds, transport)
+}
+
+/// Finds all USB serial ports identified by VID:PID.
+///
+/// Unlike [`find_usb_serial_port`], the identifier may match multiple ports,
+/// for example to [update a fleet of devices](fleet).
+/// Fails if no port matches.
+///
+/// See [`MCUmgrClient::new_from_usb_serial`] for the format of the identifier.
+pub fn find_usb_serial_ports(
+    identifier: impl AsRef<str>,
+) -> Result<Vec<UsbSerialPortInfo>, UsbSerialError> {
+    let identifier = identifier.as_ref();
+
+    let ports = serialport::available_ports()?
+        .into_iter()
+        .filter_map(|port| {
+            if let serialport::SerialPortType::UsbPort(port_info) = port.port_type {
+                if let Some(interface) = port_info.interface {
+                    Some(UsbSerialPortInfo {
+                        identifier: format!(
+                            "{:04x}:{:04x}:{}",
+                            port_info.vid, port_info.pid, interface
+                        ),
+                        port_name: port.port_name,
+                        port_info,
+                    })
+                } else {
+                    Some(UsbSerialPortInfo {
+                        identifier: format!("{:04x}:{:04x}", port_info.vid, port_info.pid),
+                        port_name: port.port_name,
+                        port_info,
+                    })
+                }
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if identifier.is_empty() {
+        return Err(UsbSerialError::IdentifierEmpty {
+            ports: UsbSerialPorts(ports),
+        });
+    }
+
+    let port_regex = regex::RegexBuilder::new(identifier)
+        .case_insensitive(true)
+        .unicode(true)
+        .build()?;
+
+    let matches = ports
+        .iter()
+        .filter(|port| {
+            if let Some(m) = port_regex.find(&port.identifier) {
+                // Only accept if the regex matches at the beginning of the string
+                m.start() == 0
+            } else {
+                false
+            }
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if matches.is_empty() {
+        return Err(UsbSerialError::NoMatchingPort {
+            identifier: identifier.to_string(),
+            available: UsbSerialPorts(ports),
+        });
+    }
+
+    Ok(matches)
+}
+
 impl MCUmgrClient {
     /// Creates a Zephyr MCUmgr SMP client based on a configured and opened serial port.
     ///
@@ -230,10 +395,7 @@ impl MCUmgrClient {
     pub fn new_from_serial<T: Send + Read + Write + ConfigurableTimeout + 'static>(
         serial: T,
     ) -> Self {
-        Self {
-            connection: Connection::new(SerialTransport::new(serial)),
-            smp_frame_size: ZEPHYR_DEFAULT_SMP_FRAME_SIZE.into(),
-        }
+        Self::new_from_transport(SerialTransport::new(serial))
     }
 
     /// Creates a Zephyr MCUmgr SMP client based on a USB serial port identified by VID:PID.
@@ -258,82 +420,139 @@ impl MCUmgrClient {
         baud_rate: u32,
         timeout: Duration,
     ) -> Result<Self, UsbSerialError> {
-        let identifier = identifier.as_ref();
-
-        let ports = serialport::available_ports()?
-            .into_iter()
-            .filter_map(|port| {
-                if let serialport::SerialPortType::UsbPort(port_info) = port.port_type {
-                    if let Some(interface) = port_info.interface {
-                        Some(UsbSerialPortInfo {
-                            identifier: format!(
-                                "{:04x}:{:04x}:{}",
-                                port_info.vid, port_info.pid, interface
-                            ),
-                            port_name: port.port_name,
-                            port_info,
-                        })
-                    } else {
-                        Some(UsbSerialPortInfo {
-                            identifier: format!("{:04x}:{:04x}", port_info.vid, port_info.pid),
-                            port_name: port.port_name,
-                            port_info,
-                        })
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-
-        if identifier.is_empty() {
-            return Err(UsbSerialError::IdentifierEmpty {
-                ports: UsbSerialPorts(ports),
-            });
-        }
-
-        let port_regex = regex::RegexBuilder::new(identifier)
-            .case_insensitive(true)
-            .unicode(true)
-            .build()?;
-
-        let matches = ports
-            .iter()
-            .filter(|port| {
-                if let Some(m) = port_regex.find(&port.identifier) {
-                    // Only accept if the regex matches at the beginning of the string
-                    m.start() == 0
-                } else {
-                    false
-                }
-            })
-            .cloned()
-            .collect::<Vec<_>>();
-
-        if matches.len() > 1 {
-            return Err(UsbSerialError::MultipleMatchingPorts {
-                identifier: identifier.to_string(),
-                ports: UsbSerialPorts(matches),
-            });
-        }
+        let port = find_usb_serial_port(identifier)?;
 
-        let port_name = match matches.into_iter().next() {
-            Some(port) => port.port_name,
-            None => {
-                return Err(UsbSerialError::NoMatchingPort {
-                    identifier: identifier.to_string(),
-                    available: UsbSerialPorts(ports),
-                });
-            }
-        };
-
-        let serial = serialport::new(port_name, baud_rate)
+        let serial = serialport::new(port.port_name, baud_rate)
             .timeout(timeout)
             .open()?;
 
         Ok(Self::new_from_serial(serial))
     }
 
+    /// Creates a Zephyr MCUmgr SMP client based on a serial port behind a TCP serial bridge.
+    ///
+    /// The data is forwarded unmodified, like `ser2net` does in `raw` mode.
+    /// For RFC 2217 connections, use [`TcpSerial::connect_rfc2217`](crate::transport::tcp::TcpSerial::connect_rfc2217)
+    /// together with [`MCUmgrClient::new_from_serial`].
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the serial bridge.
+    /// * `timeout` - The communication timeout.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// let client = MCUmgrClient::new_from_tcp_serial(
+    ///     "192.168.1.42:2000",
+    ///     std::time::Duration::from_millis(10000),
+    /// )
+    /// .unwrap();
+    /// # }
+    /// ```
+    pub fn new_from_tcp_serial(
+        addr: impl std::net::ToSocketAddrs,
+        timeout: Duration,
+    ) -> std::io::Result<Self> {
+        Ok(Self::new_from_serial(
+            crate::transport::tcp::TcpSerial::connect(addr, timeout)?,
+        ))
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on a pseudo-terminal.
+    ///
+    /// Zephyr's `native_sim` builds expose their UART as a pseudo-terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the terminal, for example `/dev/pts/5`.
+    /// * `timeout` - The communication timeout.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// let client =
+    ///     MCUmgrClient::new_from_pty("/dev/pts/5", std::time::Duration::from_millis(10000))
+    ///         .unwrap();
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn new_from_pty(
+        path: impl AsRef<std::path::Path>,
+        timeout: Duration,
+    ) -> std::io::Result<Self> {
+        Ok(Self::new_from_serial(
+            crate::transport::pty::PtySerial::open(path, timeout)?,
+        ))
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client on a serial port with unknown baud rate.
+    ///
+    /// Tries each of the candidate baud rates in order until the device
+    /// responds to an echo request, and then queries its SMP frame size.
+    ///
+    /// # Arguments
+    ///
+    /// * `serial` - The serial port to probe.
+    /// * `candidate_bauds` - The baud rates to try, for example [`COMMON_BAUD_RATES`].
+    /// * `timeout` - The communication timeout of the returned client.
+    ///
+    /// # Return
+    ///
+    /// A client configured with the detected baud rate and frame size,
+    /// together with the detected settings.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::{MCUmgrClient, client::COMMON_BAUD_RATES};
+    /// # fn main() {
+    /// let serial = serialport::new("COM42", 115200).open().unwrap();
+    ///
+    /// let (client, result) = MCUmgrClient::probe_serial(
+    ///     serial,
+    ///     COMMON_BAUD_RATES,
+    ///     std::time::Duration::from_millis(10000),
+    /// )
+    /// .unwrap();
+    /// println!("Device responds at {} baud", result.baud_rate);
+    /// # }
+    /// ```
+    pub fn probe_serial<T>(
+        serial: T,
+        candidate_bauds: &[u32],
+        timeout: Duration,
+    ) -> Result<(Self, SerialProbeResult), SerialProbeError>
+    where
+        T: Send + Read + Write + ConfigurableTimeout + ConfigurableBaudRate + 'static,
+    {
+        serial_probe::probe_serial(serial, candidate_bauds, timeout)
+    }
+
+    /// Creates a Zephyr MCUmgr SMP client based on an arbitrary transport.
+    ///
+    /// Useful for transports that require further configuration,
+    /// like a [`SerialTransport`] with a console sink.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::{MCUmgrClient, transport::serial::SerialTransport};
+    /// # fn main() {
+    /// let serial = serialport::new("COM42", 115200)
+    ///     .timeout(std::time::Duration::from_millis(10000))
+    ///     .open()
+    ///     .unwrap();
+    ///
+    /// let mut transport = SerialTransport::new(serial);
+    /// transport.set_console_sink(|line| println!("Console: {line}"));
+    ///
+    /// let mut client = MCUmgrClient::new_from_transport(transport);
+    /// # }
+    /// ```
+    pub fn new_from_transport<T: Transport + Send + 'static>(transport: T) -> Self {
+        Self {
+            connection: Connection::new(transport),
+            smp_frame_size: ZEPHYR_DEFAULT_SMP_FRAME_SIZE.into(),
+        }
+    }
+
     /// Configures the maximum SMP frame size that we can send to the device.
     ///
     /// Must not exceed [`MCUMGR_TRANSPORT_NETBUF_SIZE`](https://github.com/zephyrproject-rtos/zephyr/blob/v4.2.1/subsys/mgmt/mcumgr/transport/Kconfig#L40),
@@ -343,6 +562,12 @@ impl MCUmgrClient {
             .store(smp_frame_size, std::sync::atomic::Ordering::SeqCst);
     }
 
+    /// Returns the maximum SMP frame size that is currently used.
+    pub fn frame_size(&self) -> usize {
+        self.smp_frame_size
+            .load(std::sync::atomic::Ordering::SeqCst)
+    }
+
     /// Configures the maximum SMP frame size that we can send to the device automatically
     /// by reading the value of [`MCUMGR_TRANSPORT_NETBUF_SIZE`](https://github.com/zephyrproject-rtos/zephyr/blob/v4.2.1/subsys/mgmt/mcumgr/transport/Kconfig#L40)
     /// from the device.
@@ -371,6 +596,77 @@ impl MCUmgrClient {
             .map_err(MCUmgrClientError::SetTimeoutFailed)
     }
 
+    /// Returns the SMP protocol version used to communicate with the device.
+    ///
+    /// `None` if no command was executed yet, as the version gets negotiated
+    /// during the first command.
+    pub fn smp_version(&self) -> Option<SmpVersion> {
+        self.connection.smp_version()
+    }
+
+    /// Forces the SMP protocol version used to communicate with the device.
+    ///
+    /// By default, SMP v2 is used if the device supports it, otherwise v1.
+    /// `None` restores the automatic negotiation.
+    pub fn set_smp_version(&self, version: Option<SmpVersion>) {
+        self.connection.set_smp_version(version);
+    }
+
+    /// Adds an observer that receives every SMP frame exchanged with the device.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// # let serial = serialport::new("COM42", 115200).open().unwrap();
+    /// let client = MCUmgrClient::new_from_serial(serial);
+    /// client.add_frame_observer(|event: &mcumgr_toolkit::connection::FrameEvent| {
+    ///     println!("{:?}: {}", event.direction, event.cbor_diagnostic());
+    /// });
+    /// # }
+    /// ```
+    pub fn add_frame_observer(&self, observer: impl FrameObserver + 'static) {
+        self.connection.add_frame_observer(observer);
+    }
+
+    /// Returns statistics about the link to the device.
+    ///
+    /// Helps to find the cause of slow transfers, like retransmissions
+    /// after timeouts, transmission errors or a high device latency.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// # let serial = serialport::new("COM42", 115200).open().unwrap();
+    /// let client = MCUmgrClient::new_from_serial(serial);
+    /// client.os_echo("Hello").unwrap();
+    ///
+    /// let stats = client.link_stats();
+    /// println!("{} frames sent, {} timeouts", stats.frames_sent, stats.timeouts);
+    /// if let Some(round_trip) = stats.round_trip {
+    ///     println!("Median round trip: {:?}", round_trip.p50);
+    /// }
+    /// # }
+    /// ```
+    pub fn link_stats(&self) -> LinkStats {
+        self.connection.link_stats()
+    }
+
+    /// Resets the statistics returned by [`MCUmgrClient::link_stats`].
+    pub fn reset_link_stats(&self) {
+        self.connection.reset_link_stats();
+    }
+
+    /// Receives console output of the device for the given duration.
+    ///
+    /// Only has an effect on transports that forward console output,
+    /// like a [`SerialTransport`] with a console sink.
+    /// Useful to capture the boot log after a device reboot.
+    pub fn receive_console(&self, duration: Duration) -> Result<(), MCUmgrClientError> {
+        self.connection
+            .receive_console(duration)
+            .map_err(Into::into)
+    }
+
     /// Checks if the device is alive and responding.
     ///
     /// Runs a simple echo with random data and checks if the response matches.
@@ -410,6 +706,49 @@ impl MCUmgrClient {
         firmware_update::firmware_update(self, firmware, checksum, params, progress)
     }
 
+    /// Measures the latency and the throughput of the connection.
+    ///
+    /// Measures the round-trip time of echo requests and the upload throughput
+    /// for several frame sizes, to find the optimal frame size for the device.
+    /// The frame size gets restored afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Configurable parameters.
+    /// * `progress` - A callback that receives progress updates.
+    ///
+    pub fn bench(
+        &self,
+        params: &BenchParams,
+        progress: Option<&mut BenchProgressCallback>,
+    ) -> Result<BenchResult, BenchError> {
+        let frame_size = self.frame_size();
+        let result = bench::bench(self, params, progress);
+        self.set_frame_size(frame_size);
+        result
+    }
+
+    /// Collects everything that can be queried about the device.
+    ///
+    /// Useful for support tickets or to compare devices.
+    /// Queries that the device does not support or answers with an error are left empty.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// # let serial = serialport::new("COM42", 115200).open().unwrap();
+    /// let client = MCUmgrClient::new_from_serial(serial);
+    ///
+    /// let snapshot = client.device_snapshot().unwrap();
+    /// if let Some(bootloader) = snapshot.bootloader {
+    ///     println!("Bootloader: {bootloader:?}");
+    /// }
+    /// # }
+    /// ```
+    pub fn device_snapshot(&self) -> Result<DeviceSnapshot, DeviceSnapshotError> {
+        device_snapshot::device_snapshot(self)
+    }
+
     /// Sends a message to the device and expects the same message back as response.
     ///
     /// This can be used as a sanity check for whether the device is connected and responsive.
@@ -446,6 +785,20 @@ impl MCUmgrClient {
             .map_err(Into::into)
     }
 
+    /// Queries the statistics of the memory pools
+    ///
+    /// # Return
+    ///
+    /// A map of pool names with their respective statistics
+    pub fn os_memory_pool_statistics(
+        &self,
+    ) -> Result<HashMap<String, commands::os::MemoryPoolStatisticsEntry>, MCUmgrClientError> {
+        self.connection
+            .execute_command(&commands::os::MemoryPoolStatistics)
+            .map(|resp| resp.pools)
+            .map_err(Into::into)
+    }
+
     /// Sets the RTC of the device to the given datetime.
     pub fn os_set_datetime(
         &self,
@@ -465,6 +818,79 @@ impl MCUmgrClient {
             .map_err(Into::into)
     }
 
+    /// Sets the RTC of the device to the host time, compensating for the transport latency.
+    ///
+    /// The round-trip delay gets estimated with several datetime requests,
+    /// and half of it gets added to the time that is sent.
+    /// Afterwards, the remaining offset of the device clock is measured.
+    ///
+    /// Note that many RTCs only have a resolution of one second,
+    /// which limits the achievable accuracy.
+    ///
+    /// # Arguments
+    ///
+    /// * `utc` - Use the host's UTC time instead of its local time.
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// # let serial = serialport::new("COM42", 115200).open().unwrap();
+    /// let client = MCUmgrClient::new_from_serial(serial);
+    ///
+    /// let result = client.os_sync_time(false).unwrap();
+    /// println!("Remaining offset: {:.1} ms", result.residual_offset_ms);
+    /// # }
+    /// ```
+    pub fn os_sync_time(&self, utc: bool) -> Result<TimeSyncResult, MCUmgrClientError> {
+        let clock = if utc {
+            SystemClock::Utc
+        } else {
+            SystemClock::Local
+        };
+        self.os_sync_time_with_clock(&clock)
+    }
+
+    /// Like [`os_sync_time`](Self::os_sync_time), but with a custom host clock.
+    pub fn os_sync_time_with_clock(
+        &self,
+        clock: &dyn HostClock,
+    ) -> Result<TimeSyncResult, MCUmgrClientError> {
+        time_sync::sync_time(self, clock)
+    }
+
+    /// Measures the drift of the device RTC against the host clock.
+    ///
+    /// Samples the device datetime in regular intervals, and fits a line
+    /// through the offsets to the host clock.
+    /// The longer the duration, the more accurate the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - How long to measure.
+    /// * `interval` - The time between two samples.
+    /// * `progress` - A callback that receives progress updates.
+    ///
+    pub fn os_measure_drift(
+        &self,
+        duration: Duration,
+        interval: Duration,
+        progress: Option<&mut DriftProgressCallback>,
+    ) -> Result<DriftMeasurement, DriftError> {
+        // Only differences matter, so the host timezone is irrelevant
+        self.os_measure_drift_with_clock(&SystemClock::Utc, duration, interval, progress)
+    }
+
+    /// Like [`os_measure_drift`](Self::os_measure_drift), but with a custom host clock.
+    pub fn os_measure_drift_with_clock(
+        &self,
+        clock: &dyn HostClock,
+        duration: Duration,
+        interval: Duration,
+        progress: Option<&mut DriftProgressCallback>,
+    ) -> Result<DriftMeasurement, DriftError> {
+        time_sync::measure_drift(self, clock, duration, interval, progress)
+    }
+
     /// Issues a system reset.
     ///
     /// # Arguments
@@ -516,6 +942,24 @@ impl MCUmgrClient {
             .map_err(Into::into)
     }
 
+    /// Fetch information on the running image, parsed into its fields
+    ///
+    /// Every field is queried separately, see [`MCUmgrClient::os_application_info`].
+    ///
+    /// ```no_run
+    /// # use mcumgr_toolkit::MCUmgrClient;
+    /// # fn main() {
+    /// # let serial = serialport::new("COM42", 115200).open().unwrap();
+    /// let client = MCUmgrClient::new_from_serial(serial);
+    ///
+    /// let info = client.os_application_info_structured().unwrap();
+    /// println!("{} {} on {}", info.kernel_name, info.kernel_release, info.hardware_platform);
+    /// # }
+    /// ```
+    pub fn os_application_info_structured(&self) -> Result<ApplicationInfo, MCUmgrClientError> {
+        application_info::application_info(self)
+    }
+
     /// Fetch information on the device's bootloader
     pub fn os_bootloader_info(&self) -> Result<BootloaderInfo, MCUmgrClientError> {
         Ok(
@@ -926,4 +1370,18 @@ impl MCUmgrClient {
     ) -> Result<T::Response, MCUmgrClientError> {
         self.connection.execute_command(command).map_err(Into::into)
     }
+
+    /// Execute a raw [`commands::McuMgrCommand`] with a custom timeout.
+    ///
+    /// Same as [`MCUmgrClient::raw_command`], but the given timeout replaces
+    /// the communication timeout for this command only.
+    pub fn raw_command_with_timeout<T: commands::McuMgrCommand>(
+        &self,
+        command: &T,
+        timeout: Duration,
+    ) -> Result<T::Response, MCUmgrClientError> {
+        self.connection
+            .execute_command_with_timeout(command, timeout)
+            .map_err(Into::into)
+    }
 }
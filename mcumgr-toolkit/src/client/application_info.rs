@@ -0,0 +1,131 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use serde::Serialize;
+
+use crate::{MCUmgrClient, client::MCUmgrClientError, connection::ExecuteError};
+
+/// Structured information on the running image, see [`MCUmgrClient::os_application_info_structured`].
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ApplicationInfo {
+    /// Kernel name, like `Zephyr`
+    pub kernel_name: String,
+    /// Node name, usually the hostname
+    pub node_name: String,
+    /// Kernel release
+    pub kernel_release: String,
+    /// Kernel version
+    pub kernel_version: String,
+    /// Build date and time of the application
+    ///
+    /// `None` if `CONFIG_MCUMGR_GRP_OS_INFO_BUILD_DATE_TIME` is disabled
+    /// or the value could not be parsed.
+    pub build_time: Option<DateTime<FixedOffset>>,
+    /// Build date and time of the application, as reported by the device
+    ///
+    /// Also available if the value could not be parsed into `build_time`.
+    pub build_time_raw: Option<String>,
+    /// Machine, like `arm`
+    pub machine: String,
+    /// Processor, like `cortex-m33`
+    pub processor: String,
+    /// Hardware platform, like the board name
+    pub hardware_platform: String,
+    /// Operating system, like `Zephyr`
+    pub operating_system: String,
+}
+
+/// Parses the build time reported by the device.
+///
+/// Zephyr reports it as ISO 8601 with milliseconds and UTC offset,
+/// but older versions and custom hooks use other formats.
+/// Values without UTC offset are interpreted as UTC.
+fn parse_build_time(value: &str) -> Option<DateTime<FixedOffset>> {
+    const OFFSET_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f %z"];
+    const NAIVE_FORMATS: &[&str] = &[
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%a %b %e %H:%M:%S %Y",
+        "%b %e %Y %H:%M:%S",
+    ];
+
+    let value = value.trim();
+
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .or_else(|| {
+            OFFSET_FORMATS
+                .iter()
+                .find_map(|format| DateTime::parse_from_str(value, format).ok())
+        })
+        .or_else(|| {
+            NAIVE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .map(|datetime| datetime.and_utc().fixed_offset())
+        })
+}
+
+pub(crate) fn application_info(
+    client: &MCUmgrClient,
+) -> Result<ApplicationInfo, MCUmgrClientError> {
+    let query = |format: &str| {
+        client
+            .os_application_info(Some(format))
+            .map(|output| output.trim().to_string())
+    };
+
+    let build_time_raw = match query("b") {
+        Ok(build_time) => Some(build_time),
+        Err(MCUmgrClientError::ExecuteError(ExecuteError::ErrorResponse(e))) => {
+            log::debug!("Failed to fetch build time: {e}");
+            None
+        }
+        Err(e) => return Err(e),
+    };
+
+    let build_time = build_time_raw.as_deref().and_then(|build_time| {
+        let parsed = parse_build_time(build_time);
+        if parsed.is_none() {
+            log::warn!("Unable to parse build time '{build_time}'");
+        }
+        parsed
+    });
+
+    Ok(ApplicationInfo {
+        kernel_name: query("s")?,
+        node_name: query("n")?,
+        kernel_release: query("r")?,
+        kernel_version: query("v")?,
+        build_time,
+        build_time_raw,
+        machine: query("m")?,
+        processor: query("p")?,
+        hardware_platform: query("i")?,
+        operating_system: query("o")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_build_time_formats() {
+        let expected = DateTime::parse_from_rfc3339("2025-03-14T15:09:26Z").unwrap();
+
+        for value in [
+            "2025-03-14T15:09:26.000+0000",
+            "2025-03-14T16:09:26.000+0100",
+            "2025-03-14T15:09:26Z",
+            "2025-03-14 15:09:26 +0000",
+            "2025-03-14T15:09:26",
+            " 2025-03-14 15:09:26\n",
+            "Fri Mar 14 15:09:26 2025",
+            "Mar 14 2025 15:09:26",
+        ] {
+            assert_eq!(parse_build_time(value), Some(expected), "{value}");
+        }
+
+        assert_eq!(parse_build_time("yesterday"), None);
+        assert_eq!(parse_build_time(""), None);
+    }
+}
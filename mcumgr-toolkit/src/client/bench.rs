@@ -0,0 +1,366 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{MCUmgrClient, client::MCUmgrClientError, mcuboot};
+
+/// Possible error values of [`MCUmgrClient::bench`].
+#[derive(Error, Debug, Diagnostic)]
+pub enum BenchError {
+    /// The progress callback returned an error.
+    #[error("Progress callback returned an error")]
+    #[diagnostic(code(mcumgr_toolkit::bench::progress_cb_error))]
+    ProgressCallbackError,
+    /// Reading the SMP buffer size of the device failed.
+    #[error("Failed to read the SMP buffer size of the device")]
+    #[diagnostic(code(mcumgr_toolkit::bench::mcumgr_parameters))]
+    #[diagnostic(help("specify the frame sizes manually"))]
+    ParametersFailed(#[source] MCUmgrClientError),
+    /// An echo request failed.
+    #[error("Echo request failed")]
+    #[diagnostic(code(mcumgr_toolkit::bench::echo))]
+    EchoFailed(#[source] MCUmgrClientError),
+    /// Erasing the image slot failed.
+    #[error("Failed to erase the image slot")]
+    #[diagnostic(code(mcumgr_toolkit::bench::image_erase))]
+    EraseFailed(#[source] MCUmgrClientError),
+    /// An upload failed.
+    #[error("Upload with a frame size of {frame_size} bytes failed")]
+    #[diagnostic(code(mcumgr_toolkit::bench::upload))]
+    UploadFailed {
+        /// The frame size of the upload
+        frame_size: usize,
+        /// The underlying error
+        #[source]
+        source: MCUmgrClientError,
+    },
+    /// The download failed.
+    #[error("Download failed")]
+    #[diagnostic(code(mcumgr_toolkit::bench::download))]
+    DownloadFailed(#[source] MCUmgrClientError),
+}
+
+/// The data transfer that gets measured by [`MCUmgrClient::bench`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BenchTarget {
+    /// Upload to and download from a scratch file.
+    ///
+    /// MCUmgr cannot delete files, so the file remains on the device.
+    File(String),
+    /// Upload to the secondary slot of image 0.
+    ///
+    /// The slot gets erased before and after the benchmark;
+    /// a pending update in that slot gets lost.
+    /// Downloads are not measured, as MCUmgr cannot download images.
+    ImageSlot,
+}
+
+/// Configurable parameters for [`MCUmgrClient::bench`].
+#[derive(Clone, Debug)]
+pub struct BenchParams {
+    /// Default: `BenchTarget::File("/lfs/mcumgr_bench.bin")`
+    ///
+    /// The data transfer to measure.
+    pub target: BenchTarget,
+    /// Default: empty
+    ///
+    /// The SMP frame sizes to measure the upload throughput with.
+    /// If empty, powers of two from 128 bytes up to the buffer size
+    /// reported by the device are used.
+    pub frame_sizes: Vec<usize>,
+    /// Default: 32 KiB
+    ///
+    /// The number of bytes to transfer per measurement.
+    pub transfer_size: usize,
+    /// Default: `20`
+    ///
+    /// The number of echo requests to measure the latency with.
+    pub echo_count: usize,
+}
+
+impl Default for BenchParams {
+    fn default() -> Self {
+        Self {
+            target: BenchTarget::File("/lfs/mcumgr_bench.bin".to_string()),
+            frame_sizes: vec![],
+            transfer_size: 32 * 1024,
+            echo_count: 20,
+        }
+    }
+}
+
+/// The step of the benchmark that is currently being performed
+#[derive(Clone, Debug)]
+pub enum BenchStep {
+    /// Sending echo requests
+    MeasuringLatency,
+    /// Erasing the image slot
+    ErasingSlot,
+    /// Uploading with the given frame size
+    Uploading {
+        /// The SMP frame size
+        frame_size: usize,
+    },
+    /// Downloading the scratch file
+    Downloading,
+}
+
+impl Display for BenchStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MeasuringLatency => f.write_str("Measuring latency ..."),
+            Self::ErasingSlot => f.write_str("Erasing image slot ..."),
+            Self::Uploading { frame_size } => {
+                write!(f, "Uploading with frame size {frame_size} ...")
+            }
+            Self::Downloading => f.write_str("Downloading ..."),
+        }
+    }
+}
+
+/// The progress callback type of [`MCUmgrClient::bench`].
+///
+/// # Arguments
+///
+/// * `BenchStep` - The current step that is being executed
+/// * `Option<(u64, u64)>` - The (current, total) progress of the current step, if available.
+///
+/// # Return
+///
+/// `false` on error; this will cancel the benchmark
+///
+pub type BenchProgressCallback<'a> = dyn FnMut(BenchStep, Option<(u64, u64)>) -> bool + 'a;
+
+/// The round-trip latency of echo requests.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct EchoLatency {
+    /// Number of echo requests
+    pub count: usize,
+    /// The fastest round trip in milliseconds
+    pub min_ms: f64,
+    /// The median round trip in milliseconds
+    pub median_ms: f64,
+    /// The slowest round trip in milliseconds
+    pub max_ms: f64,
+}
+
+/// The upload throughput at a specific frame size.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct UploadThroughput {
+    /// The SMP frame size
+    pub frame_size: usize,
+    /// The throughput in bytes per second
+    pub bytes_per_second: f64,
+}
+
+/// The result of [`MCUmgrClient::bench`].
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct BenchResult {
+    /// The echo latency; `None` if no echo requests were sent
+    pub echo: Option<EchoLatency>,
+    /// The upload throughput for every frame size
+    pub upload: Vec<UploadThroughput>,
+    /// The download throughput in bytes per second; `None` for image slots.
+    ///
+    /// Not measured per frame size, as the device chooses the size of downloaded chunks.
+    pub download_bytes_per_second: Option<f64>,
+    /// The frame size with the highest upload throughput
+    pub optimal_frame_size: Option<usize>,
+}
+
+/// The frame sizes used if none are given.
+fn default_frame_sizes(buf_size: usize) -> Vec<usize> {
+    let mut frame_sizes = std::iter::successors(Some(128usize), |size| size.checked_mul(2))
+        .take_while(|size| *size < buf_size)
+        .collect::<Vec<_>>();
+    frame_sizes.push(buf_size);
+    frame_sizes
+}
+
+/// Creates the data to upload.
+///
+/// Image uploads get rejected by the device if they do not start with an MCUboot image header.
+fn scratch_data(target: &BenchTarget, size: usize) -> Vec<u8> {
+    let mut data = (0..size).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+
+    if *target == BenchTarget::ImageSlot {
+        const HEADER_SIZE: usize = 32;
+
+        let mut header = vec![];
+        header.extend_from_slice(&mcuboot::IMAGE_MAGIC.to_le_bytes()); // ih_magic
+        header.extend_from_slice(&0u32.to_le_bytes()); // ih_load_addr
+        header.extend_from_slice(&(HEADER_SIZE as u16).to_le_bytes()); // ih_hdr_size
+        header.extend_from_slice(&0u16.to_le_bytes()); // ih_protect_tlv_size
+        header.extend_from_slice(&(size.saturating_sub(HEADER_SIZE) as u32).to_le_bytes()); // ih_img_size
+        header.resize(HEADER_SIZE, 0);
+
+        let header_len = header.len().min(size);
+        data[..header_len].copy_from_slice(&header[..header_len]);
+    }
+
+    data
+}
+
+fn bytes_per_second(size: usize, elapsed: Duration) -> f64 {
+    size as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+fn measure_latency(client: &MCUmgrClient, count: usize) -> Result<Option<EchoLatency>, BenchError> {
+    let mut round_trips = (0..count)
+        .map(|_| {
+            let start = Instant::now();
+            client
+                .os_echo("mcumgr-toolkit bench")
+                .map_err(BenchError::EchoFailed)?;
+            Ok(start.elapsed())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    round_trips.sort_unstable();
+
+    let to_ms = |duration: &Duration| duration.as_secs_f64() * 1000.0;
+
+    Ok(round_trips.first().map(|min| EchoLatency {
+        count,
+        min_ms: to_ms(min),
+        median_ms: to_ms(&round_trips[round_trips.len() / 2]),
+        max_ms: to_ms(round_trips.last().unwrap()),
+    }))
+}
+
+/// Measures the latency and throughput of the connection
+///
+/// # Arguments
+///
+/// * `client` - The MCUmgr client.
+/// * `params` - Configurable parameters.
+/// * `progress` - A callback that receives progress updates.
+///
+pub(crate) fn bench(
+    client: &MCUmgrClient,
+    params: &BenchParams,
+    mut progress: Option<&mut BenchProgressCallback>,
+) -> Result<BenchResult, BenchError> {
+    let has_progress = progress.is_some();
+    let mut progress = |step: BenchStep, prog| {
+        if let Some(progress) = &mut progress {
+            if !progress(step, prog) {
+                return Err(BenchError::ProgressCallbackError);
+            }
+        }
+        Ok(())
+    };
+    let map_transfer_error = |err, into_error: &dyn Fn(MCUmgrClientError) -> BenchError| {
+        if let MCUmgrClientError::ProgressCallbackError = err {
+            // Users expect this error when the progress callback errors
+            BenchError::ProgressCallbackError
+        } else {
+            into_error(err)
+        }
+    };
+
+    let frame_sizes = if params.frame_sizes.is_empty() {
+        let buf_size = client
+            .os_mcumgr_parameters()
+            .map_err(BenchError::ParametersFailed)?
+            .buf_size;
+        default_frame_sizes(buf_size as usize)
+    } else {
+        params.frame_sizes.clone()
+    };
+
+    progress(BenchStep::MeasuringLatency, None)?;
+    let echo = measure_latency(client, params.echo_count)?;
+
+    let data = scratch_data(&params.target, params.transfer_size);
+
+    if params.target == BenchTarget::ImageSlot {
+        progress(BenchStep::ErasingSlot, None)?;
+        client.image_erase(None).map_err(BenchError::EraseFailed)?;
+    }
+
+    let mut upload = vec![];
+    for frame_size in frame_sizes {
+        client.set_frame_size(frame_size);
+
+        let step = BenchStep::Uploading { frame_size };
+        progress(step.clone(), None)?;
+        let mut upload_progress_cb =
+            |current, total| progress(step.clone(), Some((current, total))).is_ok();
+        let upload_progress = has_progress.then_some(&mut upload_progress_cb as _);
+
+        let start = Instant::now();
+        match &params.target {
+            BenchTarget::File(name) => {
+                client.fs_file_upload(name, data.as_slice(), data.len() as u64, upload_progress)
+            }
+            BenchTarget::ImageSlot => {
+                client.image_upload(&data, None, None, false, upload_progress)
+            }
+        }
+        .map_err(|err| {
+            map_transfer_error(err, &|source| BenchError::UploadFailed {
+                frame_size,
+                source,
+            })
+        })?;
+
+        upload.push(UploadThroughput {
+            frame_size,
+            bytes_per_second: bytes_per_second(data.len(), start.elapsed()),
+        });
+    }
+
+    let download_bytes_per_second = match &params.target {
+        BenchTarget::File(name) => {
+            progress(BenchStep::Downloading, None)?;
+            let mut download_progress_cb =
+                |current, total| progress(BenchStep::Downloading, Some((current, total))).is_ok();
+
+            let mut downloaded = Vec::with_capacity(data.len());
+            let start = Instant::now();
+            client
+                .fs_file_download(
+                    name,
+                    &mut downloaded,
+                    has_progress.then_some(&mut download_progress_cb),
+                )
+                .map_err(|err| map_transfer_error(err, &BenchError::DownloadFailed))?;
+
+            Some(bytes_per_second(downloaded.len(), start.elapsed()))
+        }
+        BenchTarget::ImageSlot => {
+            progress(BenchStep::ErasingSlot, None)?;
+            client.image_erase(None).map_err(BenchError::EraseFailed)?;
+            None
+        }
+    };
+
+    let optimal_frame_size = upload
+        .iter()
+        .max_by(|a, b| a.bytes_per_second.total_cmp(&b.bytes_per_second))
+        .map(|run| run.frame_size);
+
+    Ok(BenchResult {
+        echo,
+        upload,
+        download_bytes_per_second,
+        optimal_frame_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_frame_sizes_up_to_buffer_size() {
+        assert_eq!(default_frame_sizes(384), [128, 256, 384]);
+        assert_eq!(default_frame_sizes(1024), [128, 256, 512, 1024]);
+        assert_eq!(default_frame_sizes(100), [100]);
+    }
+}
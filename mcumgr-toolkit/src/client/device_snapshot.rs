@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    MCUmgrClient,
+    bootloader::BootloaderInfo,
+    client::MCUmgrClientError,
+    commands::{
+        fs::FileChecksumProperties,
+        image::{ImageState, SlotInfoImage},
+        os::{MCUmgrParametersResponse, TaskStatisticsEntry},
+    },
+};
+
+/// Possible error values of [`MCUmgrClient::device_snapshot`].
+#[derive(Error, Debug, Diagnostic)]
+pub enum DeviceSnapshotError {
+    /// A query failed for a reason other than an error response of the device
+    #[error("Failed to query {query}")]
+    #[diagnostic(code(mcumgr_toolkit::device_snapshot::query))]
+    QueryFailed {
+        /// The name of the failed query
+        query: &'static str,
+        /// The underlying error
+        #[source]
+        source: MCUmgrClientError,
+    },
+}
+
+/// Everything that can be queried about a device, see [`MCUmgrClient::device_snapshot`].
+///
+/// Every field is `None` if the device does not support the corresponding command,
+/// or answers it with an error, like an RTC that was never set.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DeviceSnapshot {
+    /// All fields of the OS/application info, like `uname -a`
+    pub application_info: Option<String>,
+    /// The bootloader, including the MCUboot mode
+    pub bootloader: Option<BootloaderInfo>,
+    /// The state of the image slots
+    pub image_state: Option<Vec<ImageState>>,
+    /// The available image slots
+    pub image_slots: Option<Vec<SlotInfoImage>>,
+    /// The SMP buffer parameters
+    pub mcumgr_parameters: Option<MCUmgrParametersResponse>,
+    /// The RTC datetime of the device
+    pub datetime: Option<chrono::NaiveDateTime>,
+    /// The statistics of all tasks, by task name
+    pub task_statistics: Option<BTreeMap<String, TaskStatisticsEntry>>,
+    /// The supported file checksum types, by name
+    pub checksum_types: Option<BTreeMap<String, FileChecksumProperties>>,
+}
+
+fn tolerate_device_error<T>(
+    query: &'static str,
+    result: Result<T, MCUmgrClientError>,
+) -> Result<Option<T>, DeviceSnapshotError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.device_error().is_some() => {
+            log::debug!("Device cannot answer {query}: {e}");
+            Ok(None)
+        }
+        Err(source) => Err(DeviceSnapshotError::QueryFailed { query, source }),
+    }
+}
+
+pub(crate) fn device_snapshot(
+    client: &MCUmgrClient,
+) -> Result<DeviceSnapshot, DeviceSnapshotError> {
+    Ok(DeviceSnapshot {
+        application_info: tolerate_device_error(
+            "application info",
+            client.os_application_info(Some("a")),
+        )?,
+        bootloader: tolerate_device_error("bootloader info", client.os_bootloader_info())?,
+        image_state: tolerate_device_error("image state", client.image_get_state())?,
+        image_slots: tolerate_device_error("image slot info", client.image_slot_info())?,
+        mcumgr_parameters: tolerate_device_error(
+            "MCUmgr parameters",
+            client.os_mcumgr_parameters(),
+        )?,
+        datetime: tolerate_device_error("datetime", client.os_get_datetime())?,
+        task_statistics: tolerate_device_error("task statistics", client.os_task_statistics())?
+            .map(|tasks| tasks.into_iter().collect()),
+        checksum_types: tolerate_device_error(
+            "checksum types",
+            client.fs_supported_checksum_types(),
+        )?
+        .map(|types| types.into_iter().collect()),
+    })
+}
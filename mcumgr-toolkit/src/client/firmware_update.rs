@@ -27,19 +27,19 @@ pub enum FirmwareUpdateError {
     InvalidMcuBootFirmwareImage(#[from] mcuboot::ImageParseError),
     /// Fetching the image state returned an error.
     #[error("Failed to fetch image state from device")]
-    #[diagnostic(code(mcumgr_toolkit::firmware_update::get_image_state))]
+    #[diagnostic(code(mcumgr_toolkit::firmware_update::get_image_state), forward(0))]
     GetStateFailed(#[source] MCUmgrClientError),
     /// Uploading the firmware image returned an error.
     #[error("Failed to upload firmware image to device")]
-    #[diagnostic(code(mcumgr_toolkit::firmware_update::image_upload))]
+    #[diagnostic(code(mcumgr_toolkit::firmware_update::image_upload), forward(0))]
     ImageUploadFailed(#[source] MCUmgrClientError),
     /// Writing the new image state to the device failed
     #[error("Failed to activate new firmware image")]
-    #[diagnostic(code(mcumgr_toolkit::firmware_update::set_image_state))]
+    #[diagnostic(code(mcumgr_toolkit::firmware_update::set_image_state), forward(0))]
     SetStateFailed(#[source] MCUmgrClientError),
     /// Performing device reset failed
     #[error("Failed to trigger device reboot")]
-    #[diagnostic(code(mcumgr_toolkit::firmware_update::reboot))]
+    #[diagnostic(code(mcumgr_toolkit::firmware_update::reboot), forward(0))]
     RebootFailed(#[source] MCUmgrClientError),
     /// The given firmware is already installed on the device
     #[error("The device is already running the given firmware")]
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    MCUmgrClient,
+    client::{
+        FirmwareUpdateError, FirmwareUpdateParams, FirmwareUpdateStep, UsbSerialError,
+        UsbSerialPortInfo, find_usb_serial_ports,
+    },
+};
+
+/// Possible error values of [`open_usb_serial`].
+#[derive(Error, Debug, Diagnostic)]
+pub enum FleetOpenError {
+    /// Finding the USB serial ports failed
+    #[error("Failed to find the USB serial ports")]
+    #[diagnostic(code(mcumgr_toolkit::fleet::find_ports))]
+    FindPortsFailed(#[from] UsbSerialError),
+}
+
+/// Possible error values of [`firmware_update`], per device.
+#[derive(Error, Debug, Diagnostic)]
+pub enum FleetUpdateError {
+    /// The serial port of the device could not be opened
+    #[error("Failed to open serial port")]
+    #[diagnostic(code(mcumgr_toolkit::fleet::open_port))]
+    OpenPortFailed(#[source] serialport::Error),
+    /// The firmware update of the device failed
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UpdateFailed(#[from] FirmwareUpdateError),
+}
+
+/// A device of a fleet.
+pub struct FleetDevice {
+    /// A human readable name of the device, like its serial port
+    pub name: String,
+    /// The client that is connected to the device,
+    /// or the reason why the device could not be opened
+    pub client: Result<MCUmgrClient, serialport::Error>,
+}
+
+impl FleetDevice {
+    /// Creates a fleet device from an arbitrary client.
+    pub fn new(name: impl Into<String>, client: MCUmgrClient) -> Self {
+        Self {
+            name: name.into(),
+            client: Ok(client),
+        }
+    }
+
+    /// Creates a fleet device that could not be opened.
+    ///
+    /// It gets reported as failed by all fleet operations.
+    pub fn failed(name: impl Into<String>, error: serialport::Error) -> Self {
+        Self {
+            name: name.into(),
+            client: Err(error),
+        }
+    }
+}
+
+/// The progress callback type of [`firmware_update`].
+///
+/// Gets called from the threads that update the devices.
+///
+/// # Arguments
+///
+/// * `usize` - The index of the device in the given fleet
+/// * `FirmwareUpdateStep` - The current step that is being executed on that device
+/// * `Option<(u64, u64)>` - The (current, total) progress of the current step, if available.
+///
+/// # Return
+///
+/// `false` on error; this will cancel the update of that device
+///
+pub type FleetProgressCallback<'a> =
+    dyn Fn(usize, FirmwareUpdateStep, Option<(u64, u64)>) -> bool + Sync + 'a;
+
+/// Opens all USB serial ports identified by VID:PID.
+///
+/// The SMP frame size of every device gets configured automatically,
+/// if the device reports it.
+///
+/// Ports that fail to open are still returned, see [`FleetDevice::failed`],
+/// so that they show up as failed devices in the results of fleet operations.
+///
+/// # Arguments
+///
+/// * `identifier` - A regex that identifies the devices, see [`MCUmgrClient::new_from_usb_serial`].
+/// * `baud_rate` - The baud rate the ports should operate at.
+/// * `timeout` - The communication timeout.
+///
+/// ```no_run
+/// # use mcumgr_toolkit::client::fleet;
+/// # fn main() {
+/// let devices = fleet::open_usb_serial("1234:89AB", 115200, std::time::Duration::from_millis(10000))
+///     .unwrap();
+///
+/// for device in &devices {
+///     match &device.client {
+///         Ok(_) => println!("Found device at {}", device.name),
+///         Err(e) => println!("Failed to open {}: {e}", device.name),
+///     }
+/// }
+/// # }
+/// ```
+pub fn open_usb_serial(
+    identifier: impl AsRef<str>,
+    baud_rate: u32,
+    timeout: Duration,
+) -> Result<Vec<FleetDevice>, FleetOpenError> {
+    let devices = find_usb_serial_ports(identifier)?
+        .into_iter()
+        .map(|UsbSerialPortInfo { port_name, .. }| {
+            let serial = match serialport::new(&port_name, baud_rate)
+                .timeout(timeout)
+                .open()
+            {
+                Ok(serial) => serial,
+                Err(e) => {
+                    log::warn!("{port_name}: Failed to open serial port: {e}");
+                    return FleetDevice::failed(port_name, e);
+                }
+            };
+
+            let client = MCUmgrClient::new_from_serial(serial);
+            if let Err(e) = client.use_auto_frame_size() {
+                log::warn!(
+                    "{port_name}: Failed to read SMP frame size from device, using slow default"
+                );
+                log::warn!("{port_name}: Reason: {e}");
+            }
+
+            FleetDevice::new(port_name, client)
+        })
+        .collect();
+
+    Ok(devices)
+}
+
+/// Performs a firmware update on all devices in parallel.
+///
+/// Every device gets updated in its own thread, see [`MCUmgrClient::firmware_update`].
+/// A failure of one device does not affect the others.
+/// Devices that could not be opened fail with [`FleetUpdateError::OpenPortFailed`].
+///
+/// # Arguments
+///
+/// * `devices` - The devices to update.
+/// * `firmware` - The firmware image data.
+/// * `checksum` - SHA256 of the firmware image. Optional.
+/// * `params` - Configurable parameters.
+/// * `progress` - A callback that receives progress updates of all devices.
+///
+/// # Return
+///
+/// The result of every device, in the same order as `devices`.
+///
+pub fn firmware_update(
+    devices: &[FleetDevice],
+    firmware: impl AsRef<[u8]>,
+    checksum: Option<[u8; 32]>,
+    params: FirmwareUpdateParams,
+    progress: Option<&FleetProgressCallback>,
+) -> Vec<Result<(), FleetUpdateError>> {
+    let firmware = firmware.as_ref();
+
+    std::thread::scope(|scope| {
+        let handles = devices
+            .iter()
+            .enumerate()
+            .map(|(index, device)| {
+                let params = params.clone();
+                scope.spawn(move || {
+                    let client = device
+                        .client
+                        .as_ref()
+                        .map_err(|e| FleetUpdateError::OpenPortFailed(e.clone()))?;
+                    match progress {
+                        Some(progress) => client.firmware_update(
+                            firmware,
+                            checksum,
+                            params,
+                            Some(&mut |step, step_progress| progress(index, step, step_progress)),
+                        ),
+                        None => client.firmware_update(firmware, checksum, params, None),
+                    }
+                    .map_err(Into::into)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,189 @@
+use std::{
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    MCUmgrClient,
+    client::MCUmgrClientError,
+    transport::serial::{ConfigurableBaudRate, ConfigurableTimeout, SerialTransportBuilder},
+};
+
+/// Commonly used baud rates, ordered by how likely they are used with Zephyr.
+pub const COMMON_BAUD_RATES: &[u32] = &[
+    115200, 1000000, 921600, 460800, 230400, 57600, 38400, 19200, 9600,
+];
+
+/// How long to wait for a response at each candidate baud rate.
+const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Possible error values of [`MCUmgrClient::probe_serial`] and [`probe_serial_port`](super::probe_serial_port).
+#[derive(Error, Debug, Diagnostic)]
+pub enum SerialProbeError {
+    /// The device did not respond at any of the candidate baud rates
+    #[error("Device did not respond at any of the baud rates {tried:?}")]
+    #[diagnostic(code(mcumgr_toolkit::serial_probe::no_response))]
+    #[diagnostic(help(
+        "make sure the device is running and SMP over UART is enabled (`CONFIG_MCUMGR_TRANSPORT_UART`)"
+    ))]
+    NoResponse {
+        /// The baud rates that were tried
+        tried: Vec<u32>,
+    },
+    /// Changing the baud rate of the serial port failed
+    #[error("Failed to set the baud rate to {baud_rate}")]
+    #[diagnostic(code(mcumgr_toolkit::serial_probe::set_baud_rate))]
+    SetBaudRateFailed {
+        /// The baud rate that was attempted to set
+        baud_rate: u32,
+        /// The underlying error
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Changing the communication timeout failed
+    #[error("Failed to set the communication timeout")]
+    #[diagnostic(code(mcumgr_toolkit::serial_probe::set_timeout))]
+    SetTimeoutFailed(#[source] MCUmgrClientError),
+    /// Reading or restoring the timeout of the serial port failed
+    #[error("Failed to restore the timeout of the serial port")]
+    #[diagnostic(code(mcumgr_toolkit::serial_probe::restore_timeout))]
+    RestoreTimeoutFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// The working serial configuration found by [`MCUmgrClient::probe_serial`].
+#[derive(Debug, Serialize, Clone, Copy, Eq, PartialEq)]
+pub struct SerialProbeResult {
+    /// The baud rate the device responded at
+    pub baud_rate: u32,
+    /// The SMP frame size reported by the device.
+    ///
+    /// `None` if the device does not report its MCUmgr parameters;
+    /// in that case Zephyr's default frame size is used.
+    pub frame_size: Option<usize>,
+}
+
+/// Allows multiple consecutive clients to use the same serial port.
+struct SharedSerial<T>(Arc<Mutex<T>>);
+
+impl<T: Read> Read for SharedSerial<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().read(buf)
+    }
+}
+
+impl<T: Write> Write for SharedSerial<T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
+
+impl<T: ConfigurableTimeout> ConfigurableTimeout for SharedSerial<T> {
+    fn set_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.0.lock().unwrap().set_timeout(duration)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.lock().unwrap().get_timeout()
+    }
+}
+
+pub(crate) fn probe_serial_port<T>(
+    serial: T,
+    candidate_bauds: &[u32],
+    transport: &SerialTransportBuilder,
+) -> Result<(T, SerialProbeResult), SerialProbeError>
+where
+    T: Send + Read + Write + ConfigurableTimeout + ConfigurableBaudRate + 'static,
+{
+    let timeout = serial
+        .get_timeout()
+        .map_err(SerialProbeError::RestoreTimeoutFailed)?;
+    let serial = Arc::new(Mutex::new(serial));
+
+    let mut detected = None;
+    for &baud_rate in candidate_bauds {
+        serial
+            .lock()
+            .unwrap()
+            .set_baud_rate(baud_rate)
+            .map_err(|source| SerialProbeError::SetBaudRateFailed { baud_rate, source })?;
+
+        let client = MCUmgrClient::new_from_transport(
+            transport.clone().build(SharedSerial(Arc::clone(&serial))),
+        );
+        client
+            .set_timeout(PROBE_TIMEOUT)
+            .map_err(SerialProbeError::SetTimeoutFailed)?;
+
+        match client.check_connection() {
+            Ok(()) => {
+                log::debug!("Device responded at {baud_rate} baud.");
+
+                let frame_size = match client.use_auto_frame_size() {
+                    Ok(()) => Some(client.frame_size()),
+                    Err(e) => {
+                        log::debug!("Failed to read SMP frame size from device: {e}");
+                        None
+                    }
+                };
+
+                detected = Some(SerialProbeResult {
+                    baud_rate,
+                    frame_size,
+                });
+                break;
+            }
+            Err(e) => log::debug!("No response at {baud_rate} baud: {e}"),
+        }
+    }
+
+    let Some(result) = detected else {
+        return Err(SerialProbeError::NoResponse {
+            tried: candidate_bauds.to_vec(),
+        });
+    };
+
+    let mut serial = Arc::into_inner(serial)
+        .expect("All probing clients should be dropped at this point")
+        .into_inner()
+        .unwrap();
+    serial
+        .set_timeout(timeout)
+        .map_err(SerialProbeError::RestoreTimeoutFailed)?;
+
+    Ok((serial, result))
+}
+
+pub(crate) fn probe_serial<T>(
+    serial: T,
+    candidate_bauds: &[u32],
+    timeout: Duration,
+) -> Result<(MCUmgrClient, SerialProbeResult), SerialProbeError>
+where
+    T: Send + Read + Write + ConfigurableTimeout + ConfigurableBaudRate + 'static,
+{
+    let (serial, result) =
+        probe_serial_port(serial, candidate_bauds, &SerialTransportBuilder::new())?;
+
+    let client = MCUmgrClient::new_from_serial(serial);
+    client
+        .set_timeout(timeout)
+        .map_err(SerialProbeError::SetTimeoutFailed)?;
+    if let Some(frame_size) = result.frame_size {
+        client.set_frame_size(frame_size);
+    }
+
+    Ok((client, result))
+}
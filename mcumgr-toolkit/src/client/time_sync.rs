@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, TimeDelta};
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{MCUmgrClient, client::MCUmgrClientError};
+
+/// The number of datetime requests used to estimate the round-trip delay.
+const DELAY_SAMPLES: usize = 5;
+
+/// Possible error values of [`MCUmgrClient::os_measure_drift`].
+#[derive(Error, Debug, Diagnostic)]
+pub enum DriftError {
+    /// The progress callback returned an error.
+    #[error("Progress callback returned an error")]
+    #[diagnostic(code(mcumgr_toolkit::time_sync::progress_cb_error))]
+    ProgressCallbackError,
+    /// Reading the datetime of the device failed.
+    #[error("Failed to read the datetime of the device")]
+    #[diagnostic(code(mcumgr_toolkit::time_sync::get_datetime))]
+    GetDatetimeFailed(#[source] MCUmgrClientError),
+    /// Less than two samples were taken, so no drift could be computed.
+    #[error("Not enough samples to compute the drift")]
+    #[diagnostic(code(mcumgr_toolkit::time_sync::not_enough_samples))]
+    #[diagnostic(help("increase the duration or decrease the interval"))]
+    NotEnoughSamples,
+}
+
+/// The progress callback type of [`MCUmgrClient::os_measure_drift`].
+///
+/// # Arguments
+///
+/// * `u64` - The elapsed time in seconds
+/// * `u64` - The total duration in seconds
+///
+/// # Return
+///
+/// `false` on error; this will cancel the measurement
+///
+pub type DriftProgressCallback<'a> = dyn FnMut(u64, u64) -> bool + 'a;
+
+/// The host clock that the device clock gets compared to.
+///
+/// Usually [`SystemClock`]; other implementations allow to simulate
+/// the passing of time, for example in tests.
+pub trait HostClock {
+    /// The current time of the host
+    fn now(&self) -> NaiveDateTime;
+    /// Blocks for the given duration
+    fn sleep(&self, duration: Duration);
+}
+
+/// The clock of the operating system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemClock {
+    /// The UTC time of the host
+    Utc,
+    /// The local time of the host
+    Local,
+}
+
+impl HostClock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        match self {
+            SystemClock::Utc => chrono::Utc::now().naive_utc(),
+            SystemClock::Local => chrono::Local::now().naive_local(),
+        }
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// The result of [`MCUmgrClient::os_sync_time`].
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct TimeSyncResult {
+    /// The fastest round trip of a datetime request in milliseconds;
+    /// half of it got added to the time that was sent
+    pub round_trip_ms: f64,
+    /// The offset of the device clock before the synchronisation in milliseconds,
+    /// positive if the device was ahead
+    pub offset_before_ms: f64,
+    /// The offset of the device clock after the synchronisation in milliseconds,
+    /// positive if the device is ahead
+    pub residual_offset_ms: f64,
+}
+
+/// The result of [`MCUmgrClient::os_measure_drift`].
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct DriftMeasurement {
+    /// The number of samples taken
+    pub samples: usize,
+    /// The time between the first and the last sample in seconds
+    pub duration_s: f64,
+    /// How much the device clock gained during the measurement in milliseconds,
+    /// negative if it fell behind
+    pub offset_change_ms: f64,
+    /// The drift of the device clock in parts per million,
+    /// positive if it runs fast
+    pub drift_ppm: f64,
+}
+
+/// A single datetime request, with the host time at the moment the device answered.
+///
+/// The device is assumed to answer after half the round trip.
+struct DatetimeSample {
+    device: NaiveDateTime,
+    host: NaiveDateTime,
+    round_trip: Duration,
+}
+
+impl DatetimeSample {
+    fn take(client: &MCUmgrClient, clock: &dyn HostClock) -> Result<Self, MCUmgrClientError> {
+        let start = clock.now();
+        let device = client.os_get_datetime()?;
+        let round_trip = (clock.now() - start).to_std().unwrap_or_default();
+
+        Ok(Self {
+            device,
+            host: start + TimeDelta::from_std(round_trip / 2).unwrap_or_default(),
+            round_trip,
+        })
+    }
+
+    /// Takes multiple samples and keeps the one with the fastest round trip,
+    /// as it has the smallest uncertainty.
+    fn take_best(client: &MCUmgrClient, clock: &dyn HostClock) -> Result<Self, MCUmgrClientError> {
+        let mut best = Self::take(client, clock)?;
+        for _ in 1..DELAY_SAMPLES {
+            let sample = Self::take(client, clock)?;
+            if sample.round_trip < best.round_trip {
+                best = sample;
+            }
+        }
+        Ok(best)
+    }
+
+    fn offset_ms(&self) -> f64 {
+        delta_ms(self.device - self.host)
+    }
+}
+
+fn delta_ms(delta: TimeDelta) -> f64 {
+    delta.as_seconds_f64() * 1000.0
+}
+
+/// Fits a line through the given points with least squares, and returns its slope.
+fn fit_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let covariance = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = points
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+pub(crate) fn sync_time(
+    client: &MCUmgrClient,
+    clock: &dyn HostClock,
+) -> Result<TimeSyncResult, MCUmgrClientError> {
+    let before = DatetimeSample::take_best(client, clock)?;
+
+    let one_way_delay = TimeDelta::from_std(before.round_trip / 2).unwrap_or_default();
+    client.os_set_datetime(clock.now() + one_way_delay)?;
+
+    let after = DatetimeSample::take_best(client, clock)?;
+
+    Ok(TimeSyncResult {
+        round_trip_ms: before.round_trip.as_secs_f64() * 1000.0,
+        offset_before_ms: before.offset_ms(),
+        residual_offset_ms: after.offset_ms(),
+    })
+}
+
+pub(crate) fn measure_drift(
+    client: &MCUmgrClient,
+    clock: &dyn HostClock,
+    duration: Duration,
+    interval: Duration,
+    mut progress: Option<&mut DriftProgressCallback>,
+) -> Result<DriftMeasurement, DriftError> {
+    let take_sample = || DatetimeSample::take(client, clock).map_err(DriftError::GetDatetimeFailed);
+
+    let first = take_sample()?;
+    // (host seconds since the first sample, device offset in ms)
+    let mut points = vec![(0.0, 0.0)];
+
+    loop {
+        let elapsed = (clock.now() - first.host).to_std().unwrap_or_default();
+        if let Some(progress) = &mut progress {
+            if !progress(elapsed.min(duration).as_secs(), duration.as_secs()) {
+                return Err(DriftError::ProgressCallbackError);
+            }
+        }
+        if elapsed >= duration {
+            break;
+        }
+        clock.sleep(interval.min(duration - elapsed));
+
+        let sample = take_sample()?;
+        let host_elapsed = sample.host - first.host;
+        let device_elapsed = sample.device - first.device;
+        points.push((
+            host_elapsed.as_seconds_f64(),
+            delta_ms(device_elapsed - host_elapsed),
+        ));
+    }
+
+    let (duration_s, offset_change_ms) = *points.last().unwrap();
+    let slope_ms_per_s = fit_slope(&points).ok_or(DriftError::NotEnoughSamples)?;
+
+    Ok(DriftMeasurement {
+        samples: points.len(),
+        duration_s,
+        offset_change_ms,
+        // ms per s is 1000 ppm
+        drift_ppm: slope_ms_per_s * 1000.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_slope_of_line() {
+        let points = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+        assert_eq!(fit_slope(&points), Some(2.0));
+    }
+
+    #[test]
+    fn fit_slope_of_noisy_line() {
+        let points = [(0.0, 0.1), (1.0, 0.9), (2.0, 2.1), (3.0, 2.9)];
+        let slope = fit_slope(&points).unwrap();
+        assert!((slope - 0.96).abs() < 1e-9, "{slope}");
+    }
+
+    #[test]
+    fn fit_slope_needs_two_distinct_points() {
+        assert_eq!(fit_slope(&[(1.0, 1.0)]), None);
+        assert_eq!(fit_slope(&[(1.0, 1.0), (1.0, 2.0)]), None);
+    }
+}
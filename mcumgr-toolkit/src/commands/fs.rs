@@ -188,7 +188,7 @@ pub struct SupportedFileChecksumTypesResponse {
 }
 
 /// Data format of the hash/checksum type
-#[derive(Display, Deserialize_repr, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Display, Serialize, Deserialize_repr, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
 #[allow(non_camel_case_types)]
 pub enum FileChecksumDataFormat {
@@ -199,7 +199,7 @@ pub enum FileChecksumDataFormat {
 }
 
 /// Properties of a hash/checksum algorithm
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct FileChecksumProperties {
     /// format that the hash/checksum returns
     pub format: FileChecksumDataFormat,
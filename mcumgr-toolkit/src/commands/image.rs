@@ -463,12 +463,12 @@ mod tests {
                     slots: vec![
                         SlotInfoImageSlot {
                             slot: 0,
-                            size: 42,
+                            size: Some(42),
                             upload_image_id: Some(2),
                         },
                         SlotInfoImageSlot {
                             slot: 1,
-                            size: 123456789012,
+                            size: Some(123456789012),
                             upload_image_id: None,
                         }
                     ],
@@ -520,4 +520,29 @@ mod tests {
             assert!(max_data_size.is_err());
         }
     }
+
+    #[test]
+    fn recommended_timeouts() {
+        use crate::commands::{LONG_OPERATION_TIMEOUT, McuMgrCommand};
+
+        let upload = |off| ImageUpload {
+            image: None,
+            len: None,
+            off,
+            sha: None,
+            data: &[],
+            upgrade: None,
+        };
+
+        assert_eq!(
+            upload(0).recommended_timeout(),
+            Some(LONG_OPERATION_TIMEOUT)
+        );
+        assert_eq!(upload(42).recommended_timeout(), None);
+        assert_eq!(
+            ImageErase { slot: None }.recommended_timeout(),
+            Some(LONG_OPERATION_TIMEOUT)
+        );
+        assert_eq!(GetImageState.recommended_timeout(), None);
+    }
 }
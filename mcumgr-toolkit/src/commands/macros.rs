@@ -6,10 +6,14 @@
 /// - `$iswrite`: Boolean literal indicating if this is a write operation
 /// - `$groupid`: The MCUmgr group
 /// - `$commandid`: The MCUmgr command ID (u8)
+/// - `$timeout`: Optional; the recommended timeout, computed from the request `$req`
 macro_rules! impl_mcumgr_command {
     (@direction read) => {false};
     (@direction write) => {true};
     (($direction:tt, $groupid:ident, $commandid:literal): $request:ty => $response:ty) => {
+        impl_mcumgr_command!(($direction, $groupid, $commandid): $request => $response, timeout(_req) = None);
+    };
+    (($direction:tt, $groupid:ident, $commandid:literal): $request:ty => $response:ty, timeout($req:ident) = $timeout:expr) => {
         impl McuMgrCommand for $request {
             type Payload = Self;
             type Response = $response;
@@ -25,6 +29,10 @@ macro_rules! impl_mcumgr_command {
             fn data(&self) -> &Self {
                 self
             }
+            fn recommended_timeout(&self) -> Option<::std::time::Duration> {
+                let $req = self;
+                $timeout
+            }
         }
     };
 }
@@ -12,8 +12,14 @@ pub mod zephyr;
 mod macros;
 use macros::impl_mcumgr_command;
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+/// The recommended timeout for commands that might take a long time on the device,
+/// like erasing a flash partition.
+pub const LONG_OPERATION_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// SMP version 2 group based error message
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 pub struct ErrResponseV2 {
@@ -48,6 +54,12 @@ pub trait McuMgrCommand {
     fn command_id(&self) -> u8;
     /// the data
     fn data(&self) -> &Self::Payload;
+    /// the minimum timeout this command should be executed with
+    ///
+    /// `None` if the normal communication timeout is sufficient.
+    fn recommended_timeout(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Checks if a value is the default value
@@ -87,6 +99,7 @@ impl std::io::Write for CountingWriter {
 
 impl_mcumgr_command!((read,  MGMT_GROUP_ID_OS, 0): os::Echo<'_> => os::EchoResponse);
 impl_mcumgr_command!((read,  MGMT_GROUP_ID_OS, 2): os::TaskStatistics => os::TaskStatisticsResponse);
+impl_mcumgr_command!((read,  MGMT_GROUP_ID_OS, 3): os::MemoryPoolStatistics => os::MemoryPoolStatisticsResponse);
 impl_mcumgr_command!((read,  MGMT_GROUP_ID_OS, 4): os::DateTimeGet => os::DateTimeGetResponse);
 impl_mcumgr_command!((write, MGMT_GROUP_ID_OS, 4): os::DateTimeSet => os::DateTimeSetResponse);
 impl_mcumgr_command!((write, MGMT_GROUP_ID_OS, 5): os::SystemReset => os::SystemResetResponse);
@@ -97,8 +110,9 @@ impl_mcumgr_command!((read,  MGMT_GROUP_ID_OS, 8): os::BootloaderInfoMcubootMode
 
 impl_mcumgr_command!((read,  MGMT_GROUP_ID_IMAGE, 0): image::GetImageState => image::ImageStateResponse);
 impl_mcumgr_command!((write,  MGMT_GROUP_ID_IMAGE, 0): image::SetImageState<'_> => image::ImageStateResponse);
-impl_mcumgr_command!((write,  MGMT_GROUP_ID_IMAGE, 1): image::ImageUpload<'_, '_> => image::ImageUploadResponse);
-impl_mcumgr_command!((write,  MGMT_GROUP_ID_IMAGE, 5): image::ImageErase => image::ImageEraseResponse);
+// The first chunk of an upload erases the target slot
+impl_mcumgr_command!((write,  MGMT_GROUP_ID_IMAGE, 1): image::ImageUpload<'_, '_> => image::ImageUploadResponse, timeout(req) = (req.off == 0).then_some(LONG_OPERATION_TIMEOUT));
+impl_mcumgr_command!((write,  MGMT_GROUP_ID_IMAGE, 5): image::ImageErase => image::ImageEraseResponse, timeout(_req) = Some(LONG_OPERATION_TIMEOUT));
 impl_mcumgr_command!((read,  MGMT_GROUP_ID_IMAGE, 6): image::SlotInfo => image::SlotInfoResponse);
 
 impl_mcumgr_command!((write, MGMT_GROUP_ID_FS, 0): fs::FileUpload<'_, '_> => fs::FileUploadResponse);
@@ -110,7 +124,7 @@ impl_mcumgr_command!((write, MGMT_GROUP_ID_FS, 4): fs::FileClose => fs::FileClos
 
 impl_mcumgr_command!((write, MGMT_GROUP_ID_SHELL, 0): shell::ShellCommandLineExecute<'_> => shell::ShellCommandLineExecuteResponse);
 
-impl_mcumgr_command!((write, ZEPHYR_MGMT_GRP_BASIC, 0): zephyr::EraseStorage => zephyr::EraseStorageResponse);
+impl_mcumgr_command!((write, ZEPHYR_MGMT_GRP_BASIC, 0): zephyr::EraseStorage => zephyr::EraseStorageResponse, timeout(_req) = Some(LONG_OPERATION_TIMEOUT));
 
 #[cfg(test)]
 mod tests {
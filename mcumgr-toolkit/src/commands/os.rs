@@ -99,6 +99,55 @@ pub struct TaskStatisticsResponse {
     pub tasks: HashMap<String, TaskStatisticsEntry>,
 }
 
+/// [Memory pool statistics](https://docs.zephyrproject.org/latest/services/device_mgmt/smp_groups/smp_group_0.html#memory-pool-statistics) command
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemoryPoolStatistics;
+impl_serialize_as_empty_map!(MemoryPoolStatistics);
+
+/// Statistics of an MCU memory pool
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct MemoryPoolStatisticsEntry {
+    /// size of the memory blocks in the pool
+    pub blksiz: u64,
+    /// number of blocks in the pool
+    pub nblks: u64,
+    /// number of free blocks
+    pub nfree: u64,
+    /// lowest number of free blocks the pool reached
+    pub min: u64,
+}
+
+/// Response for [`MemoryPoolStatistics`] command
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct MemoryPoolStatisticsResponse {
+    /// Dictionary of pool names with their respective statistics
+    ///
+    /// The device reports the pools as the top level entries of the response.
+    #[serde(flatten, deserialize_with = "deserialize_pools")]
+    pub pools: HashMap<String, MemoryPoolStatisticsEntry>,
+}
+
+/// Collects the memory pools from the response, skipping other entries like `rc`
+fn deserialize_pools<'de, D>(de: D) -> Result<HashMap<String, MemoryPoolStatisticsEntry>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PoolOrOther {
+        Pool(MemoryPoolStatisticsEntry),
+        Other(serde::de::IgnoredAny),
+    }
+
+    Ok(HashMap::<String, PoolOrOther>::deserialize(de)?
+        .into_iter()
+        .filter_map(|(name, entry)| match entry {
+            PoolOrOther::Pool(pool) => Some((name, pool)),
+            PoolOrOther::Other(_) => None,
+        })
+        .collect())
+}
+
 /// Parses a [`chrono::NaiveDateTime`] object with optional timezone specifiers
 fn deserialize_datetime_and_ignore_timezone<'de, D>(
     de: D,
@@ -187,7 +236,7 @@ pub struct MCUmgrParameters;
 impl_serialize_as_empty_map!(MCUmgrParameters);
 
 /// Response for [`MCUmgrParameters`] command
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct MCUmgrParametersResponse {
     /// Single SMP buffer size, this includes SMP header and CBOR payload
     pub buf_size: u32,
@@ -333,6 +382,33 @@ mod tests {
         ]) },
     }
 
+    command_encode_decode_test! {
+        memory_pool_statistics,
+        (0, 0, 3),
+        MemoryPoolStatistics,
+        cbor!({}),
+        cbor!({
+            "heap" => {
+                "blksiz" => 16,
+                "nblks" => 64,
+                "nfree" => 20,
+                "min" => 3,
+            },
+            "rc" => 0,
+        }),
+        MemoryPoolStatisticsResponse{ pools: HashMap::from([
+            (
+                "heap".to_string(),
+                MemoryPoolStatisticsEntry{
+                    blksiz: 16,
+                    nblks: 64,
+                    nfree: 20,
+                    min: 3,
+                },
+            ),
+        ]) },
+    }
+
     command_encode_decode_test! {
         datetime_get_with_timezone,
         (0, 0, 4),
@@ -1,9 +1,27 @@
-use std::{io::Cursor, sync::Mutex, time::Duration};
+/// Observation of the SMP traffic
+mod observer;
+
+pub use observer::{FrameDirection, FrameEvent, FrameHeader, FrameObserver, FrameOp};
+
+/// Statistics about the link quality
+mod stats;
+
+use stats::LinkCounters;
+pub use stats::{LinkStats, RoundTripStats};
+
+use std::{
+    io::Cursor,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
 
 use crate::{
     commands::{ErrResponse, ErrResponseV2, McuMgrCommand},
     smp_errors::{DeviceError, MCUmgrErr},
-    transport::{ReceiveError, SendError, Transport},
+    transport::{
+        ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, SmpVersion, Transport,
+        frame_header, frame_sequence_num,
+    },
 };
 
 use miette::{Diagnostic, IntoDiagnostic};
@@ -13,6 +31,286 @@ struct Inner {
     transport: Box<dyn Transport + Send>,
     next_seqnum: u8,
     transport_buffer: Box<[u8; u16::MAX as usize]>,
+    /// The SMP version used for requests; `None` if not negotiated yet
+    smp_version: Option<SmpVersion>,
+    observers: Vec<Box<dyn FrameObserver>>,
+    link_counters: LinkCounters,
+}
+
+fn notify_observers(
+    observers: &mut [Box<dyn FrameObserver>],
+    direction: FrameDirection,
+    header: FrameHeader,
+    payload: &[u8],
+    elapsed: Duration,
+    ignored: bool,
+) {
+    if observers.is_empty() {
+        return;
+    }
+
+    let event = FrameEvent {
+        direction,
+        header,
+        payload,
+        timestamp: SystemTime::now(),
+        elapsed,
+        ignored,
+    };
+
+    for observer in observers {
+        observer.on_frame(&event);
+    }
+}
+
+/// Counts and reports every frame received while waiting for the response to a request.
+struct ObservingTransport<'a> {
+    transport: &'a mut (dyn Transport + Send),
+    link_counters: &'a mut LinkCounters,
+    observers: &'a mut [Box<dyn FrameObserver>],
+    sequence_num: u8,
+    sent_at: Instant,
+}
+
+impl Transport for ObservingTransport<'_> {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        self.transport.send_raw_frame(header, data)
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        let frame = self.transport.recv_raw_frame(buffer)?;
+
+        let ignored = frame_sequence_num(frame).is_some_and(|num| num != self.sequence_num);
+        self.link_counters.record_receive(frame.len(), ignored);
+
+        if let Some(header) = frame_header(frame) {
+            notify_observers(
+                self.observers,
+                FrameDirection::Rx,
+                header,
+                &frame[SMP_HEADER_SIZE..],
+                self.sent_at.elapsed(),
+                ignored,
+            );
+        }
+
+        Ok(frame)
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.transport.set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.transport.get_timeout()
+    }
+}
+
+impl Inner {
+    /// Executes a command with a temporarily changed timeout.
+    ///
+    /// `timeout` computes the timeout to use from the current one.
+    fn execute_command_with_timeout<R: McuMgrCommand>(
+        &mut self,
+        request: &R,
+        timeout: impl FnOnce(Duration) -> Duration,
+    ) -> Result<R::Response, ExecuteError> {
+        let previous_timeout = self
+            .transport
+            .get_timeout()
+            .map_err(ExecuteError::SetTimeoutFailed)?;
+        let timeout = timeout(previous_timeout);
+
+        if timeout == previous_timeout {
+            return self.execute_command(request);
+        }
+
+        log::debug!("Using timeout of {} ms", timeout.as_millis());
+        self.transport
+            .set_timeout(timeout)
+            .map_err(ExecuteError::SetTimeoutFailed)?;
+
+        let result = self.execute_command(request);
+
+        let restore_result = self
+            .transport
+            .set_timeout(previous_timeout)
+            .map_err(ExecuteError::SetTimeoutFailed);
+
+        let response = result?;
+        restore_result?;
+        Ok(response)
+    }
+
+    /// Executes a command, negotiating the SMP version if necessary.
+    fn execute_command<R: McuMgrCommand>(
+        &mut self,
+        request: &R,
+    ) -> Result<R::Response, ExecuteError> {
+        let Some(version) = self.smp_version else {
+            return match self.execute_command_with_version(request, SmpVersion::V2) {
+                Err(ExecuteError::ErrorResponse(DeviceError::V1 { rc, .. }))
+                    if rc == MCUmgrErr::MGMT_ERR_UNSUPPORTED_TOO_NEW as i32 =>
+                {
+                    log::debug!("Device does not support SMP v2, falling back to v1.");
+                    self.smp_version = Some(SmpVersion::V1);
+                    self.execute_command_with_version(request, SmpVersion::V1)
+                }
+                result => result,
+            };
+        };
+
+        self.execute_command_with_version(request, version)
+    }
+
+    /// Checks the version of a response and finishes the version negotiation.
+    fn check_response_version(
+        smp_version: &mut Option<SmpVersion>,
+        response_version: SmpVersion,
+    ) -> Result<(), ExecuteError> {
+        match *smp_version {
+            None => {
+                log::debug!("Using SMP {response_version}.");
+                *smp_version = Some(response_version);
+                Ok(())
+            }
+            Some(version) if version == response_version => Ok(()),
+            Some(_) => Err(ExecuteError::ReceiveFailed(
+                ReceiveError::UnexpectedVersion(response_version.header_value()),
+            )),
+        }
+    }
+
+    /// Sends the request in the first `data_size` bytes of the transport buffer
+    /// and receives its response.
+    fn transceive(
+        &mut self,
+        version: SmpVersion,
+        write_operation: bool,
+        group_id: u16,
+        command_id: u8,
+        data_size: usize,
+    ) -> Result<&[u8], ExecuteError> {
+        let data = &self.transport_buffer[..data_size];
+
+        log::debug!("TX data: {}", hex::encode(data));
+
+        let sequence_num = self.next_seqnum;
+        self.next_seqnum = self.next_seqnum.wrapping_add(1);
+
+        self.transport.send_frame(
+            version,
+            write_operation,
+            sequence_num,
+            group_id,
+            command_id,
+            data,
+        )?;
+        self.link_counters.record_send(data.len());
+        let sent_at = Instant::now();
+
+        notify_observers(
+            &mut self.observers,
+            FrameDirection::Tx,
+            FrameHeader {
+                version,
+                op: FrameOp::new(FrameDirection::Tx, write_operation),
+                group_id,
+                command_id,
+                sequence_num,
+            },
+            data,
+            Duration::ZERO,
+            false,
+        );
+
+        let result = ObservingTransport {
+            transport: self.transport.as_mut(),
+            link_counters: &mut self.link_counters,
+            observers: &mut self.observers,
+            sequence_num,
+            sent_at,
+        }
+        .receive_frame(
+            &mut self.transport_buffer,
+            version,
+            write_operation,
+            sequence_num,
+            group_id,
+            command_id,
+        );
+
+        let (response_version, response) = match result {
+            Ok(response) => response,
+            Err(e) => {
+                self.link_counters.record_receive_error(&e);
+                return Err(e.into());
+            }
+        };
+        self.link_counters.record_response(sent_at.elapsed());
+
+        log::debug!("RX data: {}", hex::encode(response));
+
+        Self::check_response_version(&mut self.smp_version, response_version)?;
+
+        Ok(response)
+    }
+
+    fn execute_command_with_version<R: McuMgrCommand>(
+        &mut self,
+        request: &R,
+        version: SmpVersion,
+    ) -> Result<R::Response, ExecuteError> {
+        let mut cursor = Cursor::new(self.transport_buffer.as_mut_slice());
+        ciborium::into_writer(request.data(), &mut cursor)
+            .into_diagnostic()
+            .map_err(Into::into)
+            .map_err(ExecuteError::EncodeFailed)?;
+        let data_size = cursor.position() as usize;
+
+        let response = self.transceive(
+            version,
+            request.is_write_operation(),
+            request.group_id(),
+            request.command_id(),
+            data_size,
+        )?;
+
+        let err: ErrResponse = ciborium::from_reader(Cursor::new(response))
+            .into_diagnostic()
+            .map_err(Into::into)
+            .map_err(ExecuteError::DecodeFailed)?;
+
+        if let Some(ErrResponseV2 { rc, group }) = err.err {
+            return Err(ExecuteError::ErrorResponse(DeviceError::V2 { group, rc }));
+        }
+
+        if let Some(rc) = err.rc {
+            if rc != MCUmgrErr::MGMT_ERR_EOK as i32 {
+                return Err(ExecuteError::ErrorResponse(DeviceError::V1 {
+                    rc,
+                    rsn: err.rsn,
+                }));
+            }
+        }
+
+        let decoded_response: R::Response = ciborium::from_reader(Cursor::new(response))
+            .into_diagnostic()
+            .map_err(Into::into)
+            .map_err(ExecuteError::DecodeFailed)?;
+
+        Ok(decoded_response)
+    }
 }
 
 /// An SMP protocol layer connection to a device.
@@ -44,11 +342,24 @@ pub enum ExecuteError {
     DecodeFailed(#[source] Box<dyn miette::Diagnostic + Send + Sync>),
     /// The device returned an SMP error
     #[error("Device returned error code: {0}")]
-    #[diagnostic(code(mcumgr_toolkit::connection::execute::device_error))]
+    #[diagnostic(code(mcumgr_toolkit::connection::execute::device_error), forward(0))]
     ErrorResponse(DeviceError),
+    /// Reading or changing the communication timeout for the command failed
+    #[error("Failed to change the communication timeout")]
+    #[diagnostic(code(mcumgr_toolkit::connection::execute::timeout))]
+    SetTimeoutFailed(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl ExecuteError {
+    /// The error the device responded with, if any
+    pub fn device_error(&self) -> Option<&DeviceError> {
+        if let Self::ErrorResponse(err) = self {
+            Some(err)
+        } else {
+            None
+        }
+    }
+
     /// Checks if the device reported the command as unsupported
     pub fn command_not_supported(&self) -> bool {
         if let Self::ErrorResponse(DeviceError::V1 { rc, .. }) = self {
@@ -62,11 +373,15 @@ impl ExecuteError {
 impl Connection {
     /// Creates a new SMP
     pub fn new<T: Transport + Send + 'static>(transport: T) -> Self {
+        let link_counters = LinkCounters::new(&transport);
         Self {
             inner: Mutex::new(Inner {
                 transport: Box::new(transport),
                 next_seqnum: rand::random(),
                 transport_buffer: Box::new([0; u16::MAX as usize]),
+                smp_version: None,
+                observers: vec![],
+                link_counters,
             }),
         }
     }
@@ -82,73 +397,87 @@ impl Connection {
         self.inner.lock().unwrap().transport.set_timeout(timeout)
     }
 
-    /// Executes a given CBOR based SMP command.
-    pub fn execute_command<R: McuMgrCommand>(
-        &self,
-        request: &R,
-    ) -> Result<R::Response, ExecuteError> {
-        let mut lock_guard = self.inner.lock().unwrap();
-        let locked_self: &mut Inner = &mut lock_guard;
-
-        let mut cursor = Cursor::new(locked_self.transport_buffer.as_mut_slice());
-        ciborium::into_writer(request.data(), &mut cursor)
-            .into_diagnostic()
-            .map_err(Into::into)
-            .map_err(ExecuteError::EncodeFailed)?;
-        let data_size = cursor.position() as usize;
-        let data = &locked_self.transport_buffer[..data_size];
-
-        log::debug!("TX data: {}", hex::encode(data));
-
-        let sequence_num = locked_self.next_seqnum;
-        locked_self.next_seqnum = locked_self.next_seqnum.wrapping_add(1);
+    /// Returns the SMP protocol version used for requests.
+    ///
+    /// `None` if the version was not negotiated with the device yet.
+    /// The negotiation happens during the first command; SMP v2 is
+    /// preferred and v1 is used if the device does not support v2.
+    pub fn smp_version(&self) -> Option<SmpVersion> {
+        self.inner.lock().unwrap().smp_version
+    }
 
-        let write_operation = request.is_write_operation();
-        let group_id = request.group_id();
-        let command_id = request.command_id();
+    /// Forces the SMP protocol version used for requests.
+    ///
+    /// `None` renegotiates the version during the next command.
+    pub fn set_smp_version(&self, version: Option<SmpVersion>) {
+        self.inner.lock().unwrap().smp_version = version;
+    }
 
-        locked_self.transport.send_frame(
-            write_operation,
-            sequence_num,
-            group_id,
-            command_id,
-            data,
-        )?;
+    /// Adds an observer that receives every SMP frame sent or received.
+    pub fn add_frame_observer(&self, observer: impl FrameObserver + 'static) {
+        self.inner
+            .lock()
+            .unwrap()
+            .observers
+            .push(Box::new(observer));
+    }
 
-        let response = locked_self.transport.receive_frame(
-            &mut locked_self.transport_buffer,
-            write_operation,
-            sequence_num,
-            group_id,
-            command_id,
-        )?;
+    /// Returns statistics about the link to the device.
+    pub fn link_stats(&self) -> LinkStats {
+        let inner = self.inner.lock().unwrap();
+        inner.link_counters.snapshot(inner.transport.as_ref())
+    }
 
-        log::debug!("RX data: {}", hex::encode(response));
+    /// Resets the statistics returned by [`Connection::link_stats`].
+    pub fn reset_link_stats(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.link_counters = LinkCounters::new(inner.transport.as_ref());
+    }
 
-        let err: ErrResponse = ciborium::from_reader(Cursor::new(response))
-            .into_diagnostic()
+    /// Receives console output of the device for the given duration.
+    ///
+    /// See [`Transport::receive_console`].
+    pub fn receive_console(&self, duration: Duration) -> Result<(), ExecuteError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .transport
+            .receive_console(duration)
             .map_err(Into::into)
-            .map_err(ExecuteError::DecodeFailed)?;
+    }
 
-        if let Some(ErrResponseV2 { rc, group }) = err.err {
-            return Err(ExecuteError::ErrorResponse(DeviceError::V2 { group, rc }));
-        }
+    /// Executes a given CBOR based SMP command.
+    ///
+    /// If the command has a [recommended timeout](McuMgrCommand::recommended_timeout)
+    /// that is longer than the current communication timeout, the recommended timeout
+    /// is used for this command only.
+    pub fn execute_command<R: McuMgrCommand>(
+        &self,
+        request: &R,
+    ) -> Result<R::Response, ExecuteError> {
+        let mut lock_guard = self.inner.lock().unwrap();
 
-        if let Some(rc) = err.rc {
-            if rc != MCUmgrErr::MGMT_ERR_EOK as i32 {
-                return Err(ExecuteError::ErrorResponse(DeviceError::V1 {
-                    rc,
-                    rsn: err.rsn,
-                }));
+        match request.recommended_timeout() {
+            Some(recommended) => {
+                lock_guard.execute_command_with_timeout(request, |current| current.max(recommended))
             }
+            None => lock_guard.execute_command(request),
         }
+    }
 
-        let decoded_response: R::Response = ciborium::from_reader(Cursor::new(response))
-            .into_diagnostic()
-            .map_err(Into::into)
-            .map_err(ExecuteError::DecodeFailed)?;
-
-        Ok(decoded_response)
+    /// Executes a given CBOR based SMP command with a custom timeout.
+    ///
+    /// The timeout only applies to this command; the previous communication
+    /// timeout is restored afterwards.
+    pub fn execute_command_with_timeout<R: McuMgrCommand>(
+        &self,
+        request: &R,
+        timeout: Duration,
+    ) -> Result<R::Response, ExecuteError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .execute_command_with_timeout(request, |_| timeout)
     }
 
     /// Executes a raw SMP command.
@@ -170,27 +499,17 @@ impl Connection {
         let mut lock_guard = self.inner.lock().unwrap();
         let locked_self: &mut Inner = &mut lock_guard;
 
-        let sequence_num = locked_self.next_seqnum;
-        locked_self.next_seqnum = locked_self.next_seqnum.wrapping_add(1);
-
-        locked_self.transport.send_frame(
-            write_operation,
-            sequence_num,
-            group_id,
-            command_id,
-            data,
-        )?;
+        let version = locked_self.smp_version.unwrap_or(SmpVersion::V2);
 
         locked_self
-            .transport
-            .receive_frame(
-                &mut locked_self.transport_buffer,
-                write_operation,
-                sequence_num,
-                group_id,
-                command_id,
-            )
-            .map_err(Into::into)
-            .map(|val| val.into())
+            .transport_buffer
+            .get_mut(..data.len())
+            .ok_or(SendError::DataTooBig)?
+            .copy_from_slice(data);
+
+        let response =
+            locked_self.transceive(version, write_operation, group_id, command_id, data.len())?;
+
+        Ok(response.into())
     }
 }
@@ -0,0 +1,220 @@
+use std::{
+    fmt::Write,
+    time::{Duration, SystemTime},
+};
+
+use crate::transport::SmpVersion;
+
+/// The direction of an SMP frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameDirection {
+    /// A request sent to the device
+    Tx,
+    /// A response received from the device
+    Rx,
+}
+
+/// The operation of an SMP frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum FrameOp {
+    /// Read request
+    #[strum(to_string = "read")]
+    Read,
+    /// Read response
+    #[strum(to_string = "read_rsp")]
+    ReadResponse,
+    /// Write request
+    #[strum(to_string = "write")]
+    Write,
+    /// Write response
+    #[strum(to_string = "write_rsp")]
+    WriteResponse,
+}
+
+impl FrameOp {
+    pub(crate) fn new(direction: FrameDirection, write_operation: bool) -> Self {
+        match (direction, write_operation) {
+            (FrameDirection::Tx, false) => Self::Read,
+            (FrameDirection::Rx, false) => Self::ReadResponse,
+            (FrameDirection::Tx, true) => Self::Write,
+            (FrameDirection::Rx, true) => Self::WriteResponse,
+        }
+    }
+}
+
+/// The decoded header of an SMP frame.
+///
+/// See [SMP Protocol Specification](https://docs.zephyrproject.org/latest/services/device_mgmt/smp_protocol.html#frame-header).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    /// The SMP protocol version
+    pub version: SmpVersion,
+    /// The operation
+    pub op: FrameOp,
+    /// The group ID of the command
+    pub group_id: u16,
+    /// The command ID
+    pub command_id: u8,
+    /// The sequence number
+    pub sequence_num: u8,
+}
+
+/// An SMP frame that was sent or received by a [`Connection`](super::Connection).
+#[derive(Debug, Clone)]
+pub struct FrameEvent<'a> {
+    /// Whether the frame was sent or received
+    pub direction: FrameDirection,
+    /// The decoded SMP header
+    pub header: FrameHeader,
+    /// The raw payload of the frame, most likely CBOR encoded
+    pub payload: &'a [u8],
+    /// When the frame was sent or received
+    pub timestamp: SystemTime,
+    /// For received frames, the time since the request was sent; zero for sent frames
+    pub elapsed: Duration,
+    /// Whether the received frame was ignored because it is not the response to the
+    /// pending request, most likely a late response to an earlier request
+    pub ignored: bool,
+}
+
+impl FrameEvent<'_> {
+    /// Renders the payload in [CBOR diagnostic notation](https://www.rfc-editor.org/rfc/rfc8949.html#name-diagnostic-notation).
+    ///
+    /// Falls back to a hex dump if the payload is not valid CBOR.
+    pub fn cbor_diagnostic(&self) -> String {
+        match ciborium::from_reader::<ciborium::Value, _>(self.payload) {
+            Ok(value) => {
+                let mut result = String::new();
+                write_cbor_diagnostic(&mut result, &value);
+                result
+            }
+            Err(_) => format!("h'{}'", hex::encode(self.payload)),
+        }
+    }
+}
+
+fn write_cbor_diagnostic(out: &mut String, value: &ciborium::Value) {
+    use ciborium::Value;
+
+    match value {
+        Value::Integer(i) => write!(out, "{}", i128::from(*i)).unwrap(),
+        Value::Bytes(bytes) => write!(out, "h'{}'", hex::encode(bytes)).unwrap(),
+        Value::Float(f) if f.is_nan() => out.push_str("NaN"),
+        Value::Float(f) if f.is_infinite() => {
+            out.push_str(if *f > 0.0 { "Infinity" } else { "-Infinity" })
+        }
+        Value::Float(f) => write!(out, "{f:?}").unwrap(),
+        Value::Text(text) => write!(out, "{text:?}").unwrap(),
+        Value::Bool(b) => write!(out, "{b}").unwrap(),
+        Value::Null => out.push_str("null"),
+        Value::Tag(tag, value) => {
+            write!(out, "{tag}(").unwrap();
+            write_cbor_diagnostic(out, value);
+            out.push(')');
+        }
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_cbor_diagnostic(out, value);
+            }
+            out.push(']');
+        }
+        Value::Map(entries) => {
+            out.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_cbor_diagnostic(out, key);
+                out.push_str(": ");
+                write_cbor_diagnostic(out, value);
+            }
+            out.push('}');
+        }
+        _ => out.push_str("undefined"),
+    }
+}
+
+/// Receives every SMP frame sent or received by a [`Connection`](super::Connection).
+///
+/// Received frames are reported as they arrive, including ignored ones;
+/// frames whose header cannot be decoded are not reported.
+///
+/// Implemented for all closures that take a [`FrameEvent`].
+pub trait FrameObserver: Send {
+    /// Gets called for every frame.
+    ///
+    /// Gets called while the connection is locked, so it should return quickly.
+    fn on_frame(&mut self, event: &FrameEvent<'_>);
+}
+
+impl<F: FnMut(&FrameEvent<'_>) + Send> FrameObserver for F {
+    fn on_frame(&mut self, event: &FrameEvent<'_>) {
+        self(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciborium::cbor;
+
+    fn diagnostic(value: ciborium::Value) -> String {
+        let mut payload = vec![];
+        ciborium::into_writer(&value, &mut payload).unwrap();
+
+        FrameEvent {
+            direction: FrameDirection::Tx,
+            header: FrameHeader {
+                version: SmpVersion::V2,
+                op: FrameOp::Read,
+                group_id: 0,
+                command_id: 0,
+                sequence_num: 0,
+            },
+            payload: &payload,
+            timestamp: SystemTime::now(),
+            elapsed: Duration::ZERO,
+            ignored: false,
+        }
+        .cbor_diagnostic()
+    }
+
+    #[test]
+    fn cbor_diagnostic() {
+        assert_eq!(
+            diagnostic(
+                cbor!({
+                    "d" => "Hello \"world\"",
+                    "off" => -42,
+                    "data" => ciborium::Value::Bytes(vec![0xde, 0xad]),
+                    "list" => [true, null, 1.5],
+                })
+                .unwrap()
+            ),
+            r#"{"d": "Hello \"world\"", "off": -42, "data": h'dead', "list": [true, null, 1.5]}"#
+        );
+    }
+
+    #[test]
+    fn cbor_diagnostic_invalid() {
+        let event = FrameEvent {
+            direction: FrameDirection::Rx,
+            header: FrameHeader {
+                version: SmpVersion::V1,
+                op: FrameOp::ReadResponse,
+                group_id: 0,
+                command_id: 0,
+                sequence_num: 0,
+            },
+            payload: &[0xff, 0x01],
+            timestamp: SystemTime::now(),
+            elapsed: Duration::ZERO,
+            ignored: false,
+        };
+        assert_eq!(event.cbor_diagnostic(), "h'ff01'");
+    }
+}
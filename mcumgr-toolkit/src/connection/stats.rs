@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use crate::transport::{ReceiveError, SMP_HEADER_SIZE, Transport, TransportStats};
+
+/// Round-trip times of the requests sent over a [`Connection`](super::Connection).
+///
+/// Percentiles use the nearest-rank method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTripStats {
+    /// Number of requests that received a response
+    pub count: usize,
+    /// The fastest round trip
+    pub min: Duration,
+    /// The median round trip
+    pub p50: Duration,
+    /// The 90th percentile
+    pub p90: Duration,
+    /// The 99th percentile
+    pub p99: Duration,
+    /// The slowest round trip
+    pub max: Duration,
+}
+
+impl RoundTripStats {
+    fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let percentile = |p: usize| sorted[(sorted.len() * p).div_ceil(100).max(1) - 1];
+
+        Some(Self {
+            count: sorted.len(),
+            min: *sorted.first()?,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// Statistics about the link of a [`Connection`](super::Connection).
+///
+/// Byte counts refer to complete SMP frames, including their header.
+/// The overhead of the transport encoding is contained in [`LinkStats::transport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    /// Number of sent SMP frames
+    pub frames_sent: u64,
+    /// Number of received SMP frames, including ignored ones
+    pub frames_received: u64,
+    /// Number of bytes of the sent SMP frames
+    pub bytes_sent: u64,
+    /// Number of bytes of the received SMP frames
+    pub bytes_received: u64,
+    /// Number of received frames that were ignored because of a wrong sequence number,
+    /// most likely late responses to earlier requests
+    pub ignored_frames: u64,
+    /// Number of requests that did not receive a response in time
+    pub timeouts: u64,
+    /// Statistics of the transport encoding; `None` if the transport does not encode frames
+    pub transport: Option<TransportStats>,
+    /// Round-trip times of the requests; `None` if no response was received yet
+    pub round_trip: Option<RoundTripStats>,
+}
+
+/// Collects the [`LinkStats`] of a connection.
+pub(super) struct LinkCounters {
+    stats: LinkStats,
+    round_trip_times: Vec<Duration>,
+    /// The transport statistics at the time of the last reset
+    transport_baseline: Option<TransportStats>,
+}
+
+impl LinkCounters {
+    pub(super) fn new(transport: &dyn Transport) -> Self {
+        Self {
+            stats: LinkStats::default(),
+            round_trip_times: vec![],
+            transport_baseline: transport.stats(),
+        }
+    }
+
+    pub(super) fn record_send(&mut self, data_len: usize) {
+        self.stats.frames_sent += 1;
+        self.stats.bytes_sent += (SMP_HEADER_SIZE + data_len) as u64;
+    }
+
+    pub(super) fn record_response(&mut self, round_trip_time: Duration) {
+        self.round_trip_times.push(round_trip_time);
+    }
+
+    pub(super) fn record_receive_error(&mut self, error: &ReceiveError) {
+        if let ReceiveError::TransportError(e) = error {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                self.stats.timeouts += 1;
+            }
+        }
+    }
+
+    /// Counts a received frame; `ignored` if it did not belong to the pending request.
+    pub(super) fn record_receive(&mut self, frame_len: usize, ignored: bool) {
+        self.stats.frames_received += 1;
+        self.stats.bytes_received += frame_len as u64;
+        if ignored {
+            self.stats.ignored_frames += 1;
+        }
+    }
+
+    pub(super) fn snapshot(&self, transport: &dyn Transport) -> LinkStats {
+        LinkStats {
+            transport: transport.stats().map(|current| {
+                let baseline = self.transport_baseline.unwrap_or_default();
+                TransportStats {
+                    encoded_bytes_sent: current.encoded_bytes_sent - baseline.encoded_bytes_sent,
+                    encoded_bytes_received: current.encoded_bytes_received
+                        - baseline.encoded_bytes_received,
+                    crc_errors: current.crc_errors - baseline.crc_errors,
+                }
+            }),
+            round_trip: RoundTripStats::from_samples(&self.round_trip_times),
+            ..self.stats.clone()
+        }
+    }
+}
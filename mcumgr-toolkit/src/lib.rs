@@ -64,4 +64,45 @@ impl MCUmgrGroup {
             format!("{PERUSER}({group_id})")
         }
     }
+
+    /// Returns the name of a command, if it is known
+    pub fn command_name(group_id: u16, command_id: u8) -> Option<&'static str> {
+        use MCUmgrGroup::*;
+
+        let name = match (Self::from_repr(group_id)?, command_id) {
+            (MGMT_GROUP_ID_OS, 0) => "echo",
+            (MGMT_GROUP_ID_OS, 1) => "console_echo_control",
+            (MGMT_GROUP_ID_OS, 2) => "task_statistics",
+            (MGMT_GROUP_ID_OS, 3) => "memory_pool_statistics",
+            (MGMT_GROUP_ID_OS, 4) => "datetime",
+            (MGMT_GROUP_ID_OS, 5) => "system_reset",
+            (MGMT_GROUP_ID_OS, 6) => "mcumgr_parameters",
+            (MGMT_GROUP_ID_OS, 7) => "application_info",
+            (MGMT_GROUP_ID_OS, 8) => "bootloader_info",
+            (MGMT_GROUP_ID_IMAGE, 0) => "state",
+            (MGMT_GROUP_ID_IMAGE, 1) => "upload",
+            (MGMT_GROUP_ID_IMAGE, 5) => "erase",
+            (MGMT_GROUP_ID_IMAGE, 6) => "slot_info",
+            (MGMT_GROUP_ID_STAT, 0) => "group_data",
+            (MGMT_GROUP_ID_STAT, 1) => "list_groups",
+            (MGMT_GROUP_ID_SETTINGS, 0) => "read_write",
+            (MGMT_GROUP_ID_SETTINGS, 1) => "delete",
+            (MGMT_GROUP_ID_SETTINGS, 2) => "commit",
+            (MGMT_GROUP_ID_SETTINGS, 3) => "load_save",
+            (MGMT_GROUP_ID_FS, 0) => "file",
+            (MGMT_GROUP_ID_FS, 1) => "status",
+            (MGMT_GROUP_ID_FS, 2) => "checksum",
+            (MGMT_GROUP_ID_FS, 3) => "supported_checksum_types",
+            (MGMT_GROUP_ID_FS, 4) => "close",
+            (MGMT_GROUP_ID_SHELL, 0) => "execute",
+            (MGMT_GROUP_ID_ENUM, 0) => "count",
+            (MGMT_GROUP_ID_ENUM, 1) => "list",
+            (MGMT_GROUP_ID_ENUM, 2) => "single",
+            (MGMT_GROUP_ID_ENUM, 3) => "details",
+            (ZEPHYR_MGMT_GRP_BASIC, 0) => "erase_storage",
+            _ => return None,
+        };
+
+        Some(name)
+    }
 }
@@ -1,4 +1,5 @@
 /// MCUboot image parser
 mod image;
 
+pub(crate) use image::IMAGE_MAGIC;
 pub use image::{ImageInfo, ImageParseError, ImageVersion, get_image_info};
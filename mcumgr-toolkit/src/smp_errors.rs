@@ -23,21 +23,50 @@ pub enum DeviceError {
     },
 }
 
-fn v2_err_to_string(group: u16, rc: i32) -> Option<String> {
-    match MCUmgrGroup::from_repr(group)? {
-        MCUmgrGroup::MGMT_GROUP_ID_ENUM => EnumMgmtErrCode::from_repr(rc).map(|x| x.to_string()),
-        MCUmgrGroup::MGMT_GROUP_ID_FS => FsMgmtErrCode::from_repr(rc).map(|x| x.to_string()),
-        MCUmgrGroup::MGMT_GROUP_ID_IMAGE => ImgMgmtErrCode::from_repr(rc).map(|x| x.to_string()),
-        MCUmgrGroup::MGMT_GROUP_ID_OS => OsMgmtErrCode::from_repr(rc).map(|x| x.to_string()),
-        MCUmgrGroup::MGMT_GROUP_ID_SETTINGS => {
-            SettingsMgmtRetCode::from_repr(rc).map(|x| x.to_string())
+impl DeviceError {
+    /// Decodes the group specific error code of an SMP v2 error.
+    ///
+    /// `None` for SMP v1 errors, and for unknown groups or codes.
+    pub fn kind(&self) -> Option<GroupError> {
+        match self {
+            DeviceError::V1 { .. } => None,
+            DeviceError::V2 { group, rc } => GroupError::from_raw(*group, *rc),
         }
-        MCUmgrGroup::MGMT_GROUP_ID_SHELL => ShellMgmtErrCode::from_repr(rc).map(|x| x.to_string()),
-        MCUmgrGroup::MGMT_GROUP_ID_STAT => StatMgmtErrCode::from_repr(rc).map(|x| x.to_string()),
-        MCUmgrGroup::ZEPHYR_MGMT_GRP_BASIC => {
-            ZephyrBasicGroupErrCode::from_repr(rc).map(|x| x.to_string())
+    }
+
+    /// Checks if the device rejected an image because the running image is newer
+    pub fn is_image_downgrade_rejected(&self) -> bool {
+        self.kind()
+            == Some(GroupError::Image(
+                ImgMgmtErrCode::IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER,
+            ))
+    }
+
+    /// Checks if the device refused an image operation because another image is pending
+    pub fn is_image_already_pending(&self) -> bool {
+        self.kind()
+            == Some(GroupError::Image(
+                ImgMgmtErrCode::IMG_MGMT_ERR_IMAGE_ALREADY_PENDING,
+            ))
+    }
+
+    /// Checks if a file or entry does not exist on the device
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            DeviceError::V1 { rc, .. } => *rc == MCUmgrErr::MGMT_ERR_ENOENT as i32,
+            DeviceError::V2 { .. } => matches!(
+                self.kind(),
+                Some(GroupError::Fs(FsMgmtErrCode::FS_MGMT_ERR_FILE_NOT_FOUND))
+                    | Some(GroupError::Settings(
+                        SettingsMgmtRetCode::SETTINGS_MGMT_ERR_KEY_NOT_FOUND
+                    ))
+            ),
         }
-        _ => None,
+    }
+
+    /// Checks if the RTC of the device has not been set yet
+    pub fn is_rtc_not_set(&self) -> bool {
+        self.kind() == Some(GroupError::Os(OsMgmtErrCode::OS_MGMT_ERR_RTC_NOT_SET))
     }
 }
 
@@ -51,14 +80,97 @@ impl std::fmt::Display for DeviceError {
                     write!(f, "{}", MCUmgrErr::err_to_string(*rc))
                 }
             }
-            DeviceError::V2 { group, rc } => match v2_err_to_string(*group, *rc) {
-                Some(msg) => f.write_str(&msg),
+            DeviceError::V2 { group, rc } => match self.kind() {
+                Some(kind) => write!(f, "{kind}"),
                 None => write!(f, "group={group},rc={rc}"),
             },
         }
     }
 }
 
+impl std::error::Error for DeviceError {}
+
+impl miette::Diagnostic for DeviceError {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        let help = match self {
+            DeviceError::V1 { rc, .. } => MCUmgrErr::from_repr(*rc)?.help(),
+            DeviceError::V2 { .. } => self.kind()?.help(),
+        };
+        help.map(|help| Box::new(help) as Box<dyn std::fmt::Display>)
+    }
+}
+
+/// A decoded SMP v2 error, see [`DeviceError::kind`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GroupError {
+    /// Error of the default/OS management group
+    Os(OsMgmtErrCode),
+    /// Error of the application/software image management group
+    Image(ImgMgmtErrCode),
+    /// Error of the statistics management group
+    Stat(StatMgmtErrCode),
+    /// Error of the settings management group
+    Settings(SettingsMgmtRetCode),
+    /// Error of the file management group
+    Fs(FsMgmtErrCode),
+    /// Error of the shell management group
+    Shell(ShellMgmtErrCode),
+    /// Error of the enumeration management group
+    Enum(EnumMgmtErrCode),
+    /// Error of the Zephyr basic management group
+    ZephyrBasic(ZephyrBasicGroupErrCode),
+}
+
+impl GroupError {
+    /// Decodes a raw group id and group based error code
+    pub fn from_raw(group: u16, rc: i32) -> Option<Self> {
+        Some(match MCUmgrGroup::from_repr(group)? {
+            MCUmgrGroup::MGMT_GROUP_ID_OS => Self::Os(OsMgmtErrCode::from_repr(rc)?),
+            MCUmgrGroup::MGMT_GROUP_ID_IMAGE => Self::Image(ImgMgmtErrCode::from_repr(rc)?),
+            MCUmgrGroup::MGMT_GROUP_ID_STAT => Self::Stat(StatMgmtErrCode::from_repr(rc)?),
+            MCUmgrGroup::MGMT_GROUP_ID_SETTINGS => {
+                Self::Settings(SettingsMgmtRetCode::from_repr(rc)?)
+            }
+            MCUmgrGroup::MGMT_GROUP_ID_FS => Self::Fs(FsMgmtErrCode::from_repr(rc)?),
+            MCUmgrGroup::MGMT_GROUP_ID_SHELL => Self::Shell(ShellMgmtErrCode::from_repr(rc)?),
+            MCUmgrGroup::MGMT_GROUP_ID_ENUM => Self::Enum(EnumMgmtErrCode::from_repr(rc)?),
+            MCUmgrGroup::ZEPHYR_MGMT_GRP_BASIC => {
+                Self::ZephyrBasic(ZephyrBasicGroupErrCode::from_repr(rc)?)
+            }
+            _ => return None,
+        })
+    }
+
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            Self::Os(code) => code.help(),
+            Self::Image(code) => code.help(),
+            Self::Stat(code) => code.help(),
+            Self::Settings(code) => code.help(),
+            Self::Fs(code) => code.help(),
+            Self::Shell(code) => code.help(),
+            Self::Enum(code) => code.help(),
+            Self::ZephyrBasic(code) => code.help(),
+        }
+    }
+}
+
+impl std::fmt::Display for GroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Os(code) => write!(f, "{code}"),
+            Self::Image(code) => write!(f, "{code}"),
+            Self::Stat(code) => write!(f, "{code}"),
+            Self::Settings(code) => write!(f, "{code}"),
+            Self::Fs(code) => write!(f, "{code}"),
+            Self::Shell(code) => write!(f, "{code}"),
+            Self::Enum(code) => write!(f, "{code}"),
+            Self::ZephyrBasic(code) => write!(f, "{code}"),
+        }
+    }
+}
+
 /// See [`enum mcumgr_err_t`](https://docs.zephyrproject.org/latest/doxygen/html/mgmt__defines_8h.html).
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -110,6 +222,35 @@ pub enum MCUmgrErr {
     MGMT_ERR_EPERUSER = 256,
 }
 impl MCUmgrErr {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::MGMT_ERR_ENOMEM => {
+                "The device ran out of memory; use a smaller SMP frame size or increase the heap size"
+            }
+            Self::MGMT_ERR_EINVAL => "Check the arguments of the command",
+            Self::MGMT_ERR_ETIMEOUT => "Retry the command",
+            Self::MGMT_ERR_ENOENT => "Check that the file or entry exists on the device",
+            Self::MGMT_ERR_EBADSTATE => {
+                "Wait for the current operation to finish, or reset the device"
+            }
+            Self::MGMT_ERR_EMSGSIZE => {
+                "The response does not fit into the SMP buffer; increase `CONFIG_MCUMGR_TRANSPORT_NETBUF_SIZE`"
+            }
+            Self::MGMT_ERR_ENOTSUP => {
+                "Enable the corresponding `CONFIG_MCUMGR_GRP_*` option in the device configuration"
+            }
+            Self::MGMT_ERR_ECORRUPT => "The data got corrupted during the transfer; retry it",
+            Self::MGMT_ERR_EBUSY => "The device is processing another command; retry later",
+            Self::MGMT_ERR_EACCESSDENIED => {
+                "Access was denied by the device; check the MCUmgr callbacks of the application"
+            }
+            Self::MGMT_ERR_UNSUPPORTED_TOO_OLD => "Use a newer SMP protocol version",
+            Self::MGMT_ERR_UNSUPPORTED_TOO_NEW => "Use an older SMP protocol version",
+            Self::MGMT_ERR_EOK | Self::MGMT_ERR_EUNKNOWN | Self::MGMT_ERR_EPERUSER => return None,
+        })
+    }
+
     /// Converts a raw error code to a string
     pub fn err_to_string(err: i32) -> String {
         const PERUSER: MCUmgrErr = MCUmgrErr::MGMT_ERR_EPERUSER;
@@ -158,6 +299,28 @@ pub enum SettingsMgmtRetCode {
     SETTINGS_MGMT_ERR_SAVE_NOT_SUPPORTED,
 }
 
+impl SettingsMgmtRetCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::SETTINGS_MGMT_ERR_KEY_TOO_LONG => {
+                "Use a shorter key, or increase `CONFIG_MCUMGR_GRP_SETTINGS_NAME_LEN`"
+            }
+            Self::SETTINGS_MGMT_ERR_KEY_NOT_FOUND => "Check the name of the key",
+            Self::SETTINGS_MGMT_ERR_ROOT_KEY_NOT_FOUND => {
+                "Check the root of the key, the part before the first `/`"
+            }
+            Self::SETTINGS_MGMT_ERR_READ_NOT_SUPPORTED
+            | Self::SETTINGS_MGMT_ERR_WRITE_NOT_SUPPORTED
+            | Self::SETTINGS_MGMT_ERR_DELETE_NOT_SUPPORTED
+            | Self::SETTINGS_MGMT_ERR_SAVE_NOT_SUPPORTED => {
+                "The settings handler of this key does not implement this operation"
+            }
+            Self::SETTINGS_MGMT_ERR_OK | Self::SETTINGS_MGMT_ERR_UNKNOWN => return None,
+        })
+    }
+}
+
 /// See `enum fs_mgmt_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -221,6 +384,41 @@ pub enum FsMgmtErrCode {
     FS_MGMT_ERR_FILE_EMPTY,
 }
 
+impl FsMgmtErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::FS_MGMT_ERR_FILE_INVALID_NAME => {
+                "Use an absolute path that starts with the mount point, like `/lfs/file.txt`"
+            }
+            Self::FS_MGMT_ERR_FILE_NOT_FOUND => "Check the path, including the mount point",
+            Self::FS_MGMT_ERR_FILE_IS_DIRECTORY => "Specify a file instead of a directory",
+            Self::FS_MGMT_ERR_FILE_OPEN_FAILED
+            | Self::FS_MGMT_ERR_FILE_SEEK_FAILED
+            | Self::FS_MGMT_ERR_FILE_READ_FAILED
+            | Self::FS_MGMT_ERR_FILE_TRUNCATE_FAILED
+            | Self::FS_MGMT_ERR_FILE_DELETE_FAILED
+            | Self::FS_MGMT_ERR_FILE_WRITE_FAILED => {
+                "Check that the file system is mounted, not full and not corrupted"
+            }
+            Self::FS_MGMT_ERR_FILE_OFFSET_NOT_VALID
+            | Self::FS_MGMT_ERR_FILE_OFFSET_LARGER_THAN_FILE => {
+                "The file changed on the device during the transfer; restart the transfer"
+            }
+            Self::FS_MGMT_ERR_CHECKSUM_HASH_NOT_FOUND => {
+                "Use a checksum type that is supported by the device"
+            }
+            Self::FS_MGMT_ERR_MOUNT_POINT_NOT_FOUND => {
+                "Check the mount point, and that the file system is mounted"
+            }
+            Self::FS_MGMT_ERR_READ_ONLY_FILESYSTEM => "Use a writable file system",
+            Self::FS_MGMT_ERR_OK | Self::FS_MGMT_ERR_UNKNOWN | Self::FS_MGMT_ERR_FILE_EMPTY => {
+                return None;
+            }
+        })
+    }
+}
+
 /// See `enum img_mgmt_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -332,6 +530,72 @@ pub enum ImgMgmtErrCode {
     IMG_MGMT_ERR_ACTIVE_SLOT_NOT_KNOWN,
 }
 
+impl ImgMgmtErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::IMG_MGMT_ERR_FLASH_CONFIG_QUERY_FAIL
+            | Self::IMG_MGMT_ERR_FLASH_OPEN_FAILED
+            | Self::IMG_MGMT_ERR_FLASH_READ_FAILED
+            | Self::IMG_MGMT_ERR_FLASH_WRITE_FAILED
+            | Self::IMG_MGMT_ERR_FLASH_ERASE_FAILED
+            | Self::IMG_MGMT_ERR_FLASH_AREA_DEVICE_NULL => {
+                "Check the flash driver and the flash partitions of the device"
+            }
+            Self::IMG_MGMT_ERR_NO_IMAGE => "Upload an image to the slot first",
+            Self::IMG_MGMT_ERR_NO_TLVS
+            | Self::IMG_MGMT_ERR_INVALID_TLV
+            | Self::IMG_MGMT_ERR_TLV_MULTIPLE_HASHES_FOUND
+            | Self::IMG_MGMT_ERR_TLV_INVALID_SIZE
+            | Self::IMG_MGMT_ERR_HASH_NOT_FOUND
+            | Self::IMG_MGMT_ERR_INVALID_IMAGE_HEADER
+            | Self::IMG_MGMT_ERR_INVALID_IMAGE_HEADER_MAGIC => {
+                "Upload a signed MCUboot image, like `zephyr.signed.bin`"
+            }
+            Self::IMG_MGMT_ERR_NO_FREE_SLOT => {
+                "Confirm the running image or erase the secondary slot first"
+            }
+            Self::IMG_MGMT_ERR_INVALID_SLOT => "Check the slot number",
+            Self::IMG_MGMT_ERR_NO_FREE_MEMORY => {
+                "The device ran out of heap memory; increase `CONFIG_HEAP_MEM_POOL_SIZE`"
+            }
+            Self::IMG_MGMT_ERR_FLASH_CONTEXT_ALREADY_SET
+            | Self::IMG_MGMT_ERR_FLASH_CONTEXT_NOT_SET
+            | Self::IMG_MGMT_ERR_INVALID_PAGE_OFFSET
+            | Self::IMG_MGMT_ERR_INVALID_OFFSET
+            | Self::IMG_MGMT_ERR_INVALID_LENGTH
+            | Self::IMG_MGMT_ERR_INVALID_IMAGE_DATA_OVERRUN => "Restart the upload",
+            Self::IMG_MGMT_ERR_INVALID_HASH => "Check the image hash",
+            Self::IMG_MGMT_ERR_INVALID_FLASH_ADDRESS => {
+                "The image was linked for a different slot; build it for this slot"
+            }
+            Self::IMG_MGMT_ERR_VERSION_GET_FAILED => {
+                "Check that the running image has a valid MCUboot header"
+            }
+            Self::IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER => {
+                "The device rejects downgrades; upload an image with a higher version"
+            }
+            Self::IMG_MGMT_ERR_IMAGE_ALREADY_PENDING => {
+                "Reset the device to boot the pending image, or erase the secondary slot"
+            }
+            Self::IMG_MGMT_ERR_INVALID_IMAGE_VECTOR_TABLE => {
+                "The image was built for a different target"
+            }
+            Self::IMG_MGMT_ERR_INVALID_IMAGE_TOO_LARGE => {
+                "The image does not fit into the slot; reduce its size or enlarge the partitions"
+            }
+            Self::IMG_MGMT_ERR_IMAGE_CONFIRMATION_DENIED => {
+                "The device does not allow confirming this image; boot it first"
+            }
+            Self::IMG_MGMT_ERR_IMAGE_SETTING_TEST_TO_ACTIVE_DENIED => {
+                "The image is already running; confirm it instead"
+            }
+            Self::IMG_MGMT_ERR_ACTIVE_SLOT_NOT_KNOWN => "Reset the device and retry",
+            Self::IMG_MGMT_ERR_OK | Self::IMG_MGMT_ERR_UNKNOWN => return None,
+        })
+    }
+}
+
 /// See `enum os_mgmt_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -359,6 +623,23 @@ pub enum OsMgmtErrCode {
     OS_MGMT_ERR_QUERY_RESPONSE_VALUE_NOT_VALID,
 }
 
+impl OsMgmtErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::OS_MGMT_ERR_INVALID_FORMAT => {
+                "Check the format specifier; the build time requires `CONFIG_MCUMGR_GRP_OS_INFO_BUILD_DATE_TIME`"
+            }
+            Self::OS_MGMT_ERR_QUERY_YIELDS_NO_ANSWER => "The device does not support this query",
+            Self::OS_MGMT_ERR_RTC_NOT_SET => "Set the device time first",
+            Self::OS_MGMT_ERR_RTC_COMMAND_FAILED => "Check the RTC driver of the device",
+            Self::OS_MGMT_ERR_OK
+            | Self::OS_MGMT_ERR_UNKNOWN
+            | Self::OS_MGMT_ERR_QUERY_RESPONSE_VALUE_NOT_VALID => return None,
+        })
+    }
+}
+
 /// See `enum shell_mgmt_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -377,6 +658,17 @@ pub enum ShellMgmtErrCode {
     SHELL_MGMT_ERR_EMPTY_COMMAND,
 }
 
+impl ShellMgmtErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::SHELL_MGMT_ERR_COMMAND_TOO_LONG => "Shorten the command",
+            Self::SHELL_MGMT_ERR_EMPTY_COMMAND => "Specify a command to execute",
+            Self::SHELL_MGMT_ERR_OK | Self::SHELL_MGMT_ERR_UNKNOWN => return None,
+        })
+    }
+}
+
 /// See `enum stat_mgmt_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -401,6 +693,20 @@ pub enum StatMgmtErrCode {
     STAT_MGMT_ERR_WALK_ABORTED,
 }
 
+impl StatMgmtErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::STAT_MGMT_ERR_INVALID_GROUP => "Check the name of the statistics group",
+            Self::STAT_MGMT_ERR_INVALID_STAT_NAME => "Check the name of the statistic",
+            Self::STAT_MGMT_ERR_OK
+            | Self::STAT_MGMT_ERR_UNKNOWN
+            | Self::STAT_MGMT_ERR_INVALID_STAT_SIZE
+            | Self::STAT_MGMT_ERR_WALK_ABORTED => return None,
+        })
+    }
+}
+
 /// See `enum enum_mgmt_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -422,6 +728,20 @@ pub enum EnumMgmtErrCode {
     ENUM_MGMT_ERR_INDEX_TOO_LARGE,
 }
 
+impl EnumMgmtErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::ENUM_MGMT_ERR_TOO_MANY_GROUP_ENTRIES => "Query fewer groups at once",
+            Self::ENUM_MGMT_ERR_INSUFFICIENT_HEAP_FOR_ENTRIES => {
+                "The device ran out of heap memory; increase `CONFIG_HEAP_MEM_POOL_SIZE`"
+            }
+            Self::ENUM_MGMT_ERR_INDEX_TOO_LARGE => "Use a smaller group index",
+            Self::ENUM_MGMT_ERR_OK | Self::ENUM_MGMT_ERR_UNKNOWN => return None,
+        })
+    }
+}
+
 /// See `enum zephyr_basic_group_err_code_t`.
 #[derive(FromRepr, Display, Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -442,3 +762,17 @@ pub enum ZephyrBasicGroupErrCode {
     /** Erasing the flash area has failed. */
     ZEPHYRBASIC_MGMT_ERR_FLASH_ERASE_FAILED,
 }
+
+impl ZephyrBasicGroupErrCode {
+    /// A hint on how to fix the error, if available
+    pub fn help(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::ZEPHYRBASIC_MGMT_ERR_FLASH_OPEN_FAILED
+            | Self::ZEPHYRBASIC_MGMT_ERR_FLASH_CONFIG_QUERY_FAIL => {
+                "Check the `storage_partition` of the devicetree"
+            }
+            Self::ZEPHYRBASIC_MGMT_ERR_FLASH_ERASE_FAILED => "Check the flash driver of the device",
+            Self::ZEPHYRBASIC_MGMT_ERR_OK | Self::ZEPHYRBASIC_MGMT_ERR_UNKNOWN => return None,
+        })
+    }
+}
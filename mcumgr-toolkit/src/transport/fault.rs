@@ -0,0 +1,401 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use base64::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use super::{
+    ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, Transport, TransportStats,
+    serial::ConfigurableTimeout,
+};
+
+/// A link fault that can be injected by [`FaultInjectingTransport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The frame gets lost
+    Drop,
+    /// The frame arrives late
+    Delay(Duration),
+    /// The frame arrives twice
+    Duplicate,
+    /// The frame gets cut off in the middle.
+    ///
+    /// Sent frames keep their header and lose the second half of their payload.
+    Truncate,
+    /// A single bit of the frame gets flipped
+    Corrupt,
+}
+
+enum FaultPlan {
+    Random {
+        probability: f64,
+        faults: Vec<Fault>,
+    },
+    Scripted(HashMap<usize, Fault>),
+}
+
+/// A transport wrapper that injects link faults, for robustness testing.
+///
+/// Frames are numbered in the order they pass this transport, starting at 0,
+/// counting both sent requests and received responses.
+///
+/// ```no_run
+/// # use mcumgr_toolkit::{MCUmgrClient, transport::{fault::{Fault, FaultInjectingTransport}, serial::SerialTransport}};
+/// # fn main() {
+/// let serial = serialport::new("COM42", 115200)
+///     .timeout(std::time::Duration::from_millis(1000))
+///     .open()
+///     .unwrap();
+///
+/// // Lose the response to the first request
+/// let transport =
+///     FaultInjectingTransport::scripted(SerialTransport::new(serial), [(1, Fault::Drop)]);
+///
+/// let client = MCUmgrClient::new_from_transport(transport);
+/// assert!(client.os_echo("Hello").is_err());
+/// assert!(client.os_echo("Hello").is_ok());
+/// # }
+/// ```
+pub struct FaultInjectingTransport<T> {
+    transport: T,
+    plan: FaultPlan,
+    rng: StdRng,
+    frame_index: usize,
+    injected_faults: usize,
+    duplicate: Option<Vec<u8>>,
+}
+
+impl<T: Transport> FaultInjectingTransport<T> {
+    /// Injects a random fault into every frame with the given probability.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport to inject the faults into.
+    /// * `seed` - The random seed; the same seed produces the same faults.
+    /// * `probability` - The probability of a frame being faulty, between `0.0` and `1.0`.
+    /// * `faults` - The faults to choose from.
+    pub fn random(
+        transport: T,
+        seed: u64,
+        probability: f64,
+        faults: impl Into<Vec<Fault>>,
+    ) -> Self {
+        Self::new(
+            transport,
+            seed,
+            FaultPlan::Random {
+                probability: probability.clamp(0.0, 1.0),
+                faults: faults.into(),
+            },
+        )
+    }
+
+    /// Injects faults into the frames with the given numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport to inject the faults into.
+    /// * `schedule` - The frame numbers and the fault to inject into them.
+    pub fn scripted(transport: T, schedule: impl IntoIterator<Item = (usize, Fault)>) -> Self {
+        Self::new(
+            transport,
+            0,
+            FaultPlan::Scripted(schedule.into_iter().collect()),
+        )
+    }
+
+    fn new(transport: T, seed: u64, plan: FaultPlan) -> Self {
+        Self {
+            transport,
+            plan,
+            rng: StdRng::seed_from_u64(seed),
+            frame_index: 0,
+            injected_faults: 0,
+            duplicate: None,
+        }
+    }
+
+    /// The number of faults that were injected so far.
+    pub fn injected_faults(&self) -> usize {
+        self.injected_faults
+    }
+
+    /// Returns the wrapped transport.
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    fn next_fault(&mut self) -> Option<Fault> {
+        let frame_index = self.frame_index;
+        self.frame_index += 1;
+
+        let fault = match &self.plan {
+            FaultPlan::Random {
+                probability,
+                faults,
+            } => {
+                if self.rng.random_bool(*probability) {
+                    faults.choose(&mut self.rng).copied()
+                } else {
+                    None
+                }
+            }
+            FaultPlan::Scripted(schedule) => schedule.get(&frame_index).copied(),
+        };
+
+        if let Some(fault) = fault {
+            log::debug!("Injecting fault into frame {frame_index}: {fault:?}");
+            self.injected_faults += 1;
+        }
+
+        fault
+    }
+
+    fn corrupt(&mut self, frame: &mut [u8]) {
+        if !frame.is_empty() {
+            let bit = self.rng.random_range(0..frame.len() * 8);
+            frame[bit / 8] ^= 1 << (bit % 8);
+        }
+    }
+}
+
+impl<T: Transport> Transport for FaultInjectingTransport<T> {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        match self.next_fault() {
+            None => self.transport.send_raw_frame(header, data),
+            Some(Fault::Drop) => Ok(()),
+            Some(Fault::Delay(duration)) => {
+                std::thread::sleep(duration);
+                self.transport.send_raw_frame(header, data)
+            }
+            Some(Fault::Duplicate) => {
+                self.transport.send_raw_frame(header, data)?;
+                self.transport.send_raw_frame(header, data)
+            }
+            Some(Fault::Truncate) => self
+                .transport
+                .send_raw_frame(header, &data[..data.len() / 2]),
+            Some(Fault::Corrupt) => {
+                let mut frame = [&header[..], data].concat();
+                self.corrupt(&mut frame);
+                let (header, data) = frame.split_first_chunk::<SMP_HEADER_SIZE>().unwrap();
+                self.transport.send_raw_frame(*header, data)
+            }
+        }
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        if let Some(duplicate) = self.duplicate.take() {
+            buffer[..duplicate.len()].copy_from_slice(&duplicate);
+            return Ok(&buffer[..duplicate.len()]);
+        }
+
+        let mut len = self.transport.recv_raw_frame(buffer)?.len();
+
+        match self.next_fault() {
+            None => {}
+            Some(Fault::Drop) => return self.transport.recv_raw_frame(buffer),
+            Some(Fault::Delay(duration)) => std::thread::sleep(duration),
+            Some(Fault::Duplicate) => self.duplicate = Some(buffer[..len].to_vec()),
+            Some(Fault::Truncate) => len /= 2,
+            Some(Fault::Corrupt) => self.corrupt(&mut buffer[..len]),
+        }
+
+        Ok(&buffer[..len])
+    }
+
+    fn receive_console(&mut self, duration: Duration) -> Result<(), ReceiveError> {
+        self.transport.receive_console(duration)
+    }
+
+    fn stats(&self) -> Option<TransportStats> {
+        self.transport.stats()
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.transport.set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.transport.get_timeout()
+    }
+}
+
+enum PacketPlan {
+    Random(f64),
+    Scripted(HashSet<usize>),
+}
+
+/// A serial port wrapper that flips CRC bits of received SMP packets.
+///
+/// Packets are numbered in the order they are received, starting at 0.
+/// Other data is forwarded unmodified; as the data is processed line by line,
+/// console output is only forwarded once its line is complete.
+///
+/// Use it with a [`SerialTransport`](super::serial::SerialTransport)
+/// to test the CRC error handling.
+pub struct CrcFaultSerial<S> {
+    serial: S,
+    plan: PacketPlan,
+    rng: StdRng,
+    packet_index: usize,
+    injected_faults: usize,
+    /// The current incomplete line
+    line: Vec<u8>,
+    /// Processed data that is ready to be read
+    output: VecDeque<u8>,
+    /// Number of bytes missing for the current SMP packet to be complete
+    packet_remaining: Option<usize>,
+}
+
+impl<S> CrcFaultSerial<S> {
+    /// Corrupts the CRC of every packet with the given probability.
+    pub fn random(serial: S, seed: u64, probability: f64) -> Self {
+        Self::new(
+            serial,
+            seed,
+            PacketPlan::Random(probability.clamp(0.0, 1.0)),
+        )
+    }
+
+    /// Corrupts the CRC of the packets with the given numbers.
+    pub fn scripted(serial: S, packets: impl IntoIterator<Item = usize>) -> Self {
+        Self::new(
+            serial,
+            0,
+            PacketPlan::Scripted(packets.into_iter().collect()),
+        )
+    }
+
+    fn new(serial: S, seed: u64, plan: PacketPlan) -> Self {
+        Self {
+            serial,
+            plan,
+            rng: StdRng::seed_from_u64(seed),
+            packet_index: 0,
+            injected_faults: 0,
+            line: vec![],
+            output: VecDeque::new(),
+            packet_remaining: None,
+        }
+    }
+
+    /// The number of packets that were corrupted so far.
+    pub fn injected_faults(&self) -> usize {
+        self.injected_faults
+    }
+
+    /// Returns the wrapped serial port.
+    pub fn into_inner(self) -> S {
+        self.serial
+    }
+
+    fn should_corrupt(&mut self) -> bool {
+        let packet_index = self.packet_index;
+        self.packet_index += 1;
+
+        match &self.plan {
+            PacketPlan::Random(probability) => self.rng.random_bool(*probability),
+            PacketPlan::Scripted(packets) => packets.contains(&packet_index),
+        }
+    }
+
+    fn process_line(&mut self) {
+        let mut line = std::mem::take(&mut self.line);
+
+        let is_first = line.starts_with(&[6, 9]);
+        let is_continuation = line.starts_with(&[4, 20]) && self.packet_remaining.is_some();
+
+        if is_first || is_continuation {
+            let content_end = line.len() - 1 - usize::from(line.ends_with(b"\r\n"));
+            if let Ok(mut decoded) = BASE64_STANDARD.decode(&line[2..content_end]) {
+                if is_first {
+                    self.packet_remaining = decoded
+                        .first_chunk::<2>()
+                        .map(|len| usize::from(u16::from_be_bytes(*len)) + 2);
+                }
+
+                if let Some(remaining) = self.packet_remaining {
+                    let remaining = remaining.saturating_sub(decoded.len());
+                    self.packet_remaining = Some(remaining);
+
+                    // The last line of a packet ends with its CRC
+                    if remaining == 0 {
+                        self.packet_remaining = None;
+                        if self.should_corrupt() {
+                            if let Some(crc_byte) = decoded.last_mut() {
+                                log::debug!("Corrupting CRC of packet {}", self.packet_index - 1);
+                                *crc_byte ^= 1;
+                                self.injected_faults += 1;
+
+                                let encoded = BASE64_STANDARD.encode(&decoded);
+                                line.splice(2..content_end, encoded.into_bytes());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.output.extend(line);
+    }
+}
+
+impl<S: Read> Read for CrcFaultSerial<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.output.is_empty() {
+            let mut chunk = [0; 256];
+            let num_read = self.serial.read(&mut chunk)?;
+
+            if num_read == 0 {
+                self.output.extend(self.line.drain(..));
+                break;
+            }
+
+            for &byte in &chunk[..num_read] {
+                self.line.push(byte);
+                if byte == b'\n' {
+                    self.process_line();
+                }
+            }
+        }
+
+        self.output.read(buf)
+    }
+}
+
+impl<S: Write> Write for CrcFaultSerial<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.serial.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.serial.flush()
+    }
+}
+
+impl<S: ConfigurableTimeout> ConfigurableTimeout for CrcFaultSerial<S> {
+    fn set_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.serial.set_timeout(duration)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.serial.get_timeout()
+    }
+}
@@ -3,9 +3,31 @@ use std::{io, time::Duration};
 use miette::Diagnostic;
 use thiserror::Error;
 
+use crate::connection::{FrameHeader, FrameOp};
+
 /// Serial port based transport
 pub mod serial;
 
+/// Recording and replaying of SMP sessions
+pub mod record;
+
+/// Fault injection for robustness testing
+pub mod fault;
+
+/// SMP over UDP
+pub mod udp;
+
+/// Serial ports behind TCP serial bridges
+pub mod tcp;
+
+/// Pseudo-terminal support, for example for Zephyr's `native_sim`
+#[cfg(unix)]
+pub mod pty;
+
+/// Unix domain socket based transport
+#[cfg(unix)]
+pub mod unix;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 struct SmpHeader {
     ver: u8,
@@ -45,8 +67,76 @@ impl SmpHeader {
     }
 }
 
-const SMP_HEADER_SIZE: usize = 8;
-const SMP_TRANSFER_BUFFER_SIZE: usize = u16::MAX as usize;
+/// The version of the SMP protocol used in a frame.
+///
+/// See [SMP Protocol Specification](https://docs.zephyrproject.org/latest/services/device_mgmt/smp_protocol.html#frame-header).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SmpVersion {
+    /// The original protocol version
+    V1,
+    /// Version 2; adds group based error codes
+    V2,
+}
+
+impl SmpVersion {
+    /// The value of the `Version` field of the SMP header
+    pub(crate) fn header_value(self) -> u8 {
+        match self {
+            Self::V1 => 0b00,
+            Self::V2 => 0b01,
+        }
+    }
+
+    fn from_header_value(value: u8) -> Option<Self> {
+        match value {
+            0b00 => Some(Self::V1),
+            0b01 => Some(Self::V2),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SmpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::V1 => f.write_str("v1"),
+            Self::V2 => f.write_str("v2"),
+        }
+    }
+}
+
+pub(crate) const SMP_HEADER_SIZE: usize = 8;
+pub(crate) const SMP_TRANSFER_BUFFER_SIZE: usize = u16::MAX as usize;
+
+/// Extracts the sequence number from a raw SMP frame.
+pub(crate) fn frame_sequence_num(frame: &[u8]) -> Option<u8> {
+    frame
+        .first_chunk::<SMP_HEADER_SIZE>()
+        .map(|header| SmpHeader::from_bytes(*header).sequence_num)
+}
+
+/// Decodes the header of a raw SMP frame.
+///
+/// `None` if the frame is too short or its version or operation are unknown.
+pub(crate) fn frame_header(frame: &[u8]) -> Option<FrameHeader> {
+    let header = SmpHeader::from_bytes(*frame.first_chunk::<SMP_HEADER_SIZE>()?);
+
+    let op = match header.op {
+        smp_op::READ => FrameOp::Read,
+        smp_op::READ_RSP => FrameOp::ReadResponse,
+        smp_op::WRITE => FrameOp::Write,
+        smp_op::WRITE_RSP => FrameOp::WriteResponse,
+        _ => return None,
+    };
+
+    Some(FrameHeader {
+        version: SmpVersion::from_header_value(header.ver)?,
+        op,
+        group_id: header.group_id,
+        command_id: header.command_id,
+        sequence_num: header.sequence_num,
+    })
+}
 
 mod smp_op {
     pub(super) const READ: u8 = 0;
@@ -79,6 +169,10 @@ pub enum ReceiveError {
     #[error("Received unexpected response")]
     #[diagnostic(code(mcumgr_toolkit::transport::recv::unexpected))]
     UnexpectedResponse,
+    /// The response has an unknown SMP version or is newer than the request
+    #[error("Received response with unexpected SMP version field: {0}")]
+    #[diagnostic(code(mcumgr_toolkit::transport::recv::version))]
+    UnexpectedVersion(u8),
     /// The response we received is bigger than the configured MTU
     #[error("Received frame that exceeds configured MTU")]
     #[diagnostic(code(mcumgr_toolkit::transport::recv::too_big))]
@@ -89,6 +183,20 @@ pub enum ReceiveError {
     Base64DecodeError(#[from] base64::DecodeSliceError),
 }
 
+/// Statistics about the encoding of SMP frames on the link.
+///
+/// Only provided by transports that encode SMP frames before sending them,
+/// see [`Transport::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Number of bytes written to the link, including the encoding overhead
+    pub encoded_bytes_sent: u64,
+    /// Number of bytes of SMP frames read from the link, including the encoding overhead
+    pub encoded_bytes_received: u64,
+    /// Number of received frames that were dropped because of a wrong checksum
+    pub crc_errors: u64,
+}
+
 /// Defines the API of the SMP transport layer
 pub trait Transport {
     /// Send a raw SMP frame over the bus.
@@ -114,6 +222,7 @@ pub trait Transport {
     ///
     /// # Arguments
     ///
+    /// * `version` - The SMP protocol version of the frame.
     /// * `write_operation` - If the frame contains a write or read operation.
     /// * `sequence_num` - A sequence number. Must be different every time this function is called.
     /// * `group_id` - The group ID of the command.
@@ -124,6 +233,7 @@ pub trait Transport {
     ///
     fn send_frame(
         &mut self,
+        version: SmpVersion,
         write_operation: bool,
         sequence_num: u8,
         group_id: u16,
@@ -131,7 +241,7 @@ pub trait Transport {
         data: &[u8],
     ) -> Result<(), SendError> {
         let header = SmpHeader {
-            ver: 0b01,
+            ver: version.header_value(),
             op: if write_operation {
                 smp_op::WRITE
             } else {
@@ -154,6 +264,7 @@ pub trait Transport {
     /// # Arguments
     ///
     /// * `buffer` - A buffer that the data will be read into.
+    /// * `version` - The SMP protocol version of the request. The response may use an older version.
     /// * `write_operation` - If this is the response to a write or read operation.
     /// * `sequence_num` - A sequence number. Must match the sequence_num of the accompanying [`Transport::send_frame`] call.
     /// * `group_id` - The group ID of the command.
@@ -161,17 +272,18 @@ pub trait Transport {
     ///
     /// # Return
     ///
-    /// The payload data of the response, most likely CBOR encoded.
+    /// The SMP protocol version of the response and its payload data, most likely CBOR encoded.
     ///
     fn receive_frame<'a>(
         &mut self,
         buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+        version: SmpVersion,
         write_operation: bool,
         sequence_num: u8,
         group_id: u16,
         command_id: u8,
-    ) -> Result<&'a [u8], ReceiveError> {
-        let data_size = loop {
+    ) -> Result<(SmpVersion, &'a [u8]), ReceiveError> {
+        let (response_version, data_size) = loop {
             let frame = self.recv_raw_frame(buffer)?;
 
             let (header_data, data) = frame
@@ -200,10 +312,34 @@ pub trait Transport {
                 return Err(ReceiveError::UnexpectedResponse);
             }
 
-            break data.len();
+            let response_version = SmpVersion::from_header_value(header.ver)
+                .filter(|response_version| *response_version <= version)
+                .ok_or(ReceiveError::UnexpectedVersion(header.ver))?;
+
+            break (response_version, data.len());
         };
 
-        Ok(&buffer[SMP_HEADER_SIZE..SMP_HEADER_SIZE + data_size])
+        Ok((
+            response_version,
+            &buffer[SMP_HEADER_SIZE..SMP_HEADER_SIZE + data_size],
+        ))
+    }
+
+    /// Receives console output of the device for the given duration.
+    ///
+    /// Only relevant for transports that share their link with a console,
+    /// like [`SerialTransport`](serial::SerialTransport) with a console sink.
+    /// Returns immediately for all other transports.
+    fn receive_console(&mut self, duration: Duration) -> Result<(), ReceiveError> {
+        let _ = duration;
+        Ok(())
+    }
+
+    /// Returns statistics about the encoding of the frames on the link.
+    ///
+    /// `None` for transports that send the SMP frames unencoded.
+    fn stats(&self) -> Option<TransportStats> {
+        None
     }
 
     /// Changes the communication timeout.
@@ -214,4 +350,43 @@ pub trait Transport {
         &mut self,
         timeout: Duration,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Returns the current communication timeout.
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        (**self).send_raw_frame(header, data)
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        (**self).recv_raw_frame(buffer)
+    }
+
+    fn receive_console(&mut self, duration: Duration) -> Result<(), ReceiveError> {
+        (**self).receive_console(duration)
+    }
+
+    fn stats(&self) -> Option<TransportStats> {
+        (**self).stats()
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        (**self).set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        (**self).get_timeout()
+    }
 }
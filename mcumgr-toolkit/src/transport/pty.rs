@@ -0,0 +1,94 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::{fd::AsFd, unix::fs::OpenOptionsExt},
+    path::Path,
+    time::Duration,
+};
+
+use nix::{
+    fcntl::OFlag,
+    poll::{PollFd, PollFlags, PollTimeout, poll},
+    sys::termios::{SetArg, cfmakeraw, tcgetattr, tcsetattr},
+};
+
+use super::serial::ConfigurableTimeout;
+
+/// A pseudo-terminal, like the UART of a Zephyr `native_sim` build.
+///
+/// Unlike the `serialport` crate, this does not configure baud rates,
+/// flow control or exclusive access, which pseudo-terminals do not support.
+/// The terminal only gets switched to raw mode, so that the line discipline
+/// does not echo or alter the transferred data.
+///
+/// Use it with a [`SerialTransport`](super::serial::SerialTransport),
+/// or directly with [`MCUmgrClient::new_from_pty`](crate::MCUmgrClient::new_from_pty).
+pub struct PtySerial {
+    file: File,
+    timeout: Duration,
+}
+
+impl PtySerial {
+    /// Opens the pseudo-terminal at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the terminal, for example `/dev/pts/5`.
+    /// * `timeout` - The communication timeout.
+    pub fn open(path: impl AsRef<Path>, timeout: Duration) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(OFlag::O_NOCTTY.bits())
+            .open(path)?;
+
+        // Regular files and pipes have no line discipline to configure
+        if let Ok(mut termios) = tcgetattr(file.as_fd()) {
+            cfmakeraw(&mut termios);
+            tcsetattr(file.as_fd(), SetArg::TCSANOW, &termios)?;
+        }
+
+        Ok(Self { file, timeout })
+    }
+}
+
+impl Read for PtySerial {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let timeout = if self.timeout.is_zero() {
+            PollTimeout::NONE
+        } else {
+            PollTimeout::try_from(self.timeout).unwrap_or(PollTimeout::MAX)
+        };
+
+        let mut fds = [PollFd::new(self.file.as_fd(), PollFlags::POLLIN)];
+        if poll(&mut fds, timeout)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::TimedOut));
+        }
+
+        self.file.read(buf)
+    }
+}
+
+impl Write for PtySerial {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl ConfigurableTimeout for PtySerial {
+    fn set_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.timeout = duration;
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, Write},
+    time::{Duration, Instant},
+};
+
+use super::{
+    ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, Transport, TransportStats,
+};
+
+/// The first line of every session log.
+const LOG_HEADER: &str = "# mcumgr-toolkit SMP session log v1";
+
+const TX: &str = "TX";
+const RX: &str = "RX";
+
+/// Position of the sequence number in the SMP header
+const SEQUENCE_NUM_OFFSET: usize = 6;
+
+/// A transport wrapper that records every raw SMP frame into a session log.
+///
+/// The log is a line based text format; every line contains the time since
+/// the start of the recording in seconds, the direction (`TX` or `RX`) and
+/// the hex encoded frame including its SMP header.
+/// Lines starting with `#` are comments.
+///
+/// Sessions can be replayed with [`ReplayTransport`].
+///
+/// ```no_run
+/// # use mcumgr_toolkit::{MCUmgrClient, transport::{record::RecordingTransport, serial::SerialTransport}};
+/// # fn main() {
+/// let serial = serialport::new("COM42", 115200)
+///     .timeout(std::time::Duration::from_millis(10000))
+///     .open()
+///     .unwrap();
+///
+/// let log = std::fs::File::create("session.smplog").unwrap();
+/// let transport = RecordingTransport::new(SerialTransport::new(serial), log).unwrap();
+///
+/// let client = MCUmgrClient::new_from_transport(transport);
+/// # }
+/// ```
+pub struct RecordingTransport<T, W> {
+    transport: T,
+    log: W,
+    start: Instant,
+}
+
+impl<T: Transport, W: Write> RecordingTransport<T, W> {
+    /// Creates a recording transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport that communicates with the device.
+    /// * `log` - Where the session log gets written to.
+    pub fn new(transport: T, mut log: W) -> io::Result<Self> {
+        writeln!(log, "{LOG_HEADER}")?;
+        log.flush()?;
+
+        Ok(Self {
+            transport,
+            log,
+            start: Instant::now(),
+        })
+    }
+
+    /// Returns the wrapped transport and the session log.
+    pub fn into_inner(self) -> (T, W) {
+        (self.transport, self.log)
+    }
+
+    fn record(&mut self, direction: &str, frame: &[&[u8]]) -> io::Result<()> {
+        let mut line = format!("{:.6} {direction} ", self.start.elapsed().as_secs_f64());
+        for part in frame {
+            line.push_str(&hex::encode(part));
+        }
+        line.push('\n');
+
+        // Flush after every frame, so that the log survives crashes
+        self.log.write_all(line.as_bytes())?;
+        self.log.flush()
+    }
+}
+
+impl<T: Transport, W: Write> Transport for RecordingTransport<T, W> {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        self.transport.send_raw_frame(header, data)?;
+        self.record(TX, &[&header, data])?;
+        Ok(())
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        let frame = self.transport.recv_raw_frame(buffer)?;
+        self.record(RX, &[frame])?;
+        Ok(frame)
+    }
+
+    fn receive_console(&mut self, duration: Duration) -> Result<(), ReceiveError> {
+        self.transport.receive_console(duration)
+    }
+
+    fn stats(&self) -> Option<TransportStats> {
+        self.transport.stats()
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.transport.set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.transport.get_timeout()
+    }
+}
+
+struct RecordedFrame {
+    line: usize,
+    is_request: bool,
+    data: Vec<u8>,
+}
+
+/// A transport that replays a session recorded by [`RecordingTransport`].
+///
+/// Every request must match the next recorded one, otherwise sending fails.
+/// Sequence numbers are ignored and rewritten in the responses,
+/// so a session can be replayed by a different client instance.
+///
+/// Recorded timeouts are replayed as well; if the device did not
+/// respond to a request, receiving fails with a timeout error.
+pub struct ReplayTransport {
+    frames: VecDeque<RecordedFrame>,
+    timeout: Duration,
+    /// The recorded and the actual sequence number of the last request
+    sequence_nums: Option<(u8, u8)>,
+}
+
+fn invalid_log(line: usize, msg: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid session log in line {line}: {msg}"),
+    )
+}
+
+impl ReplayTransport {
+    /// Loads a session log written by [`RecordingTransport`].
+    pub fn new(log: impl BufRead) -> io::Result<Self> {
+        let mut frames = VecDeque::new();
+
+        for (index, line) in log.lines().enumerate() {
+            let line_num = index + 1;
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let (Some(timestamp), Some(direction), Some(data), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid_log(line_num, "expected 3 fields"));
+            };
+
+            timestamp
+                .parse::<f64>()
+                .map_err(|e| invalid_log(line_num, e))?;
+
+            let is_request = match direction {
+                TX => true,
+                RX => false,
+                other => {
+                    return Err(invalid_log(
+                        line_num,
+                        format!("unknown direction '{other}'"),
+                    ));
+                }
+            };
+
+            let data = hex::decode(data).map_err(|e| invalid_log(line_num, e))?;
+            if data.len() < SMP_HEADER_SIZE || data.len() > SMP_TRANSFER_BUFFER_SIZE {
+                return Err(invalid_log(line_num, "invalid frame size"));
+            }
+
+            frames.push_back(RecordedFrame {
+                line: line_num,
+                is_request,
+                data,
+            });
+        }
+
+        Ok(Self {
+            frames,
+            timeout: Duration::ZERO,
+            sequence_nums: None,
+        })
+    }
+
+    /// The number of recorded frames that were not replayed yet.
+    pub fn remaining_frames(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        // Skip responses that were not consumed during the recording
+        while self.frames.front().is_some_and(|frame| !frame.is_request) {
+            self.frames.pop_front();
+        }
+
+        let Some(recorded) = self.frames.front() else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "End of recorded session reached",
+            )
+            .into());
+        };
+
+        let mut request = Vec::with_capacity(header.len() + data.len());
+        request.extend_from_slice(&header);
+        request.extend_from_slice(data);
+
+        let recorded_sequence_num = recorded.data[SEQUENCE_NUM_OFFSET];
+        let mut expected = recorded.data.clone();
+        expected[SEQUENCE_NUM_OFFSET] = header[SEQUENCE_NUM_OFFSET];
+
+        // Mismatching requests do not consume the recorded request,
+        // so that failed optional requests do not break the replay.
+        if request != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Request does not match recorded request in line {}: expected {}, got {}",
+                    recorded.line,
+                    hex::encode(&expected),
+                    hex::encode(&request)
+                ),
+            )
+            .into());
+        }
+
+        self.frames.pop_front();
+        self.sequence_nums = Some((recorded_sequence_num, header[SEQUENCE_NUM_OFFSET]));
+
+        Ok(())
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        if self.frames.front().is_none_or(|frame| frame.is_request) {
+            // The device did not respond during the recording
+            return Err(io::Error::from(io::ErrorKind::TimedOut).into());
+        }
+        let recorded = self.frames.pop_front().unwrap();
+
+        let frame = &mut buffer[..recorded.data.len()];
+        frame.copy_from_slice(&recorded.data);
+
+        if let Some((recorded_sequence_num, actual_sequence_num)) = self.sequence_nums {
+            if frame[SEQUENCE_NUM_OFFSET] == recorded_sequence_num {
+                frame[SEQUENCE_NUM_OFFSET] = actual_sequence_num;
+            }
+        }
+
+        Ok(frame)
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
+}
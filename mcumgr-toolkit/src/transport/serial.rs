@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use base64::prelude::*;
 use ringbuf::{
@@ -8,7 +8,9 @@ use ringbuf::{
 };
 use serialport::SerialPort;
 
-use super::{ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, Transport};
+use super::{
+    ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, Transport, TransportStats,
+};
 
 /// A transport layer implementation for serial ports.
 pub struct SerialTransport<T> {
@@ -17,8 +19,21 @@ pub struct SerialTransport<T> {
     serial: T,
     crc_algo: crc::Crc<u16>,
     read_buffer: LocalRb<Heap<u8>>,
+    console_sink: Option<Box<ConsoleSink>>,
+    console_line: Vec<u8>,
+    tolerate_crlf: bool,
+    stats: TransportStats,
 }
 
+/// Receives the console output of the device, one line at a time.
+///
+/// See [`SerialTransport::set_console_sink`].
+pub type ConsoleSink = dyn FnMut(&str) + Send;
+
+/// Maximum length of a console line before it gets forwarded
+/// to the [`ConsoleSink`] without a line ending.
+const CONSOLE_MAX_LINE_LENGTH: usize = 4096;
+
 fn fill_buffer_with_data<'a, I: Iterator<Item = u8>>(
     buffer: &'a mut [u8],
     data_iter: &mut I,
@@ -37,24 +52,151 @@ fn fill_buffer_with_data<'a, I: Iterator<Item = u8>>(
 /// See Zephyr's [`MCUMGR_SERIAL_MAX_FRAME`](https://github.com/zephyrproject-rtos/zephyr/blob/v4.2.1/include/zephyr/mgmt/mcumgr/transport/serial.h#L18).
 const SERIAL_TRANSPORT_ZEPHYR_MTU: usize = 127;
 
+/// The smallest line MTU that is able to transport data.
+pub const SERIAL_TRANSPORT_MIN_MTU: usize = 7;
+
+/// The smallest supported read buffer size.
+const SERIAL_TRANSPORT_MIN_READ_BUFFER_SIZE: usize = 2;
+
+/// Creates a [`SerialTransport`] with non-default settings.
+///
+/// ```no_run
+/// # use mcumgr_toolkit::{MCUmgrClient, transport::serial::SerialTransportBuilder};
+/// # fn main() {
+/// let serial = serialport::new("/dev/ttyUSB0", 115200)
+///     .timeout(std::time::Duration::from_millis(10000))
+///     .open()
+///     .unwrap();
+///
+/// let transport = SerialTransportBuilder::new()
+///     .mtu(256)
+///     .tolerate_crlf(true)
+///     .build(serial);
+///
+/// let client = MCUmgrClient::new_from_transport(transport);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SerialTransportBuilder {
+    mtu: usize,
+    read_buffer_size: usize,
+    tolerate_crlf: bool,
+}
+
+impl Default for SerialTransportBuilder {
+    fn default() -> Self {
+        Self {
+            mtu: SERIAL_TRANSPORT_ZEPHYR_MTU,
+            read_buffer_size: 4096,
+            tolerate_crlf: false,
+        }
+    }
+}
+
+impl SerialTransportBuilder {
+    /// Creates a builder with the default settings of Zephyr.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum length of a single line on the serial port,
+    /// including the frame markers and the line ending.
+    ///
+    /// Must match Zephyr's [`MCUMGR_SERIAL_MAX_FRAME`](https://github.com/zephyrproject-rtos/zephyr/blob/v4.2.1/include/zephyr/mgmt/mcumgr/transport/serial.h#L18).
+    /// Defaults to `127`. Values below [`SERIAL_TRANSPORT_MIN_MTU`] are raised to it.
+    pub fn mtu(mut self, mtu: usize) -> Self {
+        self.mtu = mtu.max(SERIAL_TRANSPORT_MIN_MTU);
+        self
+    }
+
+    /// Sets the size of the buffer that data from the serial port gets read into.
+    ///
+    /// Defaults to `4096` bytes.
+    pub fn read_buffer_size(mut self, size: usize) -> Self {
+        self.read_buffer_size = size.max(SERIAL_TRANSPORT_MIN_READ_BUFFER_SIZE);
+        self
+    }
+
+    /// Accepts frames that are terminated with `\r\n` instead of `\n`.
+    ///
+    /// Some serial bridges and terminal servers convert line endings.
+    /// Defaults to `false`.
+    pub fn tolerate_crlf(mut self, tolerate_crlf: bool) -> Self {
+        self.tolerate_crlf = tolerate_crlf;
+        self
+    }
+
+    /// Creates the [`SerialTransport`].
+    ///
+    /// # Arguments
+    ///
+    /// * `serial` - A serial port object, like [`serialport::SerialPort`].
+    ///
+    pub fn build<T>(self, serial: T) -> SerialTransport<T>
+    where
+        T: std::io::Write + std::io::Read,
+    {
+        let mtu = self.mtu;
+        SerialTransport {
+            serial,
+            transfer_buffer: vec![0u8; mtu].into_boxed_slice(),
+            body_buffer: vec![0u8; ((mtu - 3) / 4) * 3].into_boxed_slice(),
+            crc_algo: crc::Crc::<u16>::new(&crc::CRC_16_XMODEM),
+            read_buffer: LocalRb::new(self.read_buffer_size),
+            console_sink: None,
+            console_line: vec![],
+            tolerate_crlf: self.tolerate_crlf,
+            stats: TransportStats::default(),
+        }
+    }
+}
+
 impl<T> SerialTransport<T>
 where
     T: std::io::Write + std::io::Read,
 {
-    /// Create a new [`SerialTransport`].
+    /// Create a new [`SerialTransport`] with default settings.
+    ///
+    /// Use [`SerialTransportBuilder`] to customize the settings.
     ///
     /// # Arguments
     ///
     /// * `serial` - A serial port object, like [`serialport::SerialPort`].
     ///
     pub fn new(serial: T) -> Self {
-        let mtu = SERIAL_TRANSPORT_ZEPHYR_MTU;
-        Self {
-            serial,
-            transfer_buffer: vec![0u8; mtu].into_boxed_slice(),
-            body_buffer: vec![0u8; ((mtu - 3) / 4) * 3].into_boxed_slice(),
-            crc_algo: crc::Crc::<u16>::new(&crc::CRC_16_XMODEM),
-            read_buffer: LocalRb::new(4096),
+        SerialTransportBuilder::new().build(serial)
+    }
+
+    /// Forwards all non-SMP data received on the serial port to the given callback.
+    ///
+    /// Zephyr can share a single UART between the shell/logging console and SMP.
+    /// Without a console sink, all data outside of SMP frames gets discarded.
+    ///
+    /// The data is split into lines and passed to the callback without
+    /// their line endings. Invalid UTF-8 gets replaced.
+    ///
+    /// Note that console data only gets processed while receiving;
+    /// use [`Transport::receive_console`] to receive it outside of a command.
+    pub fn set_console_sink(&mut self, sink: impl FnMut(&str) + Send + 'static) {
+        self.console_sink = Some(Box::new(sink));
+    }
+
+    /// Processes a byte that is not part of an SMP frame.
+    fn push_console_byte(&mut self, byte: u8) {
+        let Some(sink) = &mut self.console_sink else {
+            return;
+        };
+
+        if byte == b'\n' || self.console_line.len() >= CONSOLE_MAX_LINE_LENGTH {
+            if self.console_line.last() == Some(&b'\r') {
+                self.console_line.pop();
+            }
+            sink(&String::from_utf8_lossy(&self.console_line));
+            self.console_line.clear();
+        }
+
+        if byte != b'\n' {
+            self.console_line.push(byte);
         }
     }
 
@@ -83,6 +225,7 @@ where
 
             self.serial
                 .write_all(&self.transfer_buffer[..base64_len + 3])?;
+            self.stats.encoded_bytes_sent += (base64_len + 3) as u64;
 
             log::debug!(
                 "Sent Chunk ({}, {} bytes raw, {} bytes encoded)",
@@ -135,6 +278,8 @@ where
                 self.read_buffer.try_pop().unwrap();
                 break;
             }
+
+            self.push_console_byte(current);
         }
 
         let mut base64_data = None;
@@ -165,11 +310,21 @@ where
             *elem = data;
         }
 
-        if let Some(0x0a) = self.read_buffer.try_peek() {
+        if base64_data.is_none() && self.read_buffer.try_peek() == Some(&0x0a) {
+            self.read_buffer.try_pop();
             base64_data = Some(&self.transfer_buffer);
         }
 
-        if let Some(base64_data) = base64_data {
+        if let Some(mut base64_data) = base64_data {
+            // Chunk header and newline
+            self.stats.encoded_bytes_received += (base64_data.len() + 3) as u64;
+
+            if self.tolerate_crlf {
+                if let Some(stripped) = base64_data.strip_suffix(b"\r") {
+                    base64_data = stripped;
+                }
+            }
+
             let len = BASE64_STANDARD.decode_slice(base64_data, &mut self.body_buffer)?;
 
             log::debug!(
@@ -259,6 +414,7 @@ where
         let actual_checksum = self.crc_algo.checksum(data);
 
         if expected_checksum != actual_checksum {
+            self.stats.crc_errors += 1;
             return Err(ReceiveError::UnexpectedResponse);
         }
 
@@ -267,12 +423,57 @@ where
         Ok(data)
     }
 
+    fn receive_console(&mut self, duration: Duration) -> Result<(), ReceiveError> {
+        while let Some(byte) = self.read_buffer.try_pop() {
+            self.push_console_byte(byte);
+        }
+
+        let timeout =
+            ConfigurableTimeout::get_timeout(&self.serial).map_err(std::io::Error::other)?;
+
+        let deadline = Instant::now() + duration;
+        let result = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break Ok(());
+            }
+
+            if let Err(e) = ConfigurableTimeout::set_timeout(&mut self.serial, remaining) {
+                break Err(std::io::Error::other(e).into());
+            }
+
+            match self.read_buffer.read_from(&mut self.serial, None).unwrap() {
+                Ok(0) => break Ok(()),
+                Ok(_) => {
+                    while let Some(byte) = self.read_buffer.try_pop() {
+                        self.push_console_byte(byte);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => break Ok(()),
+                Err(e) => break Err(e.into()),
+            }
+        };
+
+        ConfigurableTimeout::set_timeout(&mut self.serial, timeout)
+            .map_err(std::io::Error::other)?;
+
+        result
+    }
+
+    fn stats(&self) -> Option<TransportStats> {
+        Some(self.stats)
+    }
+
     fn set_timeout(
         &mut self,
         timeout: std::time::Duration,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         ConfigurableTimeout::set_timeout(&mut self.serial, timeout)
     }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        ConfigurableTimeout::get_timeout(&self.serial)
+    }
 }
 
 /// Specifies that the serial transport has a configurable timeout
@@ -285,13 +486,38 @@ pub trait ConfigurableTimeout {
         &mut self,
         duration: Duration,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Returns the current communication timeout.
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>>;
 }
 
-impl<T: AsMut<dyn SerialPort> + ?Sized> ConfigurableTimeout for T {
+impl<T: AsMut<dyn SerialPort> + AsRef<dyn SerialPort> + ?Sized> ConfigurableTimeout for T {
     fn set_timeout(
         &mut self,
         timeout: Duration,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         SerialPort::set_timeout(self.as_mut(), timeout).map_err(Into::into)
     }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(SerialPort::timeout(self.as_ref()))
+    }
+}
+
+/// Specifies that the serial port has a configurable baud rate
+pub trait ConfigurableBaudRate {
+    /// Changes the baud rate of the serial port.
+    fn set_baud_rate(
+        &mut self,
+        baud_rate: u32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+impl<T: AsMut<dyn SerialPort> + ?Sized> ConfigurableBaudRate for T {
+    fn set_baud_rate(
+        &mut self,
+        baud_rate: u32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        SerialPort::set_baud_rate(self.as_mut(), baud_rate).map_err(Into::into)
+    }
 }
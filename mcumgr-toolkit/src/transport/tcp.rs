@@ -0,0 +1,276 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use super::serial::{ConfigurableBaudRate, ConfigurableTimeout};
+
+/// Telnet protocol bytes, see RFC 854
+mod telnet {
+    pub(super) const IAC: u8 = 255;
+    pub(super) const DONT: u8 = 254;
+    pub(super) const DO: u8 = 253;
+    pub(super) const WONT: u8 = 252;
+    pub(super) const WILL: u8 = 251;
+    pub(super) const SB: u8 = 250;
+    pub(super) const SE: u8 = 240;
+
+    pub(super) const OPT_BINARY: u8 = 0;
+    pub(super) const OPT_SUPPRESS_GO_AHEAD: u8 = 3;
+    /// See RFC 2217
+    pub(super) const OPT_COM_PORT: u8 = 44;
+
+    pub(super) const COM_PORT_SET_BAUDRATE: u8 = 1;
+}
+
+/// Position in the telnet data stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TelnetState {
+    Data,
+    Iac,
+    Negotiation(u8),
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+/// A serial port behind a TCP serial bridge, like `ser2net`.
+///
+/// Supports plain TCP connections, where the data is forwarded unmodified,
+/// and [RFC 2217](https://www.rfc-editor.org/rfc/rfc2217) connections, which
+/// additionally allow to configure the remote serial port.
+///
+/// Use it with a [`SerialTransport`](super::serial::SerialTransport),
+/// or directly with [`MCUmgrClient::new_from_tcp_serial`](crate::MCUmgrClient::new_from_tcp_serial).
+pub struct TcpSerial {
+    stream: TcpStream,
+    timeout: Duration,
+    /// `Some` if the connection uses the telnet protocol of RFC 2217
+    telnet: Option<TelnetState>,
+    /// Received data with the telnet commands removed
+    read_buffer: VecDeque<u8>,
+}
+
+impl TcpSerial {
+    /// Connects to a serial bridge that forwards the data unmodified.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the serial bridge.
+    /// * `timeout` - The communication timeout.
+    pub fn connect(addr: impl ToSocketAddrs, timeout: Duration) -> io::Result<Self> {
+        Self::new(TcpStream::connect(addr)?, false, timeout)
+    }
+
+    /// Connects to a serial bridge that speaks RFC 2217.
+    ///
+    /// Enables [`set_baud_rate`](ConfigurableBaudRate::set_baud_rate).
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the serial bridge.
+    /// * `timeout` - The communication timeout.
+    pub fn connect_rfc2217(addr: impl ToSocketAddrs, timeout: Duration) -> io::Result<Self> {
+        Self::new(TcpStream::connect(addr)?, true, timeout)
+    }
+
+    /// Creates a serial port from an already connected stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection to the serial bridge.
+    /// * `rfc2217` - Whether the serial bridge speaks RFC 2217.
+    /// * `timeout` - The communication timeout.
+    pub fn new(stream: TcpStream, rfc2217: bool, timeout: Duration) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+
+        let mut serial = Self {
+            stream,
+            timeout: Duration::ZERO,
+            telnet: rfc2217.then_some(TelnetState::Data),
+            read_buffer: VecDeque::new(),
+        };
+        serial.apply_timeout(timeout)?;
+
+        if rfc2217 {
+            use telnet::*;
+
+            // The server's answers get processed while reading
+            serial.stream.write_all(&[
+                IAC,
+                WILL,
+                OPT_BINARY,
+                IAC,
+                DO,
+                OPT_BINARY,
+                IAC,
+                WILL,
+                OPT_SUPPRESS_GO_AHEAD,
+                IAC,
+                DO,
+                OPT_SUPPRESS_GO_AHEAD,
+                IAC,
+                WILL,
+                OPT_COM_PORT,
+            ])?;
+        }
+
+        Ok(serial)
+    }
+
+    fn apply_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        // A zero timeout is rejected by the socket; treat it as 'no timeout'
+        self.stream
+            .set_read_timeout(Some(timeout).filter(|timeout| !timeout.is_zero()))?;
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    /// Removes telnet commands from the received data and answers negotiations.
+    fn process_telnet(&mut self, data: &[u8]) -> io::Result<()> {
+        use telnet::*;
+
+        let Some(mut state) = self.telnet else {
+            self.read_buffer.extend(data);
+            return Ok(());
+        };
+
+        let mut answers = vec![];
+
+        for &byte in data {
+            state = match (state, byte) {
+                (TelnetState::Data, IAC) => TelnetState::Iac,
+                (TelnetState::Data, _) => {
+                    self.read_buffer.push_back(byte);
+                    TelnetState::Data
+                }
+                (TelnetState::Iac, IAC) => {
+                    self.read_buffer.push_back(IAC);
+                    TelnetState::Data
+                }
+                (TelnetState::Iac, DO | DONT | WILL | WONT) => TelnetState::Negotiation(byte),
+                (TelnetState::Iac, SB) => TelnetState::Subnegotiation,
+                (TelnetState::Iac, _) => TelnetState::Data,
+                (TelnetState::Negotiation(command), option) => {
+                    let supported = match command {
+                        DO => [OPT_BINARY, OPT_SUPPRESS_GO_AHEAD, OPT_COM_PORT].contains(&option),
+                        WILL => [OPT_BINARY, OPT_SUPPRESS_GO_AHEAD].contains(&option),
+                        _ => true,
+                    };
+                    if !supported {
+                        let refusal = if command == DO { WONT } else { DONT };
+                        answers.extend_from_slice(&[IAC, refusal, option]);
+                    }
+                    TelnetState::Data
+                }
+                // Notifications of the server are not needed
+                (TelnetState::Subnegotiation, IAC) => TelnetState::SubnegotiationIac,
+                (TelnetState::Subnegotiation, _) => TelnetState::Subnegotiation,
+                (TelnetState::SubnegotiationIac, SE) => TelnetState::Data,
+                (TelnetState::SubnegotiationIac, _) => TelnetState::Subnegotiation,
+            };
+        }
+
+        self.telnet = Some(state);
+
+        if !answers.is_empty() {
+            self.stream.write_all(&answers)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Read for TcpSerial {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Telnet commands might not contain any data
+        while self.read_buffer.is_empty() {
+            let mut chunk = [0; 1024];
+            let num_read = self.stream.read(&mut chunk).map_err(|e| {
+                // Unix reports an expired read timeout as `WouldBlock`
+                if e.kind() == io::ErrorKind::WouldBlock {
+                    io::Error::from(io::ErrorKind::TimedOut)
+                } else {
+                    e
+                }
+            })?;
+
+            if num_read == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+
+            self.process_telnet(&chunk[..num_read])?;
+        }
+
+        self.read_buffer.read(buf)
+    }
+}
+
+impl Write for TcpSerial {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.telnet.is_none() {
+            return self.stream.write(buf);
+        }
+
+        let mut escaped = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            if byte == telnet::IAC {
+                escaped.push(telnet::IAC);
+            }
+            escaped.push(byte);
+        }
+        self.stream.write_all(&escaped)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl ConfigurableTimeout for TcpSerial {
+    fn set_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_timeout(duration).map_err(Into::into)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
+}
+
+impl ConfigurableBaudRate for TcpSerial {
+    /// Changes the baud rate of the remote serial port.
+    ///
+    /// Requires a connection with RFC 2217.
+    fn set_baud_rate(
+        &mut self,
+        baud_rate: u32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use telnet::*;
+
+        if self.telnet.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Changing the baud rate requires RFC 2217",
+            )
+            .into());
+        }
+
+        let mut command = vec![IAC, SB, OPT_COM_PORT, COM_PORT_SET_BAUDRATE];
+        for byte in baud_rate.to_be_bytes() {
+            if byte == IAC {
+                command.push(IAC);
+            }
+            command.push(byte);
+        }
+        command.extend_from_slice(&[IAC, SE]);
+
+        self.stream.write_all(&command)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use super::{
+    ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, SmpHeader, Transport,
+};
+
+/// Position of the sequence number in the SMP header
+const SEQUENCE_NUM_OFFSET: usize = 6;
+
+/// A transport layer implementation for SMP over UDP.
+///
+/// Every SMP frame is transferred in a single datagram.
+///
+/// ```no_run
+/// # use mcumgr_toolkit::{MCUmgrClient, transport::udp::UdpTransport};
+/// # fn main() {
+/// let transport =
+///     UdpTransport::new("192.168.1.42:1337", std::time::Duration::from_millis(10000)).unwrap();
+///
+/// let client = MCUmgrClient::new_from_transport(transport);
+/// # }
+/// ```
+pub struct UdpTransport {
+    socket: UdpSocket,
+    timeout: Duration,
+}
+
+impl UdpTransport {
+    /// Creates a UDP transport that communicates with the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address of the device or the [`UdpProxy`].
+    /// * `timeout` - The communication timeout.
+    pub fn new(addr: impl ToSocketAddrs, timeout: Duration) -> io::Result<Self> {
+        let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "No address to connect to")
+        })?;
+
+        let local_addr: SocketAddr = if addr.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+
+        let socket = UdpSocket::bind(local_addr)?;
+        socket.connect(addr)?;
+
+        let mut transport = Self {
+            socket,
+            timeout: Duration::ZERO,
+        };
+        transport.apply_timeout(timeout)?;
+
+        Ok(transport)
+    }
+
+    fn apply_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        // A zero timeout is rejected by the socket; treat it as 'no timeout'
+        self.socket
+            .set_read_timeout(Some(timeout).filter(|timeout| !timeout.is_zero()))?;
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        let frame = [&header[..], data].concat();
+        if frame.len() > SMP_TRANSFER_BUFFER_SIZE {
+            return Err(SendError::DataTooBig);
+        }
+
+        self.socket.send(&frame)?;
+        Ok(())
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        let len = self.socket.recv(buffer).map_err(|e| {
+            // Unix reports an expired read timeout as `WouldBlock`
+            if e.kind() == io::ErrorKind::WouldBlock {
+                io::Error::from(io::ErrorKind::TimedOut)
+            } else {
+                e
+            }
+        })?;
+
+        Ok(&buffer[..len])
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_timeout(timeout).map_err(Into::into)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
+}
+
+/// Possible error values of [`UdpProxy`].
+#[derive(Error, Debug, Diagnostic)]
+pub enum ProxyError {
+    /// Receiving a request from a client failed
+    #[error("Failed to receive request")]
+    #[diagnostic(code(mcumgr_toolkit::proxy::receive_request))]
+    ReceiveRequestFailed(#[source] io::Error),
+    /// A client sent a datagram that is not a valid SMP request
+    #[error("Received invalid request from {0}")]
+    #[diagnostic(code(mcumgr_toolkit::proxy::invalid_request))]
+    InvalidRequest(SocketAddr),
+    /// Forwarding the request to the device failed
+    #[error("Failed to forward request to the device")]
+    #[diagnostic(code(mcumgr_toolkit::proxy::device_send))]
+    DeviceSendFailed(#[from] SendError),
+    /// Receiving the response from the device failed
+    #[error("Failed to receive response from the device")]
+    #[diagnostic(code(mcumgr_toolkit::proxy::device_receive))]
+    DeviceReceiveFailed(#[from] ReceiveError),
+    /// Sending the response back to the client failed
+    #[error("Failed to send response to {addr}")]
+    #[diagnostic(code(mcumgr_toolkit::proxy::send_response))]
+    SendResponseFailed {
+        /// The address of the client
+        addr: SocketAddr,
+        /// The underlying error
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Exposes a device over SMP-over-UDP.
+///
+/// Accepts SMP requests as UDP datagrams, forwards them to the device
+/// and routes the responses back to the requesting client.
+///
+/// Requests are processed one at a time. Their sequence numbers are
+/// rewritten, so that several clients can share the same device without
+/// receiving each other's responses.
+///
+/// ```no_run
+/// # use mcumgr_toolkit::transport::{serial::SerialTransport, udp::UdpProxy};
+/// # fn main() {
+/// let serial = serialport::new("/dev/ttyACM0", 115200)
+///     .timeout(std::time::Duration::from_millis(10000))
+///     .open()
+///     .unwrap();
+///
+/// let mut proxy = UdpProxy::bind(SerialTransport::new(serial), "0.0.0.0:1337").unwrap();
+/// proxy.run().unwrap();
+/// # }
+/// ```
+pub struct UdpProxy<T> {
+    transport: T,
+    socket: UdpSocket,
+    next_seqnum: u8,
+    request_buffer: Box<[u8; SMP_TRANSFER_BUFFER_SIZE]>,
+    response_buffer: Box<[u8; SMP_TRANSFER_BUFFER_SIZE]>,
+}
+
+impl<T: Transport> UdpProxy<T> {
+    /// Creates a proxy that listens on the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport` - The transport that communicates with the device.
+    /// * `addr` - The local address to accept requests on.
+    pub fn bind(transport: T, addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::new(transport, UdpSocket::bind(addr)?))
+    }
+
+    /// Creates a proxy that accepts requests on an already bound socket.
+    pub fn new(transport: T, socket: UdpSocket) -> Self {
+        Self {
+            transport,
+            socket,
+            next_seqnum: rand::random(),
+            request_buffer: Box::new([0; SMP_TRANSFER_BUFFER_SIZE]),
+            response_buffer: Box::new([0; SMP_TRANSFER_BUFFER_SIZE]),
+        }
+    }
+
+    /// The local address the proxy accepts requests on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Waits for a single request and forwards it to the device.
+    ///
+    /// Failing requests are not answered; the client runs into its timeout.
+    pub fn serve_one(&mut self) -> Result<(), ProxyError> {
+        let (len, addr) = self
+            .socket
+            .recv_from(&mut self.request_buffer[..])
+            .map_err(ProxyError::ReceiveRequestFailed)?;
+
+        let (header, data) = self.request_buffer[..len]
+            .split_first_chunk::<SMP_HEADER_SIZE>()
+            .ok_or(ProxyError::InvalidRequest(addr))?;
+        let mut header = *header;
+
+        if usize::from(SmpHeader::from_bytes(header).data_length) != data.len() {
+            return Err(ProxyError::InvalidRequest(addr));
+        }
+
+        let client_seqnum = header[SEQUENCE_NUM_OFFSET];
+        let device_seqnum = self.next_seqnum;
+        self.next_seqnum = self.next_seqnum.wrapping_add(1);
+
+        log::debug!("{addr}: forwarding request, seq {client_seqnum} -> {device_seqnum}");
+
+        header[SEQUENCE_NUM_OFFSET] = device_seqnum;
+        self.transport.send_raw_frame(header, data)?;
+
+        let response = loop {
+            let frame = self.transport.recv_raw_frame(&mut self.response_buffer)?;
+            match frame.get(SEQUENCE_NUM_OFFSET) {
+                Some(&seqnum) if seqnum == device_seqnum && frame.len() >= SMP_HEADER_SIZE => {
+                    break frame.len();
+                }
+                // Late responses of requests that already timed out
+                _ => log::debug!("Ignoring response with unexpected sequence number"),
+            }
+        };
+
+        let response = &mut self.response_buffer[..response];
+        response[SEQUENCE_NUM_OFFSET] = client_seqnum;
+
+        self.socket
+            .send_to(response, addr)
+            .map_err(|source| ProxyError::SendResponseFailed { addr, source })?;
+
+        Ok(())
+    }
+
+    /// Serves requests until receiving from the socket fails.
+    ///
+    /// Errors of individual requests are logged and do not stop the proxy.
+    /// This includes a `ConnectionReset` when receiving, which Windows reports
+    /// after a client that sent a request went away.
+    pub fn run(&mut self) -> Result<(), ProxyError> {
+        loop {
+            match self.serve_one() {
+                Ok(()) => {}
+                Err(ProxyError::ReceiveRequestFailed(e))
+                    if !matches!(
+                        e.kind(),
+                        io::ErrorKind::ConnectionReset | io::ErrorKind::Interrupted
+                    ) =>
+                {
+                    return Err(ProxyError::ReceiveRequestFailed(e));
+                }
+                Err(e) => {
+                    let mut message = e.to_string();
+                    let mut source = std::error::Error::source(&e);
+                    while let Some(err) = source {
+                        message.push_str(&format!(": {err}"));
+                        source = err.source();
+                    }
+                    log::warn!("{message}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    io::{self, Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    time::Duration,
+};
+
+use super::{
+    ReceiveError, SMP_HEADER_SIZE, SMP_TRANSFER_BUFFER_SIZE, SendError, SmpHeader, Transport,
+    TransportStats,
+    serial::{ConfigurableTimeout, SerialTransport},
+};
+
+/// How SMP frames are encoded on a [`UnixSocketTransport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnixSocketFraming {
+    /// Raw SMP frames, delimited by the length field of their header
+    #[default]
+    Raw,
+    /// The base64 line encoding of SMP over UART
+    Serial,
+}
+
+/// A [`UnixStream`] with a communication timeout.
+struct TimeoutStream {
+    stream: UnixStream,
+    timeout: Duration,
+}
+
+impl TimeoutStream {
+    fn new(stream: UnixStream, timeout: Duration) -> io::Result<Self> {
+        let mut stream = Self {
+            stream,
+            timeout: Duration::ZERO,
+        };
+        stream.apply_timeout(timeout)?;
+        Ok(stream)
+    }
+
+    fn apply_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        // A zero timeout is rejected by the socket; treat it as 'no timeout'
+        self.stream
+            .set_read_timeout(Some(timeout).filter(|timeout| !timeout.is_zero()))?;
+        self.timeout = timeout;
+        Ok(())
+    }
+}
+
+impl Read for TimeoutStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf).map_err(|e| {
+            // Unix reports an expired read timeout as `WouldBlock`
+            if e.kind() == io::ErrorKind::WouldBlock {
+                io::Error::from(io::ErrorKind::TimedOut)
+            } else {
+                e
+            }
+        })
+    }
+}
+
+impl Write for TimeoutStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl ConfigurableTimeout for TimeoutStream {
+    fn set_timeout(
+        &mut self,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_timeout(duration).map_err(Into::into)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
+}
+
+/// A [`TimeoutStream`] that carries raw SMP frames.
+///
+/// Keeps the state of a partially received frame, so that a timeout
+/// in the middle of a frame does not corrupt the following frames.
+struct RawStream {
+    stream: TimeoutStream,
+    /// The already received bytes of the current frame
+    pending: Vec<u8>,
+    /// The remaining bytes of an oversized frame that still need to be skipped
+    discard: usize,
+}
+
+impl RawStream {
+    fn new(stream: TimeoutStream) -> Self {
+        Self {
+            stream,
+            pending: Vec::new(),
+            discard: 0,
+        }
+    }
+
+    /// The size of the current frame, or only of its header if that is still incomplete.
+    fn frame_size(&self) -> usize {
+        match self.pending.first_chunk() {
+            Some(header) => {
+                SMP_HEADER_SIZE + usize::from(SmpHeader::from_bytes(*header).data_length)
+            }
+            None => SMP_HEADER_SIZE,
+        }
+    }
+
+    /// Reads some bytes, treating the end of the stream as an error.
+    fn read_some(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.stream.read(buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(num_read) => return Ok(num_read),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn recv_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        while self.discard > 0 {
+            let chunk_size = self.discard.min(buffer.len());
+            self.discard -= self.read_some(&mut buffer[..chunk_size])?;
+        }
+
+        loop {
+            let frame_size = self.frame_size();
+            if frame_size > SMP_TRANSFER_BUFFER_SIZE {
+                self.discard = frame_size - self.pending.len();
+                self.pending.clear();
+                return Err(ReceiveError::FrameTooBig);
+            }
+
+            if self.pending.len() == frame_size {
+                buffer[..frame_size].copy_from_slice(&self.pending);
+                self.pending.clear();
+                return Ok(&buffer[..frame_size]);
+            }
+
+            let num_read = self.read_some(&mut buffer[..frame_size - self.pending.len()])?;
+            self.pending.extend_from_slice(&buffer[..num_read]);
+        }
+    }
+}
+
+enum Inner {
+    Raw(RawStream),
+    Serial(Box<SerialTransport<TimeoutStream>>),
+}
+
+/// A transport layer implementation for Unix domain sockets,
+/// as used by some device simulators.
+///
+/// ```no_run
+/// # use mcumgr_toolkit::{MCUmgrClient, transport::unix::{UnixSocketFraming, UnixSocketTransport}};
+/// # fn main() {
+/// let transport = UnixSocketTransport::connect(
+///     "/tmp/smp.sock",
+///     UnixSocketFraming::Raw,
+///     std::time::Duration::from_millis(10000),
+/// )
+/// .unwrap();
+///
+/// let client = MCUmgrClient::new_from_transport(transport);
+/// # }
+/// ```
+pub struct UnixSocketTransport {
+    inner: Inner,
+}
+
+impl UnixSocketTransport {
+    /// Connects to the Unix domain socket at the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the socket.
+    /// * `framing` - How SMP frames are encoded on the socket.
+    /// * `timeout` - The communication timeout.
+    pub fn connect(
+        path: impl AsRef<Path>,
+        framing: UnixSocketFraming,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        Self::new(UnixStream::connect(path)?, framing, timeout)
+    }
+
+    /// Creates a transport from an already connected socket.
+    pub fn new(
+        stream: UnixStream,
+        framing: UnixSocketFraming,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let stream = TimeoutStream::new(stream, timeout)?;
+
+        let inner = match framing {
+            UnixSocketFraming::Raw => Inner::Raw(RawStream::new(stream)),
+            UnixSocketFraming::Serial => Inner::Serial(Box::new(SerialTransport::new(stream))),
+        };
+
+        Ok(Self { inner })
+    }
+}
+
+impl Transport for UnixSocketTransport {
+    fn send_raw_frame(
+        &mut self,
+        header: [u8; SMP_HEADER_SIZE],
+        data: &[u8],
+    ) -> Result<(), SendError> {
+        match &mut self.inner {
+            Inner::Raw(raw) => {
+                raw.stream.write_all(&[&header[..], data].concat())?;
+                Ok(())
+            }
+            Inner::Serial(transport) => transport.send_raw_frame(header, data),
+        }
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; SMP_TRANSFER_BUFFER_SIZE],
+    ) -> Result<&'a [u8], ReceiveError> {
+        match &mut self.inner {
+            Inner::Raw(raw) => raw.recv_frame(buffer),
+            Inner::Serial(transport) => transport.recv_raw_frame(buffer),
+        }
+    }
+
+    fn receive_console(&mut self, duration: Duration) -> Result<(), ReceiveError> {
+        match &mut self.inner {
+            Inner::Raw(_) => Ok(()),
+            Inner::Serial(transport) => transport.receive_console(duration),
+        }
+    }
+
+    fn stats(&self) -> Option<TransportStats> {
+        match &self.inner {
+            Inner::Raw(_) => None,
+            Inner::Serial(transport) => transport.stats(),
+        }
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match &mut self.inner {
+            Inner::Raw(raw) => ConfigurableTimeout::set_timeout(&mut raw.stream, timeout),
+            Inner::Serial(transport) => transport.set_timeout(timeout),
+        }
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        match &self.inner {
+            Inner::Raw(raw) => ConfigurableTimeout::get_timeout(&raw.stream),
+            Inner::Serial(transport) => transport.get_timeout(),
+        }
+    }
+}
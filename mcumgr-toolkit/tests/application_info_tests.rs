@@ -0,0 +1,67 @@
+mod common;
+use common::MockDevice;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::ApplicationInfo,
+    transport::fault::{Fault, FaultInjectingTransport},
+};
+
+#[test]
+fn os_application_info_structured() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let info = client.os_application_info_structured().unwrap();
+
+    assert_eq!(
+        info,
+        ApplicationInfo {
+            kernel_name: "Zephyr".to_string(),
+            node_name: "mock".to_string(),
+            kernel_release: "4.2.0".to_string(),
+            kernel_version: "v4.2.0".to_string(),
+            build_time: Some(
+                chrono::DateTime::parse_from_rfc3339("2025-03-14T15:09:26+01:00").unwrap()
+            ),
+            build_time_raw: Some("2025-03-14T15:09:26.000+0100".to_string()),
+            machine: "arm".to_string(),
+            processor: "cortex-m4".to_string(),
+            hardware_platform: "mock_board/nrf52840".to_string(),
+            operating_system: "Zephyr".to_string(),
+        }
+    );
+}
+
+#[test]
+fn os_application_info_structured_without_build_time() {
+    // The device rejects the truncated build time request with an error response
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(0, Fault::Truncate)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let info = client.os_application_info_structured().unwrap();
+    assert_eq!(info.build_time, None);
+    assert_eq!(info.build_time_raw, None);
+    assert_eq!(info.kernel_name, "Zephyr");
+}
+
+#[test]
+fn os_application_info_structured_unparsable_build_time() {
+    let mut device = MockDevice::new();
+    device.build_time = "built on a rainy tuesday";
+    let client = MCUmgrClient::new_from_transport(device);
+
+    let info = client.os_application_info_structured().unwrap();
+    assert_eq!(info.build_time, None);
+    assert_eq!(
+        info.build_time_raw.as_deref(),
+        Some("built on a rainy tuesday")
+    );
+}
+
+#[test]
+fn os_application_info_structured_transport_error() {
+    // Frame 2 is the request for the kernel name, after the build time
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(2, Fault::Drop)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    assert!(client.os_application_info_structured().is_err());
+}
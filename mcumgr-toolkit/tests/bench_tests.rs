@@ -0,0 +1,88 @@
+mod common;
+use common::MockDevice;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::bench::{BenchError, BenchParams, BenchStep, BenchTarget},
+};
+
+#[test]
+fn bench_file() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+    client.set_frame_size(200);
+
+    let params = BenchParams {
+        transfer_size: 4096,
+        echo_count: 5,
+        ..Default::default()
+    };
+    let result = client.bench(&params, None).unwrap();
+
+    assert_eq!(result.echo.unwrap().count, 5);
+    // The mock device reports a buffer size of 1024
+    assert_eq!(
+        result
+            .upload
+            .iter()
+            .map(|run| run.frame_size)
+            .collect::<Vec<_>>(),
+        [128, 256, 512, 1024]
+    );
+    assert!(result.upload.iter().all(|run| run.bytes_per_second > 0.0));
+    assert!(result.download_bytes_per_second.unwrap() > 0.0);
+    assert!(result.optimal_frame_size.is_some());
+
+    assert_eq!(client.frame_size(), 200);
+}
+
+#[test]
+fn bench_image_slot() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let params = BenchParams {
+        target: BenchTarget::ImageSlot,
+        frame_sizes: vec![256, 512],
+        transfer_size: 2048,
+        echo_count: 0,
+    };
+
+    let mut steps = vec![];
+    let result = client
+        .bench(
+            &params,
+            Some(&mut |step, progress| {
+                if progress.is_none() {
+                    steps.push(step.to_string());
+                }
+                true
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(result.echo, None);
+    assert_eq!(result.upload.len(), 2);
+    assert_eq!(result.download_bytes_per_second, None);
+    assert_eq!(
+        steps,
+        [
+            "Measuring latency ...",
+            "Erasing image slot ...",
+            "Uploading with frame size 256 ...",
+            "Uploading with frame size 512 ...",
+            "Erasing image slot ...",
+        ]
+    );
+}
+
+#[test]
+fn bench_cancel() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let err = client
+        .bench(
+            &BenchParams::default(),
+            Some(&mut |step, _| !matches!(step, BenchStep::Uploading { .. })),
+        )
+        .unwrap_err();
+
+    assert!(matches!(err, BenchError::ProgressCallbackError), "{err:?}");
+}
@@ -0,0 +1,407 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{NaiveDateTime, TimeDelta};
+use ciborium::{Value, cbor};
+use mcumgr_toolkit::{
+    client::time_sync::HostClock,
+    transport::{ReceiveError, SendError, Transport},
+};
+use sha2::{Digest, Sha256};
+
+/// A simulated device with an MCUboot bootloader, on SMP level.
+pub(crate) struct MockDevice {
+    response: Option<Vec<u8>>,
+    timeout: Duration,
+    active_hash: [u8; 32],
+    pub(crate) upload: Vec<u8>,
+    upload_len: usize,
+    upload_sha: Option<Vec<u8>>,
+    pub(crate) pending_hash: Option<[u8; 32]>,
+    pub(crate) files: HashMap<String, Vec<u8>>,
+    pub(crate) reset_count: usize,
+    /// The RTC, as the time it was set to and the host time when; unsupported until set
+    clock: Option<(NaiveDateTime, NaiveDateTime)>,
+    /// How fast the RTC runs compared to the host
+    pub(crate) clock_rate: f64,
+    /// The host time, which only advances through requests and sleeps
+    pub(crate) host_clock: SimulatedClock,
+    /// How long every request takes, half of it before the device handles it
+    pub(crate) latency: Duration,
+    /// The build time, as reported by `os application-info -b`
+    pub(crate) build_time: &'static str,
+    /// Whether reading the unset RTC fails with `OS_MGMT_ERR_RTC_NOT_SET` instead of `MGMT_ERR_ENOTSUP`
+    pub(crate) rtc_not_set: bool,
+}
+
+impl MockDevice {
+    pub(crate) fn new() -> Self {
+        Self {
+            response: None,
+            timeout: Duration::ZERO,
+            active_hash: [0x11; 32],
+            upload: vec![],
+            upload_len: 0,
+            upload_sha: None,
+            pending_hash: None,
+            files: HashMap::new(),
+            reset_count: 0,
+            clock: None,
+            clock_rate: 1.0,
+            host_clock: SimulatedClock::default(),
+            latency: Duration::ZERO,
+            build_time: "2025-03-14T15:09:26.000+0100",
+            rtc_not_set: false,
+        }
+    }
+
+    pub(crate) fn add_file(&mut self, name: &str, data: Vec<u8>) {
+        self.files.insert(name.to_string(), data);
+    }
+
+    fn get<'a>(map: &'a Value, key: &str) -> Option<&'a Value> {
+        map.as_map()?
+            .iter()
+            .find(|(k, _)| k.as_text() == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    fn get_usize(map: &Value, key: &str) -> Option<usize> {
+        Self::get(map, key)
+            .and_then(Value::as_integer)
+            .and_then(|value| usize::try_from(value).ok())
+    }
+
+    fn image_state(&self) -> Value {
+        let mut images = vec![
+            cbor!({
+                "image" => 0,
+                "slot" => 0,
+                "version" => "1.0.0",
+                "hash" => Value::Bytes(self.active_hash.to_vec()),
+                "bootable" => true,
+                "confirmed" => true,
+                "active" => true,
+            })
+            .unwrap(),
+        ];
+
+        if let Some(pending_hash) = self.pending_hash {
+            images.push(
+                cbor!({
+                    "image" => 0,
+                    "slot" => 1,
+                    "version" => "2.0.0",
+                    "hash" => Value::Bytes(pending_hash.to_vec()),
+                    "bootable" => true,
+                    "pending" => true,
+                })
+                .unwrap(),
+            );
+        }
+
+        cbor!({ "images" => images }).unwrap()
+    }
+
+    fn invalid_request() -> Value {
+        // MGMT_ERR_EINVAL
+        cbor!({ "rc" => 3 }).unwrap()
+    }
+
+    fn process(&mut self, write: bool, group_id: u16, command_id: u8, request: &Value) -> Value {
+        match (group_id, command_id, write) {
+            (0, 0, false) => match Self::get(request, "d").and_then(Value::as_text) {
+                Some(msg) => cbor!({ "r" => msg }).unwrap(),
+                None => Self::invalid_request(),
+            },
+            (0, 3, false) => cbor!({
+                "heap" => { "blksiz" => 16, "nblks" => 64, "nfree" => 20, "min" => 3 },
+                "net_buf" => { "blksiz" => 128, "nblks" => 8, "nfree" => 8, "min" => 6 },
+            })
+            .unwrap(),
+            (0, 6, false) => cbor!({ "buf_size" => 1024, "buf_count" => 4 }).unwrap(),
+            (0, 7, false) => {
+                let format = Self::get(request, "format").and_then(Value::as_text);
+                let output = match format.unwrap_or("s") {
+                    "s" | "o" => "Zephyr",
+                    "n" => "mock",
+                    "r" => "4.2.0",
+                    "v" => "v4.2.0",
+                    "b" => self.build_time,
+                    "m" => "arm",
+                    "p" => "cortex-m4",
+                    "i" => "mock_board/nrf52840",
+                    "a" => "Zephyr mock 4.2.0 v4.2.0 2025-03-14T15:09:26.000+0100 arm cortex-m4 mock_board/nrf52840 Zephyr",
+                    _ => return Self::invalid_request(),
+                };
+                cbor!({ "output" => output }).unwrap()
+            }
+            (0, 8, false) => {
+                if Self::get(request, "query").and_then(Value::as_text) == Some("mode") {
+                    cbor!({ "mode" => 0 }).unwrap()
+                } else {
+                    cbor!({ "bootloader" => "MCUboot" }).unwrap()
+                }
+            }
+            (0, 4, false) => match self.clock {
+                Some((datetime, set_at)) => {
+                    let elapsed = (self.host_clock.now() - set_at).to_std().unwrap();
+                    let now = datetime + TimeDelta::from_std(elapsed.mul_f64(self.clock_rate)).unwrap();
+                    cbor!({ "datetime" => now.format("%Y-%m-%dT%H:%M:%S%.6f").to_string() })
+                        .unwrap()
+                }
+                // OS_MGMT_ERR_RTC_NOT_SET
+                None if self.rtc_not_set => cbor!({ "err" => { "group" => 0, "rc" => 4 } }).unwrap(),
+                // MGMT_ERR_ENOTSUP
+                None => cbor!({ "rc" => 8 }).unwrap(),
+            },
+            (0, 4, true) => {
+                let Some(datetime) = Self::get(request, "datetime")
+                    .and_then(Value::as_text)
+                    .and_then(|text| {
+                        NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").ok()
+                    })
+                else {
+                    return Self::invalid_request();
+                };
+                self.clock = Some((datetime, self.host_clock.now()));
+                cbor!({}).unwrap()
+            }
+            (0, 5, true) => {
+                self.reset_count += 1;
+                cbor!({}).unwrap()
+            }
+            (1, 0, false) => self.image_state(),
+            (1, 0, true) => {
+                let hash = Self::get(request, "hash")
+                    .and_then(Value::as_bytes)
+                    .and_then(|hash| <[u8; 32]>::try_from(hash.as_slice()).ok());
+                self.pending_hash = hash;
+                self.image_state()
+            }
+            (1, 1, true) => {
+                let (Some(off), Some(data)) = (
+                    Self::get_usize(request, "off"),
+                    Self::get(request, "data").and_then(Value::as_bytes),
+                ) else {
+                    return Self::invalid_request();
+                };
+
+                if off == 0 {
+                    let Some(len) = Self::get_usize(request, "len") else {
+                        return Self::invalid_request();
+                    };
+                    self.upload.clear();
+                    self.upload_len = len;
+                    self.upload_sha = Self::get(request, "sha")
+                        .and_then(Value::as_bytes)
+                        .cloned();
+                }
+
+                // Like MCUboot, report the current offset on mismatch
+                if off == self.upload.len() {
+                    self.upload.extend_from_slice(data);
+                }
+
+                let off = self.upload.len();
+                if off == self.upload_len {
+                    // Like MCUboot, verify the image against the hash of the first chunk
+                    let matches = self
+                        .upload_sha
+                        .as_ref()
+                        .is_none_or(|sha| Sha256::digest(&self.upload).as_slice() == sha);
+                    cbor!({ "off" => off, "match" => matches }).unwrap()
+                } else {
+                    cbor!({ "off" => off }).unwrap()
+                }
+            }
+            (1, 6, false) => cbor!({
+                "images" => [{
+                    "image" => 0,
+                    "slots" => [{ "slot" => 0, "size" => 0x10000 }, { "slot" => 1, "size" => 0x10000 }],
+                }]
+            })
+            .unwrap(),
+            (1, 5, true) => {
+                self.upload.clear();
+                cbor!({}).unwrap()
+            }
+            (8, 0, true) => {
+                let (Some(name), Some(off), Some(data)) = (
+                    Self::get(request, "name").and_then(Value::as_text),
+                    Self::get_usize(request, "off"),
+                    Self::get(request, "data").and_then(Value::as_bytes),
+                ) else {
+                    return Self::invalid_request();
+                };
+
+                let file = self.files.entry(name.to_string()).or_default();
+                if off == 0 {
+                    file.clear();
+                }
+                if off == file.len() {
+                    file.extend_from_slice(data);
+                }
+                cbor!({ "off" => file.len() }).unwrap()
+            }
+            (8, 0, false) => {
+                let (Some(name), Some(off)) = (
+                    Self::get(request, "name").and_then(Value::as_text),
+                    Self::get_usize(request, "off"),
+                ) else {
+                    return Self::invalid_request();
+                };
+                let Some(file) = self.files.get(name) else {
+                    // MGMT_ERR_ENOENT
+                    return cbor!({ "rc" => 5 }).unwrap();
+                };
+
+                let chunk = file.get(off..).unwrap_or_default();
+                let chunk = Value::Bytes(chunk[..chunk.len().min(128)].to_vec());
+                if off == 0 {
+                    cbor!({ "off" => off, "data" => chunk, "len" => file.len() }).unwrap()
+                } else {
+                    cbor!({ "off" => off, "data" => chunk }).unwrap()
+                }
+            }
+            // MGMT_ERR_ENOTSUP
+            _ => cbor!({ "rc" => 8 }).unwrap(),
+        }
+    }
+}
+
+impl Transport for MockDevice {
+    fn send_raw_frame(&mut self, header: [u8; 8], data: &[u8]) -> Result<(), SendError> {
+        let write = header[0] & 0b111 == 2;
+        let group_id = u16::from_be_bytes([header[4], header[5]]);
+        let command_id = header[7];
+
+        self.host_clock.sleep(self.latency / 2);
+        let response = match ciborium::from_reader(data) {
+            Ok(request) => self.process(write, group_id, command_id, &request),
+            Err(_) => Self::invalid_request(),
+        };
+        self.host_clock.sleep(self.latency - self.latency / 2);
+
+        let mut payload = vec![];
+        ciborium::into_writer(&response, &mut payload).unwrap();
+
+        let mut frame = header.to_vec();
+        frame[0] |= 1;
+        frame[2..4].copy_from_slice(&(payload.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&payload);
+        self.response = Some(frame);
+
+        Ok(())
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; u16::MAX as usize],
+    ) -> Result<&'a [u8], ReceiveError> {
+        let Some(response) = self.response.take() else {
+            return Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into());
+        };
+
+        let frame = &mut buffer[..response.len()];
+        frame.copy_from_slice(&response);
+        Ok(frame)
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
+}
+
+/// A host clock that only advances when sleeping, shared between its clones.
+#[derive(Clone)]
+pub(crate) struct SimulatedClock(Arc<Mutex<NaiveDateTime>>);
+
+impl Default for SimulatedClock {
+    fn default() -> Self {
+        let start = NaiveDateTime::parse_from_str("2025-03-14T12:00:00", "%Y-%m-%dT%H:%M:%S");
+        Self(Arc::new(Mutex::new(start.unwrap())))
+    }
+}
+
+impl HostClock for SimulatedClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.0.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        *self.0.lock().unwrap() += TimeDelta::from_std(duration).unwrap();
+    }
+}
+
+/// A [`MockDevice`] that stays accessible after it was passed to a client.
+#[derive(Clone)]
+pub(crate) struct SharedMockDevice(pub(crate) Arc<Mutex<MockDevice>>);
+
+impl SharedMockDevice {
+    pub(crate) fn new(device: MockDevice) -> Self {
+        Self(Arc::new(Mutex::new(device)))
+    }
+}
+
+impl Transport for SharedMockDevice {
+    fn send_raw_frame(&mut self, header: [u8; 8], data: &[u8]) -> Result<(), SendError> {
+        self.0.lock().unwrap().send_raw_frame(header, data)
+    }
+
+    fn recv_raw_frame<'a>(
+        &mut self,
+        buffer: &'a mut [u8; u16::MAX as usize],
+    ) -> Result<&'a [u8], ReceiveError> {
+        self.0.lock().unwrap().recv_raw_frame(buffer)
+    }
+
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.0.lock().unwrap().set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.lock().unwrap().get_timeout()
+    }
+}
+
+/// Builds a minimal MCUboot image with the given id hash.
+pub(crate) fn mcuboot_image(id_hash: [u8; 32], body_size: usize) -> Vec<u8> {
+    const HEADER_SIZE: u16 = 32;
+
+    let mut image = vec![];
+    image.extend_from_slice(&0x96f3b83du32.to_le_bytes()); // ih_magic
+    image.extend_from_slice(&0u32.to_le_bytes()); // ih_load_addr
+    image.extend_from_slice(&HEADER_SIZE.to_le_bytes()); // ih_hdr_size
+    image.extend_from_slice(&0u16.to_le_bytes()); // ih_protect_tlv_size
+    image.extend_from_slice(&(body_size as u32).to_le_bytes()); // ih_img_size
+    image.extend_from_slice(&0u32.to_le_bytes()); // ih_flags
+    image.extend_from_slice(&[2, 0]); // ih_ver major, minor
+    image.extend_from_slice(&0u16.to_le_bytes()); // ih_ver revision
+    image.extend_from_slice(&0u32.to_le_bytes()); // ih_ver build_num
+    image.resize(HEADER_SIZE.into(), 0);
+
+    image.extend((0..body_size).map(|i| i as u8));
+
+    image.extend_from_slice(&0x6907u16.to_le_bytes()); // it_magic
+    image.extend_from_slice(&(4u16 + 4 + 32).to_le_bytes()); // it_tlv_tot
+    image.extend_from_slice(&[0x10, 0]); // IMAGE_TLV_SHA256
+    image.extend_from_slice(&32u16.to_le_bytes());
+    image.extend_from_slice(&id_hash);
+
+    image
+}
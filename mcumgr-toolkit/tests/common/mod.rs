@@ -3,13 +3,19 @@
 use std::{
     collections::VecDeque,
     io::{Read, Write},
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use mcumgr_toolkit::transport::serial::ConfigurableTimeout;
+use mcumgr_toolkit::transport::serial::{ConfigurableBaudRate, ConfigurableTimeout};
+
+mod mock_device;
+pub(crate) use mock_device::{MockDevice, SharedMockDevice, SimulatedClock, mcuboot_image};
 
 #[derive(Default)]
 pub(crate) struct LoopbackSerial {
     data: VecDeque<u8>,
+    timeout: Duration,
 }
 
 impl Read for LoopbackSerial {
@@ -31,10 +37,15 @@ impl Write for LoopbackSerial {
 impl ConfigurableTimeout for LoopbackSerial {
     fn set_timeout(
         &mut self,
-        _: std::time::Duration,
+        timeout: Duration,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.timeout = timeout;
         Ok(())
     }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
 }
 
 impl Drop for LoopbackSerial {
@@ -45,10 +56,52 @@ impl Drop for LoopbackSerial {
     }
 }
 
+/// A [`LoopbackSerial`] that converts all line endings to `\r\n`,
+/// like some serial bridges do.
+#[derive(Default)]
+pub(crate) struct CrlfSerial(LoopbackSerial);
+
+impl Read for CrlfSerial {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for CrlfSerial {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for byte in buf {
+            if *byte == b'\n' {
+                self.0.write_all(b"\r")?;
+            }
+            self.0.write_all(&[*byte])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl ConfigurableTimeout for CrlfSerial {
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.0.set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.get_timeout()
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct EchoSerial {
     input_buffer: VecDeque<u8>,
     output_buffer: VecDeque<u8>,
+    timeout: Duration,
+    smp_v1: bool,
 }
 
 const FRAME_START_1: u8 = 6;
@@ -58,6 +111,13 @@ const FRAME_START_CONT_2: u8 = 20;
 const FRAME_END: u8 = 0x0a;
 
 impl EchoSerial {
+    /// Creates an echo device that only supports SMP v1.
+    pub(crate) fn new_smp_v1() -> Self {
+        let mut serial = Self::default();
+        serial.smp_v1 = true;
+        serial
+    }
+
     fn process_input_data(&mut self) {
         let mut data = vec![];
 
@@ -126,6 +186,9 @@ impl EchoSerial {
         let mut response_smp = vec![];
         response_smp.extend_from_slice(header);
         response_smp[0] |= 1;
+        if self.smp_v1 {
+            response_smp[0] &= !0b11000;
+        }
         ciborium::into_writer(&data, &mut response_smp).unwrap();
         let new_crc = crc_algo.checksum(&response_smp);
         response_smp.extend_from_slice(&new_crc.to_be_bytes());
@@ -165,10 +228,15 @@ impl Write for EchoSerial {
 impl ConfigurableTimeout for EchoSerial {
     fn set_timeout(
         &mut self,
-        _: std::time::Duration,
+        timeout: Duration,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.timeout = timeout;
         Ok(())
     }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.timeout)
+    }
 }
 
 impl Drop for EchoSerial {
@@ -181,3 +249,156 @@ impl Drop for EchoSerial {
         }
     }
 }
+
+/// An [`EchoSerial`] that only responds at a single baud rate.
+pub(crate) struct BaudRateSerial {
+    echo: EchoSerial,
+    device_baud_rate: u32,
+    baud_rate: u32,
+}
+
+impl BaudRateSerial {
+    pub(crate) fn new(device_baud_rate: u32) -> Self {
+        Self {
+            echo: EchoSerial::default(),
+            device_baud_rate,
+            baud_rate: 0,
+        }
+    }
+}
+
+impl Read for BaudRateSerial {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.baud_rate == self.device_baud_rate {
+            self.echo.read(buf)
+        } else {
+            Err(std::io::ErrorKind::TimedOut.into())
+        }
+    }
+}
+
+impl Write for BaudRateSerial {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.baud_rate == self.device_baud_rate {
+            self.echo.write(buf)
+        } else {
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.echo.flush()
+    }
+}
+
+impl ConfigurableTimeout for BaudRateSerial {
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.echo.set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.echo.get_timeout()
+    }
+}
+
+impl ConfigurableBaudRate for BaudRateSerial {
+    fn set_baud_rate(
+        &mut self,
+        baud_rate: u32,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.baud_rate = baud_rate;
+        Ok(())
+    }
+}
+
+/// An [`EchoSerial`] that records every timeout change.
+#[derive(Default)]
+pub(crate) struct TimeoutLogSerial {
+    echo: EchoSerial,
+    pub(crate) log: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl Read for TimeoutLogSerial {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.echo.read(buf)
+    }
+}
+
+impl Write for TimeoutLogSerial {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.echo.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.echo.flush()
+    }
+}
+
+impl ConfigurableTimeout for TimeoutLogSerial {
+    fn set_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.log.lock().unwrap().push(timeout);
+        self.echo.set_timeout(timeout)
+    }
+
+    fn get_timeout(&self) -> Result<Duration, Box<dyn std::error::Error + Send + Sync>> {
+        self.echo.get_timeout()
+    }
+}
+
+/// Whether the data contains a complete serial framed SMP packet.
+fn is_complete_packet(data: &[u8]) -> bool {
+    use base64::prelude::*;
+
+    let mut decoded = vec![];
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        let Some(content) = line.get(2..line.len() - 1) else {
+            return false;
+        };
+        if !line.ends_with(b"\n") {
+            return false;
+        }
+        decoded.extend(BASE64_STANDARD.decode(content).unwrap());
+    }
+
+    decoded
+        .first_chunk()
+        .is_some_and(|len| decoded.len() >= usize::from(u16::from_be_bytes(*len)) + 2)
+}
+
+/// Answers serial framed echo requests until the link gets closed.
+pub(crate) fn serve_serial_echo(mut link: impl Read + Write) {
+    let mut device = EchoSerial::default();
+    let mut request = vec![];
+    let mut buffer = [0; 4096];
+
+    loop {
+        let Ok(num_read) = link.read(&mut buffer) else {
+            return;
+        };
+        if num_read == 0 {
+            return;
+        }
+        request.extend_from_slice(&buffer[..num_read]);
+
+        // The echo device expects complete requests
+        if !is_complete_packet(&request) {
+            continue;
+        }
+        device.write_all(&request).unwrap();
+        request.clear();
+
+        loop {
+            let num_read = device.read(&mut buffer).unwrap();
+            if num_read == 0 {
+                break;
+            }
+            link.write_all(&buffer[..num_read]).unwrap();
+        }
+    }
+}
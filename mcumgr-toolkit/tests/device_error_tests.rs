@@ -0,0 +1,90 @@
+mod common;
+use common::MockDevice;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::MCUmgrClientError,
+    connection::ExecuteError,
+    smp_errors::{DeviceError, FsMgmtErrCode, GroupError, ImgMgmtErrCode, MCUmgrErr},
+};
+use miette::Diagnostic;
+
+fn client_error(device_error: DeviceError) -> MCUmgrClientError {
+    ExecuteError::ErrorResponse(device_error).into()
+}
+
+#[test]
+fn device_error_kind() {
+    let err = DeviceError::V2 { group: 1, rc: 27 };
+    assert_eq!(
+        err.kind(),
+        Some(GroupError::Image(
+            ImgMgmtErrCode::IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER
+        ))
+    );
+    assert!(err.is_image_downgrade_rejected());
+    assert!(!err.is_not_found());
+    assert_eq!(err.to_string(), "IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER");
+
+    let err = DeviceError::V2 { group: 8, rc: 3 };
+    assert_eq!(
+        err.kind(),
+        Some(GroupError::Fs(FsMgmtErrCode::FS_MGMT_ERR_FILE_NOT_FOUND))
+    );
+    assert!(err.is_not_found());
+    assert!(!err.is_image_downgrade_rejected());
+
+    let err = DeviceError::V1 {
+        rc: MCUmgrErr::MGMT_ERR_ENOENT as i32,
+        rsn: None,
+    };
+    assert_eq!(err.kind(), None);
+    assert!(err.is_not_found());
+}
+
+#[test]
+fn device_error_kind_unknown() {
+    // Unknown group
+    let err = DeviceError::V2 { group: 1234, rc: 1 };
+    assert_eq!(err.kind(), None);
+    assert!(err.help().is_none());
+    assert_eq!(err.to_string(), "group=1234,rc=1");
+
+    // Unknown code
+    let err = DeviceError::V2 { group: 1, rc: 1000 };
+    assert_eq!(err.kind(), None);
+}
+
+#[test]
+fn device_error_help_is_forwarded() {
+    let err = client_error(DeviceError::V2 { group: 1, rc: 27 });
+
+    assert!(
+        err.device_error()
+            .is_some_and(DeviceError::is_image_downgrade_rejected)
+    );
+    assert_eq!(
+        err.help().map(|help| help.to_string()).as_deref(),
+        ImgMgmtErrCode::IMG_MGMT_ERR_CURRENT_VERSION_IS_NEWER.help()
+    );
+    // The own diagnostic code is kept
+    assert_eq!(
+        err.code().unwrap().to_string(),
+        "mcumgr_toolkit::client::execute"
+    );
+
+    assert!(MCUmgrClientError::UnexpectedOffset.device_error().is_none());
+}
+
+#[test]
+fn device_error_from_device() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let err = client.zephyr_erase_storage().unwrap_err();
+
+    let device_error = err.device_error().unwrap();
+    assert_eq!(device_error.kind(), None);
+    assert_eq!(
+        err.help().map(|help| help.to_string()).as_deref(),
+        MCUmgrErr::MGMT_ERR_ENOTSUP.help()
+    );
+}
@@ -0,0 +1,66 @@
+mod common;
+use common::MockDevice;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    bootloader::BootloaderInfo,
+    client::DeviceSnapshotError,
+    transport::fault::{Fault, FaultInjectingTransport},
+};
+
+#[test]
+fn device_snapshot() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let snapshot = client.device_snapshot().unwrap();
+
+    assert!(
+        snapshot
+            .application_info
+            .unwrap()
+            .starts_with("Zephyr mock 4.2.0")
+    );
+    assert_eq!(
+        snapshot.bootloader,
+        Some(BootloaderInfo::MCUboot {
+            mode: 0,
+            no_downgrade: false
+        })
+    );
+    assert_eq!(snapshot.image_state.unwrap().len(), 1);
+    assert_eq!(snapshot.image_slots.unwrap()[0].slots.len(), 2);
+    assert_eq!(snapshot.mcumgr_parameters.unwrap().buf_size, 1024);
+
+    // Not supported by the mock device
+    assert_eq!(snapshot.datetime, None);
+    assert_eq!(snapshot.task_statistics, None);
+    assert_eq!(snapshot.checksum_types, None);
+}
+
+#[test]
+fn device_snapshot_rtc_not_set() {
+    let mut device = MockDevice::new();
+    device.rtc_not_set = true;
+    let client = MCUmgrClient::new_from_transport(device);
+
+    let snapshot = client.device_snapshot().unwrap();
+    assert_eq!(snapshot.datetime, None);
+    assert!(snapshot.application_info.is_some());
+}
+
+#[test]
+fn device_snapshot_query_failed() {
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(2, Fault::Drop)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let err = client.device_snapshot().unwrap_err();
+    assert!(
+        matches!(
+            err,
+            DeviceSnapshotError::QueryFailed {
+                query: "bootloader info",
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,112 @@
+mod common;
+use std::sync::Mutex;
+
+use common::{MockDevice, mcuboot_image};
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::{
+        FirmwareUpdateError, FirmwareUpdateParams, FirmwareUpdateStep,
+        fleet::{self, FleetDevice, FleetUpdateError},
+    },
+    transport::fault::{Fault, FaultInjectingTransport},
+};
+
+#[test]
+fn fleet_firmware_update() {
+    let firmware = mcuboot_image([0x22; 32], 2000);
+
+    let devices = vec![
+        FleetDevice::new("a", MCUmgrClient::new_from_transport(MockDevice::new())),
+        FleetDevice::new(
+            "b",
+            MCUmgrClient::new_from_transport(FaultInjectingTransport::scripted(
+                MockDevice::new(),
+                [(0, Fault::Drop)],
+            )),
+        ),
+        FleetDevice::new("c", MCUmgrClient::new_from_transport(MockDevice::new())),
+    ];
+
+    let uploads = Mutex::new(vec![false; devices.len()]);
+    let results = fleet::firmware_update(
+        &devices,
+        &firmware,
+        None,
+        FirmwareUpdateParams::default(),
+        Some(&|index, step, _progress| {
+            if matches!(step, FirmwareUpdateStep::UploadingFirmware) {
+                uploads.lock().unwrap()[index] = true;
+            }
+            true
+        }),
+    );
+
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(FleetUpdateError::UpdateFailed(
+            FirmwareUpdateError::BootloaderDetectionFailed(_)
+        ))
+    ));
+    assert!(results[2].is_ok());
+    assert_eq!(*uploads.lock().unwrap(), [true, false, true]);
+}
+
+#[test]
+fn fleet_firmware_update_cancel() {
+    let firmware = mcuboot_image([0x22; 32], 2000);
+
+    let devices = vec![
+        FleetDevice::new("a", MCUmgrClient::new_from_transport(MockDevice::new())),
+        FleetDevice::new("b", MCUmgrClient::new_from_transport(MockDevice::new())),
+    ];
+
+    let results = fleet::firmware_update(
+        &devices,
+        &firmware,
+        None,
+        FirmwareUpdateParams::default(),
+        Some(&|index, _step, _progress| index != 1),
+    );
+
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(FleetUpdateError::UpdateFailed(
+            FirmwareUpdateError::ProgressCallbackError
+        ))
+    ));
+}
+
+#[test]
+fn fleet_firmware_update_open_failed() {
+    let firmware = mcuboot_image([0x22; 32], 2000);
+
+    let devices = vec![
+        FleetDevice::new("a", MCUmgrClient::new_from_transport(MockDevice::new())),
+        FleetDevice::failed(
+            "b",
+            serialport::Error::new(serialport::ErrorKind::NoDevice, "Device busy"),
+        ),
+    ];
+
+    let updated = Mutex::new(vec![false; devices.len()]);
+    let results = fleet::firmware_update(
+        &devices,
+        &firmware,
+        None,
+        FirmwareUpdateParams::default(),
+        Some(&|index, _step, _progress| {
+            updated.lock().unwrap()[index] = true;
+            true
+        }),
+    );
+
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        &results[1],
+        Err(FleetUpdateError::OpenPortFailed(e)) if e.kind() == serialport::ErrorKind::NoDevice
+    ));
+    assert_eq!(*updated.lock().unwrap(), [true, false]);
+}
@@ -1,7 +1,18 @@
 mod common;
-use common::EchoSerial;
-use mcumgr_toolkit::MCUmgrClient;
+use common::{BaudRateSerial, EchoSerial, MockDevice, TimeoutLogSerial};
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::{COMMON_BAUD_RATES, SerialProbeError, probe_serial_port},
+    commands::{self, LONG_OPERATION_TIMEOUT},
+    connection::{FrameDirection, FrameEvent, FrameOp},
+    transport::{
+        SmpVersion,
+        serial::{ConfigurableTimeout, SerialTransportBuilder},
+    },
+};
 use rand::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[test]
 fn echo() {
@@ -19,3 +30,201 @@ fn echo() {
     let response = client.os_echo(&request).unwrap();
     assert_eq!(request, response);
 }
+
+#[test]
+fn memory_pool_statistics() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let pools = client.os_memory_pool_statistics().unwrap();
+    assert_eq!(pools.len(), 2);
+    assert_eq!(pools["heap"].nfree, 20);
+    assert_eq!(pools["net_buf"].blksiz, 128);
+}
+
+#[test]
+fn probe_serial() {
+    let (client, result) = MCUmgrClient::probe_serial(
+        BaudRateSerial::new(921600),
+        COMMON_BAUD_RATES,
+        Duration::from_secs(1),
+    )
+    .unwrap();
+
+    assert_eq!(result.baud_rate, 921600);
+    // The echo device does not understand the MCUmgr parameters request
+    assert_eq!(result.frame_size, None);
+
+    let request = "Hello world!";
+    let response = client.os_echo(request).unwrap();
+    assert_eq!(request, response);
+}
+
+#[test]
+fn probe_serial_port_keeps_port() {
+    let mut serial = BaudRateSerial::new(921600);
+    serial.set_timeout(Duration::from_secs(3)).unwrap();
+
+    let (serial, result) =
+        probe_serial_port(serial, COMMON_BAUD_RATES, &SerialTransportBuilder::new()).unwrap();
+
+    assert_eq!(result.baud_rate, 921600);
+    assert_eq!(serial.get_timeout().unwrap(), Duration::from_secs(3));
+
+    let client = MCUmgrClient::new_from_serial(serial);
+    let request = "Hello world!";
+    let response = client.os_echo(request).unwrap();
+    assert_eq!(request, response);
+}
+
+#[test]
+fn probe_serial_no_response() {
+    let candidates = [9600, 19200];
+    let result = MCUmgrClient::probe_serial(
+        BaudRateSerial::new(115200),
+        &candidates,
+        Duration::from_secs(1),
+    );
+
+    match result {
+        Err(SerialProbeError::NoResponse { tried }) => assert_eq!(tried, candidates),
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Probing should fail"),
+    }
+}
+
+#[test]
+fn long_operation_timeout() {
+    let serial = TimeoutLogSerial::default();
+    let log = serial.log.clone();
+
+    let client = MCUmgrClient::new_from_serial(serial);
+    client.set_timeout(Duration::from_secs(1)).unwrap();
+    log.lock().unwrap().clear();
+
+    // Normal commands keep the configured timeout
+    client.os_echo("Hello").unwrap();
+    assert_eq!(*log.lock().unwrap(), []);
+
+    // Slow commands raise it temporarily
+    client.image_erase(None).unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        [LONG_OPERATION_TIMEOUT, Duration::from_secs(1)]
+    );
+    log.lock().unwrap().clear();
+
+    // A longer configured timeout is not reduced
+    client.set_timeout(Duration::from_secs(100)).unwrap();
+    log.lock().unwrap().clear();
+    client.image_erase(None).unwrap();
+    assert_eq!(*log.lock().unwrap(), []);
+
+    // Callers can pass their own timeout
+    client
+        .raw_command_with_timeout(
+            &commands::os::Echo { d: "Hello" },
+            Duration::from_millis(100),
+        )
+        .unwrap();
+    assert_eq!(
+        *log.lock().unwrap(),
+        [Duration::from_millis(100), Duration::from_secs(100)]
+    );
+}
+
+#[test]
+fn smp_version_negotiation() {
+    let client = MCUmgrClient::new_from_serial(EchoSerial::default());
+    assert_eq!(client.smp_version(), None);
+    client.os_echo("Hello").unwrap();
+    assert_eq!(client.smp_version(), Some(SmpVersion::V2));
+
+    let client = MCUmgrClient::new_from_serial(EchoSerial::new_smp_v1());
+    client.os_echo("Hello").unwrap();
+    assert_eq!(client.smp_version(), Some(SmpVersion::V1));
+    client.os_echo("Hello").unwrap();
+}
+
+#[test]
+fn smp_version_forced() {
+    let client = MCUmgrClient::new_from_serial(EchoSerial::new_smp_v1());
+    client.set_smp_version(Some(SmpVersion::V1));
+    client.os_echo("Hello").unwrap();
+    assert_eq!(client.smp_version(), Some(SmpVersion::V1));
+
+    // A device that answers with a different version than forced is an error
+    let client = MCUmgrClient::new_from_serial(EchoSerial::new_smp_v1());
+    client.set_smp_version(Some(SmpVersion::V2));
+    assert!(client.os_echo("Hello").is_err());
+}
+
+#[test]
+fn frame_observer() {
+    let events = Arc::new(Mutex::new(vec![]));
+
+    let client = MCUmgrClient::new_from_serial(EchoSerial::default());
+    client.add_frame_observer({
+        let events = events.clone();
+        move |event: &FrameEvent| {
+            events
+                .lock()
+                .unwrap()
+                .push((event.direction, event.header, event.cbor_diagnostic()))
+        }
+    });
+
+    client.os_echo("Hello").unwrap();
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+
+    let (direction, header, diagnostic) = &events[0];
+    assert_eq!(*direction, FrameDirection::Tx);
+    assert_eq!(header.op, FrameOp::Read);
+    assert_eq!(header.group_id, 0);
+    assert_eq!(header.command_id, 0);
+    assert_eq!(diagnostic, r#"{"d": "Hello"}"#);
+
+    let (direction, response_header, diagnostic) = &events[1];
+    assert_eq!(*direction, FrameDirection::Rx);
+    assert_eq!(response_header.op, FrameOp::ReadResponse);
+    assert_eq!(response_header.sequence_num, header.sequence_num);
+    assert_eq!(response_header.version, SmpVersion::V2);
+    assert_eq!(diagnostic, r#"{"r": "Hello"}"#);
+}
+
+#[test]
+fn link_stats() {
+    let client = MCUmgrClient::new_from_serial(EchoSerial::default());
+
+    let stats = client.link_stats();
+    assert_eq!(stats.frames_sent, 0);
+    assert_eq!(stats.round_trip, None);
+
+    client.os_echo("Hello world!").unwrap();
+    client.os_echo("x".repeat(1000)).unwrap();
+
+    let stats = client.link_stats();
+    assert_eq!(stats.frames_sent, 2);
+    assert_eq!(stats.frames_received, 2);
+    assert_eq!(stats.bytes_sent, stats.bytes_received);
+    assert_eq!(stats.ignored_frames, 0);
+    assert_eq!(stats.timeouts, 0);
+
+    let transport = stats.transport.unwrap();
+    assert!(transport.encoded_bytes_sent > stats.bytes_sent);
+    assert!(transport.encoded_bytes_received > stats.bytes_received);
+    assert_eq!(transport.crc_errors, 0);
+
+    let round_trip = stats.round_trip.unwrap();
+    assert_eq!(round_trip.count, 2);
+    assert!(round_trip.min <= round_trip.p50);
+    assert!(round_trip.p50 <= round_trip.p99);
+    assert_eq!(round_trip.p99, round_trip.max);
+
+    client.reset_link_stats();
+    let stats = client.link_stats();
+    assert_eq!(stats.frames_sent, 0);
+    assert_eq!(stats.transport.unwrap().encoded_bytes_sent, 0);
+    assert_eq!(stats.round_trip, None);
+}
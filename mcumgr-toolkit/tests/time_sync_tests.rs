@@ -0,0 +1,89 @@
+mod common;
+use std::time::Duration;
+
+use common::MockDevice;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::time_sync::{DriftError, HostClock},
+};
+
+#[test]
+fn os_sync_time() {
+    let mut device = MockDevice::new();
+    device.latency = Duration::from_millis(10);
+    let clock = device.host_clock.clone();
+    let client = MCUmgrClient::new_from_transport(device);
+
+    // The device clock is one hour behind, plus the latency of the set request
+    client
+        .os_set_datetime(clock.now() - chrono::TimeDelta::hours(1))
+        .unwrap();
+
+    let result = client.os_sync_time_with_clock(&clock).unwrap();
+
+    assert_eq!(result.round_trip_ms, 10.0);
+    assert!(
+        (result.offset_before_ms + 3_600_005.0).abs() < 1e-6,
+        "{result:?}"
+    );
+    assert!(result.residual_offset_ms.abs() < 1e-6, "{result:?}");
+}
+
+#[test]
+fn os_measure_drift() {
+    let mut device = MockDevice::new();
+    device.clock_rate = 1.1;
+    device.latency = Duration::from_millis(10);
+    let clock = device.host_clock.clone();
+    let client = MCUmgrClient::new_from_transport(device);
+    client.os_set_datetime(clock.now()).unwrap();
+
+    let mut progress_calls = 0;
+    let result = client
+        .os_measure_drift_with_clock(
+            &clock,
+            Duration::from_millis(300),
+            Duration::from_millis(20),
+            Some(&mut |_, _| {
+                progress_calls += 1;
+                true
+            }),
+        )
+        .unwrap();
+
+    // A sample every 30 ms: 20 ms interval plus 10 ms latency
+    assert_eq!(result.samples, 11, "{result:?}");
+    assert_eq!(progress_calls, result.samples);
+    assert!((result.duration_s - 0.3).abs() < 1e-9, "{result:?}");
+    assert!((result.offset_change_ms - 30.0).abs() < 1e-3, "{result:?}");
+    assert!((result.drift_ppm - 100_000.0).abs() < 10.0, "{result:?}");
+}
+
+#[test]
+fn os_measure_drift_cancel() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+    client
+        .os_set_datetime(chrono::Utc::now().naive_utc())
+        .unwrap();
+
+    let err = client
+        .os_measure_drift(
+            Duration::from_secs(10),
+            Duration::from_millis(10),
+            Some(&mut |_, _| false),
+        )
+        .unwrap_err();
+
+    assert!(matches!(err, DriftError::ProgressCallbackError), "{err:?}");
+}
+
+#[test]
+fn os_measure_drift_unsupported() {
+    let client = MCUmgrClient::new_from_transport(MockDevice::new());
+
+    let err = client
+        .os_measure_drift(Duration::from_millis(10), Duration::from_millis(5), None)
+        .unwrap_err();
+
+    assert!(matches!(err, DriftError::GetDatetimeFailed(_)), "{err:?}");
+}
@@ -0,0 +1,327 @@
+mod common;
+use common::{EchoSerial, MockDevice, SharedMockDevice, mcuboot_image};
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::{FirmwareUpdateError, FirmwareUpdateParams, MCUmgrClientError},
+    connection::{ExecuteError, FrameDirection, FrameEvent},
+    transport::{
+        ReceiveError,
+        fault::{CrcFaultSerial, Fault, FaultInjectingTransport},
+    },
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const FILE_NAME: &str = "/lfs/data.bin";
+
+fn device_with_file() -> (MockDevice, Vec<u8>) {
+    let data = (0..500).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+    let mut device = MockDevice::new();
+    device.add_file(FILE_NAME, data.clone());
+    (device, data)
+}
+
+fn is_receive_error(err: &MCUmgrClientError) -> bool {
+    matches!(
+        err,
+        MCUmgrClientError::ExecuteError(ExecuteError::ReceiveFailed(_))
+    )
+}
+
+/// Checks that a link fault was reported as such, instead of as a local error.
+fn is_link_fault(err: &MCUmgrClientError) -> bool {
+    matches!(
+        err,
+        MCUmgrClientError::ExecuteError(
+            ExecuteError::ReceiveFailed(_)
+                | ExecuteError::DecodeFailed(_)
+                | ExecuteError::ErrorResponse(_)
+        ) | MCUmgrClientError::UnexpectedOffset
+            | MCUmgrClientError::ChecksumMismatchOnDevice
+    )
+}
+
+#[test]
+fn image_upload_dropped_response() {
+    let firmware = mcuboot_image([0x22; 32], 1000);
+
+    // Frame 3 is the response to the second chunk
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(3, Fault::Drop)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let err = client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap_err();
+    assert!(is_receive_error(&err), "{err:?}");
+
+    client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap();
+}
+
+#[test]
+fn image_upload_dropped_request() {
+    let firmware = mcuboot_image([0x22; 32], 1000);
+
+    // Frame 2 is the second chunk
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(2, Fault::Drop)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let err = client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap_err();
+    assert!(is_receive_error(&err), "{err:?}");
+
+    client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap();
+}
+
+#[test]
+fn image_upload_duplicates_and_delays() {
+    let firmware = mcuboot_image([0x22; 32], 1000);
+
+    let transport = FaultInjectingTransport::scripted(
+        MockDevice::new(),
+        [
+            (1, Fault::Duplicate),
+            (2, Fault::Duplicate),
+            (5, Fault::Delay(Duration::from_millis(10))),
+        ],
+    );
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    // Stale responses get ignored and repeated chunks are idempotent
+    client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap();
+}
+
+#[test]
+fn image_upload_truncated_request() {
+    let firmware = mcuboot_image([0x22; 32], 1000);
+
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(2, Fault::Truncate)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let err = client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            MCUmgrClientError::ExecuteError(ExecuteError::ErrorResponse(_))
+        ),
+        "{err:?}"
+    );
+
+    client
+        .image_upload(&firmware, None, None, false, None)
+        .unwrap();
+}
+
+#[test]
+fn fs_file_download_truncated_response() {
+    let (device, data) = device_with_file();
+
+    // Frame 3 is the response to the second chunk
+    let transport = FaultInjectingTransport::scripted(device, [(3, Fault::Truncate)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let mut downloaded = vec![];
+    let err = client
+        .fs_file_download(FILE_NAME, &mut downloaded, None)
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            MCUmgrClientError::ExecuteError(ExecuteError::ReceiveFailed(
+                ReceiveError::UnexpectedResponse
+            ))
+        ),
+        "{err:?}"
+    );
+
+    let mut downloaded = vec![];
+    client
+        .fs_file_download(FILE_NAME, &mut downloaded, None)
+        .unwrap();
+    assert_eq!(downloaded, data);
+}
+
+#[test]
+fn fs_file_download_corrupted_response() {
+    // SMP frames carry no checksum, so a flipped payload bit may go unnoticed.
+    // Whatever bit gets flipped, the client must not panic and must recover.
+    for frame in [1, 3, 5, 7] {
+        let (device, data) = device_with_file();
+        let transport = FaultInjectingTransport::scripted(device, [(frame, Fault::Corrupt)]);
+        let client = MCUmgrClient::new_from_transport(transport);
+
+        let mut downloaded = vec![];
+        match client.fs_file_download(FILE_NAME, &mut downloaded, None) {
+            // The flipped bit was in the file content
+            Ok(()) => {
+                let flipped_bits = downloaded
+                    .iter()
+                    .zip(&data)
+                    .map(|(a, b)| (a ^ b).count_ones())
+                    .sum::<u32>();
+                assert_eq!(downloaded.len(), data.len(), "frame {frame}");
+                assert!(
+                    flipped_bits <= 1,
+                    "frame {frame}: {flipped_bits} bits differ"
+                );
+            }
+            Err(err) => assert!(is_link_fault(&err), "frame {frame}: {err:?}"),
+        }
+
+        let mut downloaded = vec![];
+        client
+            .fs_file_download(FILE_NAME, &mut downloaded, None)
+            .unwrap();
+        assert_eq!(downloaded, data);
+    }
+}
+
+#[test]
+fn firmware_update_random_faults() {
+    let firmware = mcuboot_image([0x22; 32], 2000);
+    let faults = [
+        Fault::Drop,
+        Fault::Delay(Duration::from_millis(1)),
+        Fault::Duplicate,
+        Fault::Truncate,
+        Fault::Corrupt,
+    ];
+
+    let mut succeeded = 0;
+    for seed in 0..50 {
+        let device = SharedMockDevice::new(MockDevice::new());
+        let transport = FaultInjectingTransport::random(device.clone(), seed, 0.05, faults);
+        let client = MCUmgrClient::new_from_transport(transport);
+
+        match client.firmware_update(&firmware, None, FirmwareUpdateParams::default(), None) {
+            Ok(()) => {
+                let device = device.0.lock().unwrap();
+                assert_eq!(device.upload, firmware, "seed {seed}");
+                assert_eq!(device.pending_hash, Some([0x22; 32]), "seed {seed}");
+                assert!(device.reset_count >= 1, "seed {seed}");
+                succeeded += 1;
+            }
+            Err(
+                FirmwareUpdateError::BootloaderDetectionFailed(err)
+                | FirmwareUpdateError::GetStateFailed(err)
+                | FirmwareUpdateError::ImageUploadFailed(err)
+                | FirmwareUpdateError::SetStateFailed(err)
+                | FirmwareUpdateError::RebootFailed(err),
+            ) => assert!(is_link_fault(&err), "seed {seed}: {err:?}"),
+            Err(err) => panic!("seed {seed}: {err:?}"),
+        }
+    }
+
+    assert!(succeeded > 0);
+    assert!(succeeded < 50);
+}
+
+#[test]
+fn serial_crc_error() {
+    let client =
+        MCUmgrClient::new_from_serial(CrcFaultSerial::scripted(EchoSerial::default(), [0]));
+
+    let err = client.os_echo("Hello world!").unwrap_err();
+    assert!(
+        matches!(
+            err,
+            MCUmgrClientError::ExecuteError(ExecuteError::ReceiveFailed(
+                ReceiveError::UnexpectedResponse
+            ))
+        ),
+        "{err:?}"
+    );
+
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+}
+
+#[test]
+fn serial_crc_error_long_packet() {
+    // Multi-line packets get corrupted in their last line
+    let msg = "x".repeat(500);
+    let client =
+        MCUmgrClient::new_from_serial(CrcFaultSerial::random(EchoSerial::default(), 0, 1.0));
+
+    assert!(client.os_echo(&msg).is_err());
+}
+
+#[test]
+fn link_stats_ignored_frames_and_timeouts() {
+    // The duplicate of frame 1 arrives while waiting for the second response,
+    // the third request gets dropped
+    let transport = FaultInjectingTransport::scripted(
+        MockDevice::new(),
+        [(1, Fault::Duplicate), (4, Fault::Drop)],
+    );
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    client.image_get_state().unwrap();
+    client.image_get_state().unwrap();
+    client.image_get_state().unwrap_err();
+
+    let stats = client.link_stats();
+    assert_eq!(stats.frames_sent, 3);
+    assert_eq!(stats.frames_received, 3);
+    assert_eq!(stats.ignored_frames, 1);
+    assert_eq!(stats.timeouts, 1);
+    assert_eq!(stats.transport, None);
+    assert_eq!(stats.round_trip.unwrap().count, 2);
+}
+
+#[test]
+fn frame_observer_reports_ignored_frames() {
+    // The duplicate of frame 1 arrives while waiting for the second response
+    let transport = FaultInjectingTransport::scripted(MockDevice::new(), [(1, Fault::Duplicate)]);
+    let client = MCUmgrClient::new_from_transport(transport);
+
+    let events = Arc::new(Mutex::new(vec![]));
+    client.add_frame_observer({
+        let events = events.clone();
+        move |event: &FrameEvent| {
+            events
+                .lock()
+                .unwrap()
+                .push((event.direction, event.header.sequence_num, event.ignored))
+        }
+    });
+
+    client.image_get_state().unwrap();
+    client.image_get_state().unwrap();
+
+    let events = events.lock().unwrap();
+    let seq = events[0].1;
+    assert_eq!(
+        *events,
+        [
+            (FrameDirection::Tx, seq, false),
+            (FrameDirection::Rx, seq, false),
+            (FrameDirection::Tx, seq.wrapping_add(1), false),
+            (FrameDirection::Rx, seq, true),
+            (FrameDirection::Rx, seq.wrapping_add(1), false),
+        ]
+    );
+}
+
+#[test]
+fn link_stats_crc_errors() {
+    let client =
+        MCUmgrClient::new_from_serial(CrcFaultSerial::scripted(EchoSerial::default(), [0, 2]));
+
+    client.os_echo("Hello").unwrap_err();
+    client.os_echo("Hello").unwrap();
+    client.os_echo("Hello").unwrap_err();
+
+    let stats = client.link_stats();
+    assert_eq!(stats.transport.unwrap().crc_errors, 2);
+    assert_eq!(stats.round_trip.unwrap().count, 1);
+}
@@ -0,0 +1,127 @@
+mod common;
+use common::{MockDevice, mcuboot_image};
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    client::{FirmwareUpdateError, FirmwareUpdateParams, MCUmgrClientError},
+    connection::ExecuteError,
+    transport::{
+        ReceiveError,
+        record::{RecordingTransport, ReplayTransport},
+    },
+};
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+/// A session log that can still be read after the client took ownership of it.
+#[derive(Clone, Default)]
+struct SharedLog(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn record_firmware_update(firmware: &[u8]) -> Vec<u8> {
+    let log = SharedLog::default();
+
+    let transport = RecordingTransport::new(MockDevice::new(), log.clone()).unwrap();
+    let client = MCUmgrClient::new_from_transport(transport);
+    client
+        .firmware_update(firmware, None, FirmwareUpdateParams::default(), None)
+        .unwrap();
+
+    log.0.lock().unwrap().clone()
+}
+
+#[test]
+fn record_and_replay() {
+    let firmware = mcuboot_image([0x22; 32], 1000);
+    let log = record_firmware_update(&firmware);
+
+    let client = MCUmgrClient::new_from_transport(ReplayTransport::new(log.as_slice()).unwrap());
+    client
+        .firmware_update(&firmware, None, FirmwareUpdateParams::default(), None)
+        .unwrap();
+
+    // The recorded session is finished
+    assert!(client.os_echo("Hello").is_err());
+}
+
+#[test]
+fn replay_request_mismatch() {
+    let log = record_firmware_update(&mcuboot_image([0x22; 32], 1000));
+
+    let client = MCUmgrClient::new_from_transport(ReplayTransport::new(log.as_slice()).unwrap());
+    let result = client.firmware_update(
+        mcuboot_image([0x33; 32], 1000),
+        None,
+        FirmwareUpdateParams::default(),
+        None,
+    );
+
+    assert!(matches!(
+        result,
+        Err(FirmwareUpdateError::ImageUploadFailed(_))
+    ));
+}
+
+#[test]
+fn replay_timeout() {
+    let log = "\
+# mcumgr-toolkit SMP session log v1
+0.000100 TX 0800000600002a00a16164624865";
+
+    let client = MCUmgrClient::new_from_transport(ReplayTransport::new(log.as_bytes()).unwrap());
+    let result = client.os_echo("He");
+
+    assert!(matches!(
+        result,
+        Err(MCUmgrClientError::ExecuteError(ExecuteError::ReceiveFailed(
+            ReceiveError::TransportError(e)
+        ))) if e.kind() == std::io::ErrorKind::TimedOut
+    ));
+}
+
+#[test]
+fn replay_invalid_log() {
+    assert!(ReplayTransport::new("0.1 XX 0000".as_bytes()).is_err());
+    assert!(ReplayTransport::new("0.1 TX zz".as_bytes()).is_err());
+    assert!(ReplayTransport::new("0.1 TX 00".as_bytes()).is_err());
+    assert!(ReplayTransport::new("# comment\n\n".as_bytes()).is_ok());
+}
+
+/// Regression test for the request sequence of `firmware_update`.
+///
+/// The session was recorded against [`MockDevice`] with `record_firmware_update`.
+#[test]
+fn replay_firmware_update_regression() {
+    let firmware = mcuboot_image([0x22; 32], 1000);
+
+    let log = include_str!("data/firmware_update.smplog");
+
+    let client = MCUmgrClient::new_from_transport(ReplayTransport::new(log.as_bytes()).unwrap());
+    client
+        .firmware_update(&firmware, None, FirmwareUpdateParams::default(), None)
+        .unwrap();
+}
+
+#[test]
+#[ignore = "only used to regenerate the regression test data"]
+fn generate_firmware_update_regression_data() {
+    let log = record_firmware_update(&mcuboot_image([0x22; 32], 1000));
+    std::fs::write(
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/firmware_update.smplog"
+        ),
+        log,
+    )
+    .unwrap();
+}
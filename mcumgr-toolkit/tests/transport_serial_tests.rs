@@ -1,7 +1,16 @@
 mod common;
-use common::LoopbackSerial;
+use common::{CrlfSerial, LoopbackSerial};
 
-use mcumgr_toolkit::transport::{Transport, serial::SerialTransport};
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use mcumgr_toolkit::transport::{
+    Transport,
+    serial::{SerialTransport, SerialTransportBuilder},
+};
 use proptest::prelude::*;
 
 fn create_loopback_transport() -> Box<dyn Transport> {
@@ -27,6 +36,28 @@ proptest! {
         assert_eq!(data, &data_received[8..], "Received data did not match! (len: {})", data.len());
     }
 
+    #[test]
+    fn test_chunking_reassembly_custom_settings(
+        mtu in 0usize..1024,
+        read_buffer_size in 0usize..256,
+        header in prop::array::uniform::<_, 8>(any::<u8>()),
+        data in prop::collection::vec(any::<u8>(), 0..2000),
+    ) {
+        // Verify chunking and reassembly works for any valid MTU
+
+        let mut transport = SerialTransportBuilder::new()
+            .mtu(mtu)
+            .read_buffer_size(read_buffer_size)
+            .build(LoopbackSerial::default());
+
+        transport.send_raw_frame(header, &data).unwrap();
+
+        let mut recv_buffer = [0u8; u16::MAX as usize];
+        let data_received = transport.recv_raw_frame(&mut recv_buffer).unwrap();
+
+        assert_eq!(header, &data_received[..8], "Received header did not match!");
+        assert_eq!(data, &data_received[8..], "Received data did not match! (len: {})", data.len());
+    }
 }
 
 #[test]
@@ -59,3 +90,47 @@ fn test_chunking_upper_limit() {
         data.len()
     );
 }
+
+#[test]
+fn test_console_sink() {
+    let mut serial = LoopbackSerial::default();
+    serial
+        .write_all(b"*** Booting Zephyr OS ***\r\n[00:00:00.001] <inf> main: ")
+        .unwrap();
+
+    let lines = Arc::new(Mutex::new(vec![]));
+    let mut transport = SerialTransport::new(serial);
+    transport.set_console_sink({
+        let lines = Arc::clone(&lines);
+        move |line| lines.lock().unwrap().push(line.to_string())
+    });
+
+    let header = [1, 2, 3, 4, 5, 6, 7, 8];
+    let data = [0x0a, 6, 9, 4, 20, 42];
+    transport.send_raw_frame(header, &data).unwrap();
+
+    let mut recv_buffer = [0u8; u16::MAX as usize];
+    let data_received = transport.recv_raw_frame(&mut recv_buffer).unwrap();
+    assert_eq!(header, &data_received[..8]);
+    assert_eq!(data, &data_received[8..]);
+
+    transport.receive_console(Duration::from_secs(1)).unwrap();
+
+    assert_eq!(*lines.lock().unwrap(), ["*** Booting Zephyr OS ***"]);
+}
+
+#[test]
+fn test_tolerate_crlf() {
+    let mut transport = SerialTransportBuilder::new()
+        .tolerate_crlf(true)
+        .build(CrlfSerial::default());
+
+    let header = [1, 2, 3, 4, 5, 6, 7, 8];
+    let data = vec![42u8; 1000];
+    transport.send_raw_frame(header, &data).unwrap();
+
+    let mut recv_buffer = [0u8; u16::MAX as usize];
+    let data_received = transport.recv_raw_frame(&mut recv_buffer).unwrap();
+    assert_eq!(header, &data_received[..8]);
+    assert_eq!(data, &data_received[8..]);
+}
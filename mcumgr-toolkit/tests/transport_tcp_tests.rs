@@ -0,0 +1,198 @@
+mod common;
+use common::serve_serial_echo;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    transport::{serial::ConfigurableBaudRate, tcp::TcpSerial},
+};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const NOP: u8 = 241;
+const OPT_ECHO: u8 = 1;
+const OPT_COM_PORT: u8 = 44;
+
+/// What the RFC 2217 server received besides data.
+#[derive(Default, Debug)]
+struct TelnetLog {
+    baud_rates: Vec<u32>,
+    refused_options: Vec<u8>,
+}
+
+/// The server side of an RFC 2217 connection.
+struct TelnetServer {
+    stream: TcpStream,
+    log: Arc<Mutex<TelnetLog>>,
+    pending: Vec<u8>,
+}
+
+impl TelnetServer {
+    fn new(mut stream: TcpStream, log: Arc<Mutex<TelnetLog>>) -> Self {
+        // Offer an option that the client does not support
+        stream.write_all(&[IAC, WILL, OPT_ECHO]).unwrap();
+
+        Self {
+            stream,
+            log,
+            pending: vec![],
+        }
+    }
+
+    /// Extracts the data from `pending`, leaving incomplete commands in it.
+    fn parse(&mut self) -> Vec<u8> {
+        let mut data = vec![];
+        let mut pos = 0;
+
+        while pos < self.pending.len() {
+            let rest = &self.pending[pos..];
+            match rest {
+                [IAC, IAC, ..] => {
+                    data.push(IAC);
+                    pos += 2;
+                }
+                [IAC, DONT, option, ..] => {
+                    self.log.lock().unwrap().refused_options.push(*option);
+                    pos += 3;
+                }
+                [IAC, DO | WILL | WONT, _, ..] => pos += 3,
+                [IAC, SB, ..] => {
+                    let Some(end) = rest.windows(2).position(|w| w == [IAC, SE]) else {
+                        break;
+                    };
+                    let mut content = vec![];
+                    let mut i = 2;
+                    while i < end {
+                        content.push(rest[i]);
+                        i += if rest[i] == IAC { 2 } else { 1 };
+                    }
+                    if let [OPT_COM_PORT, 1, b0, b1, b2, b3] = content[..] {
+                        let baud_rate = u32::from_be_bytes([b0, b1, b2, b3]);
+                        self.log.lock().unwrap().baud_rates.push(baud_rate);
+                    }
+                    pos += end + 2;
+                }
+                [IAC, _, ..] | [IAC] => break,
+                [byte, ..] => {
+                    data.push(*byte);
+                    pos += 1;
+                }
+                [] => unreachable!(),
+            }
+        }
+
+        self.pending.drain(..pos);
+        data
+    }
+}
+
+impl Read for TelnetServer {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let mut chunk = [0; 1024];
+            let num_read = self.stream.read(&mut chunk)?;
+            if num_read == 0 {
+                return Ok(0);
+            }
+            self.pending.extend_from_slice(&chunk[..num_read]);
+
+            let data = self.parse();
+            if !data.is_empty() {
+                buf[..data.len()].copy_from_slice(&data);
+                return Ok(data.len());
+            }
+        }
+    }
+}
+
+impl Write for TelnetServer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Commands in between the data must be filtered by the client
+        self.stream.write_all(&[IAC, NOP])?;
+        self.stream.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+fn start_server(rfc2217: Option<Arc<Mutex<TelnetLog>>>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        match rfc2217 {
+            Some(log) => serve_serial_echo(TelnetServer::new(stream, log)),
+            None => serve_serial_echo(stream),
+        }
+    });
+
+    addr
+}
+
+#[test]
+fn tcp_serial() {
+    let addr = start_server(None);
+
+    let client = MCUmgrClient::new_from_tcp_serial(addr, TIMEOUT).unwrap();
+
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+    let long_msg = "x".repeat(1000);
+    assert_eq!(client.os_echo(&long_msg).unwrap(), long_msg);
+}
+
+#[test]
+fn tcp_serial_baud_rate_requires_rfc2217() {
+    let addr = start_server(None);
+
+    let mut serial = TcpSerial::connect(addr, TIMEOUT).unwrap();
+    assert!(serial.set_baud_rate(115200).is_err());
+}
+
+#[test]
+fn tcp_serial_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let client = MCUmgrClient::new_from_tcp_serial(
+        listener.local_addr().unwrap(),
+        Duration::from_millis(100),
+    )
+    .unwrap();
+
+    assert!(client.os_echo("Hello").is_err());
+}
+
+#[test]
+fn rfc2217() {
+    let log = Arc::new(Mutex::new(TelnetLog::default()));
+    let addr = start_server(Some(log.clone()));
+
+    let mut serial = TcpSerial::connect_rfc2217(addr, TIMEOUT).unwrap();
+    serial.set_baud_rate(921600).unwrap();
+    // Contains an IAC byte that has to be escaped
+    serial.set_baud_rate(0x0001_c2ff).unwrap();
+
+    let client = MCUmgrClient::new_from_serial(serial);
+
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+    let long_msg = "x".repeat(1000);
+    assert_eq!(client.os_echo(&long_msg).unwrap(), long_msg);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.baud_rates, [921600, 0x0001_c2ff]);
+    assert_eq!(log.refused_options, [OPT_ECHO]);
+}
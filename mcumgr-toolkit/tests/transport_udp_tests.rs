@@ -0,0 +1,135 @@
+mod common;
+use ciborium::cbor;
+use common::EchoSerial;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    transport::{
+        serial::SerialTransport,
+        udp::{ProxyError, UdpProxy, UdpTransport},
+    },
+};
+use std::{net::UdpSocket, time::Duration};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Starts a proxy for an echo device in the background.
+fn start_proxy() -> std::net::SocketAddr {
+    let mut proxy =
+        UdpProxy::bind(SerialTransport::new(EchoSerial::default()), "127.0.0.1:0").unwrap();
+    let addr = proxy.local_addr().unwrap();
+
+    std::thread::spawn(move || proxy.run());
+
+    addr
+}
+
+/// An echo request with the given sequence number.
+fn echo_request(sequence_num: u8, msg: &str) -> Vec<u8> {
+    let mut payload = vec![];
+    ciborium::into_writer(&cbor!({ "d" => msg }).unwrap(), &mut payload).unwrap();
+
+    let mut frame = vec![(1 << 3) | 2, 0];
+    frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    frame.extend_from_slice(&[0, 0, sequence_num, 0]);
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+#[test]
+fn proxy_echo() {
+    let addr = start_proxy();
+
+    let client = MCUmgrClient::new_from_transport(UdpTransport::new(addr, TIMEOUT).unwrap());
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+    assert_eq!(client.os_echo("Hello again!").unwrap(), "Hello again!");
+}
+
+#[test]
+fn proxy_multiple_clients() {
+    let addr = start_proxy();
+
+    let threads = (0..4)
+        .map(|i| {
+            std::thread::spawn(move || {
+                let client =
+                    MCUmgrClient::new_from_transport(UdpTransport::new(addr, TIMEOUT).unwrap());
+                for j in 0..20 {
+                    let msg = format!("Client {i}, message {j}");
+                    assert_eq!(client.os_echo(&msg).unwrap(), msg);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn proxy_restores_sequence_numbers() {
+    let addr = start_proxy();
+
+    // Two clients that use the same sequence number
+    let sockets = [
+        UdpSocket::bind("127.0.0.1:0").unwrap(),
+        UdpSocket::bind("127.0.0.1:0").unwrap(),
+    ];
+    for (i, socket) in sockets.iter().enumerate() {
+        socket.set_read_timeout(Some(TIMEOUT)).unwrap();
+        socket
+            .send_to(&echo_request(42, &format!("Client {i}")), addr)
+            .unwrap();
+    }
+
+    for (i, socket) in sockets.iter().enumerate() {
+        let mut buffer = [0; 1024];
+        let len = socket.recv(&mut buffer).unwrap();
+        let response = &buffer[..len];
+
+        assert_eq!(response[0] & 0b111, 3);
+        assert_eq!(response[6], 42);
+
+        let payload: ciborium::Value = ciborium::from_reader(&response[8..]).unwrap();
+        assert_eq!(payload, cbor!({ "r" => format!("Client {i}") }).unwrap());
+    }
+}
+
+#[test]
+fn proxy_invalid_request() {
+    let mut proxy =
+        UdpProxy::bind(SerialTransport::new(EchoSerial::default()), "127.0.0.1:0").unwrap();
+    let addr = proxy.local_addr().unwrap();
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    socket.send_to(&[1, 2, 3], addr).unwrap();
+    assert!(matches!(
+        proxy.serve_one(),
+        Err(ProxyError::InvalidRequest(_))
+    ));
+
+    // Length field does not match the payload
+    let mut request = echo_request(1, "Hello");
+    request.push(0);
+    socket.send_to(&request, addr).unwrap();
+    assert!(matches!(
+        proxy.serve_one(),
+        Err(ProxyError::InvalidRequest(_))
+    ));
+
+    // The proxy is still usable
+    socket.send_to(&echo_request(2, "Hello"), addr).unwrap();
+    proxy.serve_one().unwrap();
+}
+
+#[test]
+fn udp_transport_timeout() {
+    // Nobody answers on this socket
+    let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let client = MCUmgrClient::new_from_transport(
+        UdpTransport::new(silent.local_addr().unwrap(), Duration::from_millis(100)).unwrap(),
+    );
+    assert!(client.os_echo("Hello").is_err());
+}
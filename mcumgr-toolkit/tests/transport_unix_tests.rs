@@ -0,0 +1,163 @@
+#![cfg(unix)]
+
+mod common;
+use ciborium::{Value, cbor};
+use common::serve_serial_echo;
+use mcumgr_toolkit::{
+    MCUmgrClient,
+    transport::{
+        ReceiveError, Transport,
+        unix::{UnixSocketFraming, UnixSocketTransport},
+    },
+};
+use std::{
+    io::{Read, Write},
+    os::{fd::AsRawFd, unix::net::UnixStream},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Answers raw SMP echo requests until the link gets closed.
+fn serve_raw_echo(mut link: UnixStream) {
+    loop {
+        let mut header = [0; 8];
+        if link.read_exact(&mut header).is_err() {
+            return;
+        }
+        let mut data = vec![0; u16::from_be_bytes([header[2], header[3]]).into()];
+        link.read_exact(&mut data).unwrap();
+
+        let request: Value = ciborium::from_reader(data.as_slice()).unwrap();
+        let msg = request.as_map().unwrap()[0].1.as_text().unwrap();
+
+        let mut payload = vec![];
+        ciborium::into_writer(&cbor!({ "r" => msg }).unwrap(), &mut payload).unwrap();
+
+        header[0] |= 1;
+        header[2..4].copy_from_slice(&(payload.len() as u16).to_be_bytes());
+        link.write_all(&header).unwrap();
+        link.write_all(&payload).unwrap();
+    }
+}
+
+#[test]
+fn unix_socket_raw() {
+    let (client_end, device_end) = UnixStream::pair().unwrap();
+    std::thread::spawn(move || serve_raw_echo(device_end));
+
+    let client = MCUmgrClient::new_from_transport(
+        UnixSocketTransport::new(client_end, UnixSocketFraming::Raw, TIMEOUT).unwrap(),
+    );
+
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+    let long_msg = "x".repeat(1000);
+    assert_eq!(client.os_echo(&long_msg).unwrap(), long_msg);
+}
+
+#[test]
+fn unix_socket_serial() {
+    let (client_end, device_end) = UnixStream::pair().unwrap();
+    std::thread::spawn(move || serve_serial_echo(device_end));
+
+    let client = MCUmgrClient::new_from_transport(
+        UnixSocketTransport::new(client_end, UnixSocketFraming::Serial, TIMEOUT).unwrap(),
+    );
+
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+    let long_msg = "x".repeat(1000);
+    assert_eq!(client.os_echo(&long_msg).unwrap(), long_msg);
+}
+
+#[test]
+fn unix_socket_timeout() {
+    let (client_end, _device_end) = UnixStream::pair().unwrap();
+
+    let client = MCUmgrClient::new_from_transport(
+        UnixSocketTransport::new(
+            client_end,
+            UnixSocketFraming::Raw,
+            Duration::from_millis(100),
+        )
+        .unwrap(),
+    );
+
+    assert!(client.os_echo("Hello").is_err());
+}
+
+#[test]
+fn unix_socket_raw_resumes_after_timeout() {
+    let (client_end, mut device_end) = UnixStream::pair().unwrap();
+    let mut transport = UnixSocketTransport::new(
+        client_end,
+        UnixSocketFraming::Raw,
+        Duration::from_millis(100),
+    )
+    .unwrap();
+
+    let frame = [1, 0, 0, 4, 0, 0, 0, 0, 0xa1, 0x61, 0x72, 0x60];
+    let mut buffer = [0; u16::MAX as usize];
+
+    // The frame arrives in pieces that are interrupted by timeouts
+    device_end.write_all(&frame[..5]).unwrap();
+    assert!(transport.recv_raw_frame(&mut buffer).is_err());
+    device_end.write_all(&frame[5..10]).unwrap();
+    assert!(transport.recv_raw_frame(&mut buffer).is_err());
+    device_end.write_all(&frame[10..]).unwrap();
+    assert_eq!(transport.recv_raw_frame(&mut buffer).unwrap(), frame);
+}
+
+#[test]
+fn unix_socket_raw_skips_oversized_frame() {
+    let (client_end, mut device_end) = UnixStream::pair().unwrap();
+    let mut transport =
+        UnixSocketTransport::new(client_end, UnixSocketFraming::Raw, TIMEOUT).unwrap();
+
+    let oversized = u16::MAX;
+    std::thread::spawn(move || {
+        let mut header = [1, 0, 0, 0, 0, 0, 0, 0];
+        header[2..4].copy_from_slice(&oversized.to_be_bytes());
+        device_end.write_all(&header).unwrap();
+        device_end.write_all(&vec![0xff; oversized.into()]).unwrap();
+        device_end
+            .write_all(&[1, 0, 0, 1, 0, 0, 0, 0, 0xa0])
+            .unwrap();
+        // Keep the socket open until the test is done
+        let _ = device_end.read(&mut [0]);
+    });
+
+    let mut buffer = [0; u16::MAX as usize];
+    assert!(matches!(
+        transport.recv_raw_frame(&mut buffer),
+        Err(ReceiveError::FrameTooBig)
+    ));
+    assert_eq!(
+        transport.recv_raw_frame(&mut buffer).unwrap(),
+        [1, 0, 0, 1, 0, 0, 0, 0, 0xa0]
+    );
+}
+
+#[test]
+fn pty() {
+    let pty = nix::pty::openpty(None, None).unwrap();
+    let path = format!("/proc/self/fd/{}", pty.slave.as_raw_fd());
+
+    let client = MCUmgrClient::new_from_pty(&path, TIMEOUT).unwrap();
+
+    let master = std::fs::File::from(pty.master);
+    std::thread::spawn(move || serve_serial_echo(master));
+
+    assert_eq!(client.os_echo("Hello world!").unwrap(), "Hello world!");
+    let long_msg = "x".repeat(1000);
+    assert_eq!(client.os_echo(&long_msg).unwrap(), long_msg);
+}
+
+#[test]
+fn pty_timeout() {
+    let pty = nix::pty::openpty(None, None).unwrap();
+    let path = format!("/proc/self/fd/{}", pty.slave.as_raw_fd());
+
+    let client = MCUmgrClient::new_from_pty(&path, Duration::from_millis(100)).unwrap();
+
+    assert!(client.os_echo("Hello").is_err());
+}